@@ -0,0 +1,51 @@
+pub mod state;
+pub mod store;
+pub mod transactions;
+
+use spl_token_2022::solana_program::pubkey::Pubkey;
+
+use comptoken_utils::events::ComptokenEvent;
+use state::IndexedState;
+use transactions::Transaction;
+
+#[derive(Debug)]
+pub struct IndexedEvent {
+    pub slot: u64,
+    pub signature: String,
+    // the position of the event within its transaction
+    pub event_index: u64,
+    pub event: ComptokenEvent,
+}
+
+impl IndexedEvent {
+    pub fn kind(&self) -> &'static str {
+        match self.event {
+            ComptokenEvent::ProofAccepted { .. } => "ProofAccepted",
+            ComptokenEvent::TokensMinted { .. } => "TokensMinted",
+            ComptokenEvent::DailyDistribution { .. } => "DailyDistribution",
+            ComptokenEvent::InterestClaimed { .. } => "InterestClaimed",
+            ComptokenEvent::HumanVerified { .. } => "HumanVerified",
+            ComptokenEvent::UserDataResized { .. } => "UserDataResized",
+            ComptokenEvent::AccountMigrated { .. } => "AccountMigrated",
+            ComptokenEvent::UserDataClosed { .. } => "UserDataClosed",
+        }
+    }
+}
+
+// `transactions` must be in the order they were executed. failed transactions are skipped
+pub fn index(program_id: &Pubkey, transactions: &[Transaction]) -> (Vec<IndexedEvent>, IndexedState) {
+    let mut state = IndexedState::default();
+    let mut events = Vec::new();
+    for transaction in transactions.iter().filter(|transaction| transaction.succeeded()) {
+        for (event_index, event) in transaction.events(program_id).into_iter().enumerate() {
+            state.apply(&event);
+            events.push(IndexedEvent {
+                slot: transaction.slot,
+                signature: transaction.signature.clone(),
+                event_index: event_index as u64,
+                event,
+            });
+        }
+    }
+    (events, state)
+}
@@ -0,0 +1,32 @@
+use std::{error::Error, fs::File, io::BufReader, process::ExitCode, str::FromStr};
+
+use rusqlite::Connection;
+use spl_token_2022::solana_program::pubkey::Pubkey;
+
+use comptoken_indexer::{index, store, transactions::read_transactions};
+use comptoken_utils::quiet_logs::silence_program_logs;
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    // replaying distributions runs the program's code
+    silence_program_logs();
+    let args: Vec<String> = std::env::args().collect();
+    let [_, program_id, transactions_path, database_path] = args.as_slice() else {
+        eprintln!("usage: comptoken-indexer <comptoken program id> <transactions.jsonl> <database.sqlite>");
+        return Ok(ExitCode::FAILURE);
+    };
+
+    let program_id = Pubkey::from_str(program_id)?;
+    let transactions = read_transactions(BufReader::new(File::open(transactions_path)?))?;
+    let (events, state) = index(&program_id, &transactions);
+    store::write(&mut Connection::open(database_path)?, &events, &state)?;
+
+    println!("indexed {} events from {} transactions", events.len(), transactions.len());
+    let mismatches: Vec<_> = state.distributions.iter().filter(|distribution| !distribution.matches_replay).collect();
+    for distribution in &mismatches {
+        eprintln!(
+            "distribution at {} does not match the replayed distribution: {:?}",
+            distribution.record.distribution_time, distribution.replayed
+        );
+    }
+    Ok(if mismatches.is_empty() { ExitCode::SUCCESS } else { ExitCode::FAILURE })
+}
@@ -0,0 +1,296 @@
+use std::collections::BTreeMap;
+
+use spl_token_2022::solana_program::pubkey::Pubkey;
+
+use comptoken::global_data::daily_distribution_data::{
+    DailyDistributionData, DailyDistributionValues, HistoricDistribution, HISTORY_SIZE,
+};
+use comptoken_utils::{distribution_record::DistributionRecord, events::ComptokenEvent, SEC_PER_DAY};
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct UserState {
+    pub proofs: u64,
+    pub mined: u64,
+    pub interest_claimed: u64,
+    pub ubi_claimed: u64,
+    pub future_ubi_received: u64,
+    pub is_verified_human: bool,
+}
+
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct BankBalances {
+    pub interest_bank: u64,
+    pub verified_human_ubi_bank: u64,
+    pub future_ubi_bank: u64,
+}
+
+#[derive(Debug)]
+pub struct IndexedDistribution {
+    pub record: DistributionRecord,
+    // what the program's own `DailyDistributionData` logic distributes, given the indexed state
+    pub replayed: DailyDistributionValues,
+    pub matches_replay: bool,
+}
+
+// comptoken state rebuilt from events alone
+#[derive(Debug)]
+pub struct IndexedState {
+    pub supply: u64,
+    pub daily_distribution_data: Box<DailyDistributionData>,
+    pub banks: BankBalances,
+    // keyed by comptoken token account
+    pub users: BTreeMap<Pubkey, UserState>,
+    // keyed by user data account
+    pub user_data_sizes: BTreeMap<Pubkey, u64>,
+    pub distributions: Vec<IndexedDistribution>,
+}
+
+impl Default for IndexedState {
+    fn default() -> Self {
+        Self {
+            supply: 0,
+            daily_distribution_data: Box::new(DailyDistributionData {
+                yesterday_supply: 0,
+                high_water_mark: 0,
+                last_daily_distribution_time: 0,
+                verified_humans: 0,
+                oldest_historic_index: 0,
+                historic_distributions: [HistoricDistribution::default(); HISTORY_SIZE],
+            }),
+            banks: BankBalances::default(),
+            users: BTreeMap::new(),
+            user_data_sizes: BTreeMap::new(),
+            distributions: Vec::new(),
+        }
+    }
+}
+
+impl IndexedState {
+    pub fn apply(&mut self, event: &ComptokenEvent) {
+        match *event {
+            ComptokenEvent::ProofAccepted { user_comptoken_wallet, .. } => {
+                self.users.entry(user_comptoken_wallet).or_default().proofs += 1;
+            }
+            ComptokenEvent::TokensMinted { destination, amount } => {
+                self.supply += amount;
+                self.users.entry(destination).or_default().mined += amount;
+            }
+            ComptokenEvent::DailyDistribution {
+                distribution_time,
+                interest_distribution,
+                ubi_for_verified_humans,
+                future_ubi_distribution,
+                crank_reward,
+                supply,
+                high_water_mark,
+            } => {
+                let distribution = DailyDistributionValues {
+                    high_water_mark_increase: high_water_mark
+                        .saturating_sub(self.daily_distribution_data.high_water_mark),
+                    interest_distribution,
+                    ubi_for_verified_humans,
+                    future_ubi_distribution,
+                    crank_reward,
+                };
+                self.apply_daily_distribution(distribution_time, distribution, supply, high_water_mark);
+            }
+            ComptokenEvent::InterestClaimed { user_comptoken_wallet, interest, ubi } => {
+                self.banks.interest_bank = self.banks.interest_bank.saturating_sub(interest);
+                self.banks.verified_human_ubi_bank = self.banks.verified_human_ubi_bank.saturating_sub(ubi);
+                let user = self.users.entry(user_comptoken_wallet).or_default();
+                user.interest_claimed += interest;
+                user.ubi_claimed += ubi;
+            }
+            ComptokenEvent::HumanVerified { user_comptoken_wallet, future_ubi } => {
+                self.banks.future_ubi_bank = self.banks.future_ubi_bank.saturating_sub(future_ubi);
+                self.daily_distribution_data.verified_humans += 1;
+                let user = self.users.entry(user_comptoken_wallet).or_default();
+                user.is_verified_human = true;
+                user.future_ubi_received += future_ubi;
+            }
+            ComptokenEvent::UserDataResized { user_data, new_size } => {
+                self.user_data_sizes.insert(user_data, new_size);
+            }
+            // only user data sizes are tracked, the global data can be migrated too
+            ComptokenEvent::AccountMigrated { account, new_size, .. } => {
+                if let Some(size) = self.user_data_sizes.get_mut(&account) {
+                    *size = new_size;
+                }
+            }
+            ComptokenEvent::UserDataClosed {
+                user_comptoken_wallet, user_data, forfeited_verified_human, ..
+            } => {
+                self.user_data_sizes.remove(&user_data);
+                if forfeited_verified_human {
+                    self.daily_distribution_data.verified_humans -= 1;
+                    self.users.entry(user_comptoken_wallet).or_default().is_verified_human = false;
+                }
+            }
+        }
+    }
+
+    // `supply` and `high_water_mark` are the program's, once `distribution` is minted
+    fn apply_daily_distribution(
+        &mut self, distribution_time: i64, distribution: DailyDistributionValues, supply: u64, high_water_mark: u64,
+    ) {
+        let data = &mut self.daily_distribution_data;
+        // the program is initialized the day before its first distribution
+        if self.distributions.is_empty() {
+            data.last_daily_distribution_time = distribution_time - SEC_PER_DAY;
+        }
+        // burns don't emit events, so the supply the program distributed from is only known from the event
+        self.supply = supply.saturating_sub(distribution.total_distributed());
+        let replayed = data.distribute(distribution_time, self.supply, self.banks.future_ubi_bank);
+        // swept dust (see `BankLiabilities::daily_distribution`) is subtracted from the interest and verified human
+        // ubi, so those can only be smaller than the replayed values
+        let matches_replay = distribution.interest_distribution <= replayed.interest_distribution
+            && distribution.ubi_for_verified_humans <= replayed.ubi_for_verified_humans
+            && distribution.future_ubi_distribution == replayed.future_ubi_distribution
+            && distribution.high_water_mark_increase == replayed.high_water_mark_increase;
+        // use the program's values, so one mismatch does not carry over into the following days
+        data.yesterday_supply = supply;
+        data.high_water_mark = high_water_mark;
+
+        self.distributions.push(IndexedDistribution {
+            record: data.distribution_record(self.supply, &distribution),
+            replayed,
+            matches_replay,
+        });
+        self.supply = supply;
+        self.banks.interest_bank += distribution.interest_distribution;
+        self.banks.verified_human_ubi_bank += distribution.ubi_for_verified_humans;
+        self.banks.future_ubi_bank += distribution.future_ubi_distribution;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const START_TIME: i64 = 1_721_865_600;
+
+    #[test]
+    fn test_replay_matches_program() {
+        // the program's view, advanced independently of the indexer
+        let mut program = IndexedState::default();
+        program.daily_distribution_data.last_daily_distribution_time = START_TIME;
+        let user = Pubkey::new_unique();
+
+        let mut state = IndexedState::default();
+        let mut supply = 0;
+        let mut future_ubi_bank = 0;
+        for day in 1..=3 {
+            let mined = ComptokenEvent::TokensMinted { destination: user, amount: 10_000 * day };
+            state.apply(&mined);
+            supply += 10_000 * day;
+
+            let distribution_time = START_TIME + day as i64 * SEC_PER_DAY;
+            let values = program.daily_distribution_data.distribute(distribution_time, supply, future_ubi_bank);
+            supply += values.total_distributed();
+            future_ubi_bank += values.future_ubi_distribution;
+
+            state.apply(&ComptokenEvent::DailyDistribution {
+                distribution_time,
+                interest_distribution: values.interest_distribution,
+                ubi_for_verified_humans: values.ubi_for_verified_humans,
+                future_ubi_distribution: values.future_ubi_distribution,
+                crank_reward: 0,
+                supply,
+                high_water_mark: program.daily_distribution_data.high_water_mark,
+            });
+            let indexed = state.distributions.last().unwrap();
+            assert!(indexed.matches_replay);
+            assert_eq!(indexed.replayed, values);
+            assert_eq!(indexed.record.distribution_time, distribution_time);
+        }
+
+        assert_eq!(state.supply, supply);
+        assert_eq!(state.banks.future_ubi_bank, future_ubi_bank);
+        assert_eq!(state.daily_distribution_data.high_water_mark, program.daily_distribution_data.high_water_mark);
+        assert_eq!(state.users[&user].mined, 60_000);
+
+        state.apply(&ComptokenEvent::InterestClaimed { user_comptoken_wallet: user, interest: 3, ubi: 0 });
+        assert_eq!(state.users[&user].interest_claimed, 3);
+        assert_eq!(
+            state.banks.interest_bank,
+            state.distributions.iter().map(|d| d.record.interest_distribution).sum::<u64>() - 3
+        );
+    }
+
+    #[test]
+    fn test_replay_mismatch() {
+        let mut state = IndexedState::default();
+        state.apply(&ComptokenEvent::TokensMinted { destination: Pubkey::new_unique(), amount: 10_000 });
+        // more interest than the program's logic could have distributed
+        state.apply(&ComptokenEvent::DailyDistribution {
+            distribution_time: START_TIME,
+            interest_distribution: u64::MAX / 4,
+            ubi_for_verified_humans: 0,
+            future_ubi_distribution: 0,
+            crank_reward: 0,
+            supply: 10_000 + u64::MAX / 4,
+            high_water_mark: 0,
+        });
+        assert!(!state.distributions[0].matches_replay);
+    }
+
+    #[test]
+    fn test_replay_burn() {
+        let mut program = IndexedState::default();
+        program.daily_distribution_data.last_daily_distribution_time = START_TIME;
+        let user = Pubkey::new_unique();
+
+        let mut state = IndexedState::default();
+        let mut supply = 0;
+        for day in 1..=2 {
+            state.apply(&ComptokenEvent::TokensMinted { destination: user, amount: 10_000 });
+            supply += 10_000;
+            if day == 2 {
+                // burning doesn't go through the program, so there is no event for it
+                supply -= 4_000;
+            }
+
+            let distribution_time = START_TIME + day * SEC_PER_DAY;
+            let values = program.daily_distribution_data.distribute(distribution_time, supply, 0);
+            let pre_distribution_supply = supply;
+            supply += values.total_distributed();
+
+            state.apply(&ComptokenEvent::DailyDistribution {
+                distribution_time,
+                interest_distribution: values.interest_distribution,
+                ubi_for_verified_humans: values.ubi_for_verified_humans,
+                future_ubi_distribution: values.future_ubi_distribution,
+                crank_reward: 0,
+                supply,
+                high_water_mark: program.daily_distribution_data.high_water_mark,
+            });
+            let indexed = state.distributions.last().unwrap();
+            assert!(indexed.matches_replay, "the burn is accounted for before the replay");
+            assert_eq!(indexed.replayed, values);
+            assert_eq!(indexed.record.supply, pre_distribution_supply);
+            assert_eq!(indexed.record.high_water_mark, program.daily_distribution_data.high_water_mark);
+        }
+
+        assert_eq!(state.supply, supply);
+        assert_eq!(state.daily_distribution_data.yesterday_supply, program.daily_distribution_data.yesterday_supply);
+        assert_eq!(state.daily_distribution_data.high_water_mark, program.daily_distribution_data.high_water_mark);
+    }
+
+    #[test]
+    fn test_user_data_closed() {
+        let mut state = IndexedState::default();
+        let (user, user_data) = (Pubkey::new_unique(), Pubkey::new_unique());
+        state.apply(&ComptokenEvent::HumanVerified { user_comptoken_wallet: user, future_ubi: 0 });
+        state.apply(&ComptokenEvent::UserDataResized { user_data, new_size: 136 });
+
+        state.apply(&ComptokenEvent::UserDataClosed {
+            user_comptoken_wallet: user,
+            user_data,
+            lamports: 1_837_440,
+            forfeited_verified_human: true,
+        });
+        assert_eq!(state.daily_distribution_data.verified_humans, 0);
+        assert!(!state.users[&user].is_verified_human);
+        assert!(state.user_data_sizes.is_empty());
+    }
+}
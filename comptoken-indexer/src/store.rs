@@ -0,0 +1,167 @@
+use rusqlite::{params, Connection};
+
+use crate::{state::IndexedState, IndexedEvent};
+
+// the database is rebuilt from scratch on every run, since the state can only be reconstructed from the full history
+const SCHEMA: &str = "
+DROP TABLE IF EXISTS events;
+DROP TABLE IF EXISTS distributions;
+DROP TABLE IF EXISTS users;
+DROP TABLE IF EXISTS user_data;
+DROP TABLE IF EXISTS global_data;
+
+CREATE TABLE events (
+    slot INTEGER NOT NULL,
+    signature TEXT NOT NULL,
+    event_index INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    data BLOB NOT NULL,
+    PRIMARY KEY (signature, event_index)
+);
+CREATE TABLE distributions (
+    distribution_time INTEGER PRIMARY KEY,
+    supply INTEGER NOT NULL,
+    high_water_mark INTEGER NOT NULL,
+    high_water_mark_increase INTEGER NOT NULL,
+    interest_distribution INTEGER NOT NULL,
+    ubi_for_verified_humans INTEGER NOT NULL,
+    future_ubi_distribution INTEGER NOT NULL,
+    crank_reward INTEGER NOT NULL,
+    verified_humans INTEGER NOT NULL,
+    interest_rate REAL NOT NULL,
+    ubi INTEGER NOT NULL,
+    matches_replay INTEGER NOT NULL
+);
+CREATE TABLE users (
+    comptoken_wallet TEXT PRIMARY KEY,
+    proofs INTEGER NOT NULL,
+    mined INTEGER NOT NULL,
+    interest_claimed INTEGER NOT NULL,
+    ubi_claimed INTEGER NOT NULL,
+    future_ubi_received INTEGER NOT NULL,
+    is_verified_human INTEGER NOT NULL
+);
+CREATE TABLE user_data (
+    address TEXT PRIMARY KEY,
+    size INTEGER NOT NULL
+);
+CREATE TABLE global_data (
+    supply INTEGER NOT NULL,
+    yesterday_supply INTEGER NOT NULL,
+    high_water_mark INTEGER NOT NULL,
+    last_daily_distribution_time INTEGER NOT NULL,
+    verified_humans INTEGER NOT NULL,
+    interest_bank INTEGER NOT NULL,
+    verified_human_ubi_bank INTEGER NOT NULL,
+    future_ubi_bank INTEGER NOT NULL
+);
+";
+
+pub fn write(connection: &mut Connection, events: &[IndexedEvent], state: &IndexedState) -> rusqlite::Result<()> {
+    let transaction = connection.transaction()?;
+    transaction.execute_batch(SCHEMA)?;
+
+    let mut insert_event = transaction.prepare("INSERT INTO events VALUES (?1, ?2, ?3, ?4, ?5)")?;
+    for event in events {
+        insert_event.execute(params![
+            event.slot,
+            event.signature,
+            event.event_index,
+            event.kind(),
+            event.event.to_bytes()
+        ])?;
+    }
+    drop(insert_event);
+
+    let mut insert_distribution =
+        transaction.prepare("INSERT INTO distributions VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)")?;
+    for distribution in &state.distributions {
+        let record = &distribution.record;
+        insert_distribution.execute(params![
+            record.distribution_time,
+            record.supply,
+            record.high_water_mark,
+            record.high_water_mark_increase,
+            record.interest_distribution,
+            record.ubi_for_verified_humans,
+            record.future_ubi_distribution,
+            record.crank_reward,
+            record.verified_humans,
+            record.interest_rate,
+            record.ubi,
+            distribution.matches_replay,
+        ])?;
+    }
+    drop(insert_distribution);
+
+    let mut insert_user = transaction.prepare("INSERT INTO users VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)")?;
+    for (comptoken_wallet, user) in &state.users {
+        insert_user.execute(params![
+            comptoken_wallet.to_string(),
+            user.proofs,
+            user.mined,
+            user.interest_claimed,
+            user.ubi_claimed,
+            user.future_ubi_received,
+            user.is_verified_human,
+        ])?;
+    }
+    drop(insert_user);
+
+    let mut insert_user_data = transaction.prepare("INSERT INTO user_data VALUES (?1, ?2)")?;
+    for (address, size) in &state.user_data_sizes {
+        insert_user_data.execute(params![address.to_string(), size])?;
+    }
+    drop(insert_user_data);
+
+    let data = &state.daily_distribution_data;
+    transaction.execute(
+        "INSERT INTO global_data VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        params![
+            state.supply,
+            data.yesterday_supply,
+            data.high_water_mark,
+            data.last_daily_distribution_time,
+            data.verified_humans,
+            state.banks.interest_bank,
+            state.banks.verified_human_ubi_bank,
+            state.banks.future_ubi_bank,
+        ],
+    )?;
+
+    transaction.commit()
+}
+
+#[cfg(test)]
+mod test {
+    use spl_token_2022::solana_program::pubkey::Pubkey;
+
+    use comptoken_utils::events::ComptokenEvent;
+
+    use super::*;
+
+    #[test]
+    fn test_write() {
+        let user = Pubkey::new_unique();
+        let event = ComptokenEvent::TokensMinted { destination: user, amount: 10_000 };
+        let mut state = IndexedState::default();
+        state.apply(&event);
+        let events = [IndexedEvent { slot: 1, signature: "sig".to_string(), event_index: 0, event }];
+
+        let mut connection = Connection::open_in_memory().unwrap();
+        // writing twice replaces the previous contents
+        write(&mut connection, &events, &state).unwrap();
+        write(&mut connection, &events, &state).unwrap();
+
+        let (kind, count): (String, u64) = connection
+            .query_row("SELECT kind, COUNT(*) FROM events", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!((kind.as_str(), count), ("TokensMinted", 1));
+        let mined: u64 = connection
+            .query_row("SELECT mined FROM users WHERE comptoken_wallet = ?1", [user.to_string()], |row| row.get(0))
+            .unwrap();
+        assert_eq!(mined, 10_000);
+        let supply: u64 = connection.query_row("SELECT supply FROM global_data", [], |row| row.get(0)).unwrap();
+        assert_eq!(supply, 10_000);
+    }
+}
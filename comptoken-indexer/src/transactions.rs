@@ -0,0 +1,100 @@
+use std::{error::Error, io::BufRead};
+
+use serde::Deserialize;
+use spl_token_2022::solana_program::pubkey::Pubkey;
+
+use comptoken_utils::events::ComptokenEvent;
+
+// one transaction per line of the input file. the fields are those of the rpc's `getTransaction` response, flattened:
+// {"slot": 1, "signature": "...", "err": null, "logs": ["Program ... invoke [1]", ...]}
+#[derive(Debug, Deserialize)]
+pub struct Transaction {
+    pub slot: u64,
+    pub signature: String,
+    #[serde(default)]
+    pub err: Option<serde_json::Value>,
+    pub logs: Vec<String>,
+}
+
+impl Transaction {
+    pub fn succeeded(&self) -> bool {
+        self.err.is_none()
+    }
+
+    // only events logged while `program_id` is the innermost running program are returned, so other programs cannot
+    // forge events
+    pub fn events(&self, program_id: &Pubkey) -> Vec<ComptokenEvent> {
+        let program_id = program_id.to_string();
+        let mut invocations: Vec<&str> = Vec::new();
+        let mut events = Vec::new();
+        for log in &self.logs {
+            if let Some((program, _)) = log.strip_prefix("Program ").and_then(|log| log.split_once(" invoke [")) {
+                invocations.push(program);
+            } else if invocations.last().is_some_and(|program| is_invocation_end(log, program)) {
+                invocations.pop();
+            } else if invocations.last() == Some(&program_id.as_str()) {
+                events.extend(ComptokenEvent::from_log(log));
+            }
+        }
+        events
+    }
+}
+
+fn is_invocation_end(log: &str, program: &str) -> bool {
+    log.strip_prefix("Program ")
+        .and_then(|log| log.strip_prefix(program))
+        .is_some_and(|result| result == " success" || result.starts_with(" failed"))
+}
+
+pub fn read_transactions(reader: impl BufRead) -> Result<Vec<Transaction>, Box<dyn Error>> {
+    reader
+        .lines()
+        .filter(|line| !line.as_ref().is_ok_and(|line| line.trim().is_empty()))
+        .map(|line| Ok(serde_json::from_str(&line?)?))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use base64::{engine::general_purpose::STANDARD, Engine};
+
+    use comptoken_utils::events::EVENT_TAG;
+
+    use super::*;
+
+    fn event_log(event: &ComptokenEvent) -> String {
+        format!("Program data: {} {}", STANDARD.encode(EVENT_TAG), STANDARD.encode(event.to_bytes()))
+    }
+
+    #[test]
+    fn test_events_only_from_program() {
+        let program_id = Pubkey::new_unique();
+        let other_program_id = Pubkey::new_unique();
+        let event = ComptokenEvent::TokensMinted { destination: Pubkey::new_unique(), amount: 10_000 };
+        let forged_event = ComptokenEvent::TokensMinted { destination: Pubkey::new_unique(), amount: 1_000_000 };
+
+        let input = serde_json::json!({
+            "slot": 5,
+            "signature": "sig",
+            "err": null,
+            "logs": [
+                event_log(&forged_event),
+                format!("Program {program_id} invoke [1]"),
+                "Program log: Mint New Comptokens",
+                format!("Program {other_program_id} invoke [2]"),
+                event_log(&forged_event),
+                format!("Program {other_program_id} success"),
+                event_log(&event),
+                format!("Program {program_id} consumed 5000 of 200000 compute units"),
+                format!("Program {program_id} success"),
+                event_log(&forged_event),
+            ],
+        })
+        .to_string();
+
+        let transactions = read_transactions(format!("{input}\n\n").as_bytes()).unwrap();
+        assert_eq!(transactions.len(), 1);
+        assert!(transactions[0].succeeded());
+        assert_eq!(transactions[0].events(&program_id), vec![event]);
+    }
+}
@@ -0,0 +1,692 @@
+use solana_program_test::{ProgramTest, ProgramTestContext};
+use std::path::PathBuf;
+
+use solana_sdk::{
+    account::Account,
+    bpf_loader_upgradeable::{self, UpgradeableLoaderState},
+    clock::Clock,
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    rent::Rent,
+    signature::{Keypair, Signer},
+    system_instruction, system_program, sysvar,
+    transaction::{Transaction, TransactionError},
+};
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use spl_token_2022::{
+    extension::{BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    state::{Account as TokenAccount, Mint},
+};
+use spl_token_metadata_interface::state::{Field, TokenMetadata};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use spl_type_length_value::state::TlvStateBorrowed;
+
+use comptoken::{
+    constants::{
+        COMPTO_FUTURE_UBI_BANK_ACCOUNT_SEED, COMPTO_GLOBAL_DATA_ACCOUNT_SEED, COMPTO_INTEREST_BANK_ACCOUNT_SEED,
+        COMPTO_MINT_SEED, COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_SEED, MINT_DECIMALS,
+    },
+    global_data::GlobalData,
+};
+use comptoken_utils::{
+    distribution_record::{DistributionRecord, DISTRIBUTION_RECORD_SEED},
+    events::ComptokenEvent,
+    exempt_accounts::{ExemptAccounts, EXEMPT_ACCOUNTS_SEED},
+    user_data::UserData,
+    SEC_PER_DAY,
+};
+
+// MAGIC NUMBER: remain consistent with common.js
+pub const DEFAULT_START_TIME: i64 = 1_721_940_656;
+// MAGIC NUMBER: remain consistent with common.js. the offset `start_initialized` initializes the program with
+pub const DAY_BOUNDARY_OFFSET: i64 = 0;
+
+// MAGIC NUMBER: remain consistent with the instruction numbers in comptoken.rs
+const INITIALIZE_COMPTOKEN_PROGRAM: u8 = 2;
+const CREATE_USER_DATA_ACCOUNT: u8 = 3;
+const DAILY_DISTRIBUTION_EVENT: u8 = 4;
+const GET_OWED_COMPTOKENS: u8 = 6;
+const GROW_USER_DATA_ACCOUNT: u8 = 7;
+const MIGRATE_GLOBAL_DATA: u8 = 10;
+const MIGRATE_USER_DATA: u8 = 11;
+const CLOSE_USER_DATA_ACCOUNT: u8 = 12;
+const SHRINK_USER_DATA_ACCOUNT: u8 = 13;
+const UPDATE_METADATA_FIELD: u8 = 14;
+const REMOVE_METADATA_KEY: u8 = 15;
+const UPDATE_EXTRA_ACCOUNT_METAS: u8 = 16;
+const ADD_EXEMPT_ACCOUNT: u8 = 17;
+const REMOVE_EXEMPT_ACCOUNT: u8 = 18;
+const MIGRATE_LEGACY_GLOBAL_DATA: u8 = 19;
+const SET_ADMIN: u8 = 20;
+const MIGRATE_EXTRA_ACCOUNT_METAS: u8 = 21;
+const TEST: u8 = 255;
+
+pub struct Addresses {
+    pub comptoken_program: Pubkey,
+    pub comptoken_program_data: Pubkey,
+    pub transfer_hook_program: Pubkey,
+    pub mint: Pubkey,
+    pub global_data: Pubkey,
+    pub interest_bank: Pubkey,
+    pub verified_human_ubi_bank: Pubkey,
+    pub future_ubi_bank: Pubkey,
+    pub extra_account_metas: Pubkey,
+    pub exempt_accounts: Pubkey,
+}
+
+impl Addresses {
+    // the programs don't depend on their addresses, so every test gets its own
+    pub fn new_unique() -> Self {
+        let comptoken_program = Pubkey::new_unique();
+        let transfer_hook_program = Pubkey::new_unique();
+
+        let pda = |seed: &[u8]| Pubkey::find_program_address(&[seed], &comptoken_program).0;
+        let mint = pda(COMPTO_MINT_SEED);
+        Self {
+            comptoken_program,
+            comptoken_program_data: bpf_loader_upgradeable::get_program_data_address(&comptoken_program),
+            transfer_hook_program,
+            mint,
+            global_data: pda(COMPTO_GLOBAL_DATA_ACCOUNT_SEED),
+            interest_bank: pda(COMPTO_INTEREST_BANK_ACCOUNT_SEED),
+            verified_human_ubi_bank: pda(COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_SEED),
+            future_ubi_bank: pda(COMPTO_FUTURE_UBI_BANK_ACCOUNT_SEED),
+            extra_account_metas: Pubkey::find_program_address(
+                &[b"extra-account-metas", mint.as_ref()],
+                &transfer_hook_program,
+            )
+            .0,
+            exempt_accounts: pda(EXEMPT_ACCOUNTS_SEED),
+        }
+    }
+
+    // also used for the banks, whose user data accounts don't actually exist
+    pub fn user_data(&self, comptoken_token_account: &Pubkey) -> Pubkey {
+        Pubkey::find_program_address(&[comptoken_token_account.as_ref()], &self.comptoken_program).0
+    }
+
+    pub fn distribution_record(&self, distribution_time: i64) -> Pubkey {
+        Pubkey::find_program_address(
+            &[DISTRIBUTION_RECORD_SEED, &distribution_time.to_le_bytes()],
+            &self.comptoken_program,
+        )
+        .0
+    }
+}
+
+pub struct User {
+    pub wallet: Keypair,
+    pub comptoken_token_account: Pubkey,
+}
+
+pub struct TestContext {
+    pub context: ProgramTestContext,
+    pub addresses: Addresses,
+    // of the comptoken program, so the only one who may initialize it and choose its admin
+    pub upgrade_authority: Keypair,
+}
+
+impl TestContext {
+    // loads comptoken.so and comptoken_transfer_hook.so from SBF_OUT_DIR. the clock starts at `DEFAULT_START_TIME`,
+    // and the comptoken program (and so the mint) is not initialized yet
+    pub async fn start() -> Self {
+        let addresses = Addresses::new_unique();
+        let upgrade_authority = Keypair::new();
+        let mut program_test = ProgramTest::default();
+        add_upgradeable_program(&mut program_test, "comptoken", &addresses, &upgrade_authority.pubkey());
+        program_test.add_program("comptoken_transfer_hook", addresses.transfer_hook_program, None);
+
+        let mut test = Self {
+            context: program_test.start_with_context().await,
+            addresses,
+            upgrade_authority,
+        };
+        test.set_time(DEFAULT_START_TIME).await;
+        test
+    }
+
+    // starts and initializes the comptoken program. the upgrade authority then makes the payer the admin, so the tests
+    // don't need another signer
+    pub async fn start_initialized() -> Self {
+        let mut test = Self::start().await;
+        let upgrade_authority = test.upgrade_authority.pubkey();
+        let instructions = [
+            initialize_comptoken_program(&test.addresses, &test.payer(), &upgrade_authority, DAY_BOUNDARY_OFFSET),
+            set_admin(&test.addresses, &upgrade_authority, &test.payer()),
+        ];
+        let upgrade_authority = test.upgrade_authority.insecure_clone();
+        test.process(&instructions, &[&upgrade_authority]).await.expect("initialization succeeds");
+        test
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    pub async fn rent(&mut self) -> Rent {
+        self.context.banks_client.get_rent().await.unwrap()
+    }
+
+    pub async fn clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar::<Clock>().await.unwrap()
+    }
+
+    pub async fn set_time(&mut self, unix_timestamp: i64) {
+        let clock = Clock { unix_timestamp, ..self.clock().await };
+        self.context.set_sysvar(&clock);
+    }
+
+    // moves `days` days into the future. the slot is advanced too, so that there are new slot hashes (and blockhashes)
+    pub async fn warp_days(&mut self, days: i64) {
+        let clock = self.clock().await;
+        // roughly a days worth of slots
+        self.context.warp_to_slot(clock.slot + days as u64 * 216_000).unwrap();
+        self.set_time(clock.unix_timestamp + days * SEC_PER_DAY).await;
+    }
+
+    // the payer always signs. returns the events the comptoken program emitted
+    pub async fn process(
+        &mut self, instructions: &[Instruction], signers: &[&Keypair],
+    ) -> Result<Vec<ComptokenEvent>, TransactionError> {
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend(signers.iter().copied());
+        let blockhash = self.context.banks_client.get_latest_blockhash().await.unwrap();
+        let transaction =
+            Transaction::new_signed_with_payer(instructions, Some(&self.payer()), &all_signers, blockhash);
+
+        let result = self.context.banks_client.process_transaction_with_metadata(transaction).await.unwrap();
+        result.result?;
+        let logs = result.metadata.map(|metadata| metadata.log_messages).unwrap_or_default();
+        Ok(logs.iter().filter_map(|log| ComptokenEvent::from_log(log)).collect())
+    }
+
+    pub async fn get_account(&mut self, address: &Pubkey) -> Option<Account> {
+        self.context.banks_client.get_account(*address).await.unwrap()
+    }
+
+    // overwrites an account, e.g. to put it back in a legacy layout
+    pub fn set_account(&mut self, address: &Pubkey, account: &Account) {
+        self.context.set_account(address, &account.clone().into());
+    }
+
+    pub async fn token_balance(&mut self, comptoken_token_account: &Pubkey) -> u64 {
+        let account = self.get_account(comptoken_token_account).await.expect("token account exists");
+        StateWithExtensions::<TokenAccount>::unpack(&account.data).unwrap().base.amount
+    }
+
+    pub async fn mint_supply(&mut self) -> u64 {
+        let mint = self.addresses.mint;
+        let account = self.get_account(&mint).await.expect("mint exists");
+        StateWithExtensions::<Mint>::unpack(&account.data).unwrap().base.supply
+    }
+
+    pub async fn token_metadata(&mut self) -> TokenMetadata {
+        let mint = self.addresses.mint;
+        let account = self.get_account(&mint).await.expect("mint exists");
+        StateWithExtensions::<Mint>::unpack(&account.data)
+            .unwrap()
+            .get_variable_len_extension()
+            .unwrap()
+    }
+
+    // the accounts the transfer hook is passed, starting with the comptoken accounts
+    pub async fn extra_account_metas(&mut self) -> Vec<ExtraAccountMeta> {
+        let extra_account_metas = self.addresses.extra_account_metas;
+        let account = self.get_account(&extra_account_metas).await.expect("initialized");
+        let tlv_state = TlvStateBorrowed::unpack(&account.data).unwrap();
+        ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(&tlv_state)
+            .unwrap()
+            .data()
+            .to_vec()
+    }
+
+    pub async fn with_exempt_accounts<R>(&mut self, f: impl FnOnce(&ExemptAccounts) -> R) -> R {
+        let exempt_accounts = self.addresses.exempt_accounts;
+        let mut account = self.get_account(&exempt_accounts).await.expect("an account has been exempted");
+        let exempt_accounts = ExemptAccounts::try_from(account.data.as_mut_slice()).unwrap();
+        f(&exempt_accounts)
+    }
+
+    pub async fn with_global_data<R>(&mut self, f: impl FnOnce(&GlobalData) -> R) -> R {
+        let global_data = self.addresses.global_data;
+        let mut account = self.get_account(&global_data).await.expect("initialized");
+        let global_data: &mut GlobalData = account.data.as_mut_slice().into();
+        f(global_data)
+    }
+
+    pub async fn with_user_data<R>(&mut self, user_data: &Pubkey, f: impl FnOnce(&UserData) -> R) -> R {
+        let mut account = self.get_account(user_data).await.expect("user data exists");
+        let user_data = UserData::try_from(account.data.as_mut_slice()).unwrap();
+        f(&user_data)
+    }
+
+    pub async fn distribution_record(&mut self, distribution_time: i64) -> Option<DistributionRecord> {
+        let address = self.addresses.distribution_record(distribution_time);
+        let account = self.get_account(&address).await?;
+        let record: &DistributionRecord = account.data.as_slice().into();
+        Some(*record)
+    }
+
+    // creates a wallet with a comptoken token account and a user data account with room for one proof
+    pub async fn create_user(&mut self) -> User {
+        let user = self.create_user_without_user_data().await;
+        let instruction = create_user_data_account(
+            &self.addresses,
+            &self.payer(),
+            &user.wallet.pubkey(),
+            &user.comptoken_token_account,
+            1,
+        );
+        self.process(&[instruction], &[&user.wallet]).await.expect("user data account is created");
+        user
+    }
+
+    // creates a wallet with only a comptoken token account, like a first time recipient
+    pub async fn create_user_without_user_data(&mut self) -> User {
+        let wallet = Keypair::new();
+        let comptoken_token_account = Keypair::new();
+        let space =
+            ExtensionType::try_calculate_account_len::<TokenAccount>(&[ExtensionType::TransferHookAccount]).unwrap();
+        let rent = self.rent().await;
+        let instructions = [
+            system_instruction::create_account(
+                &self.payer(),
+                &comptoken_token_account.pubkey(),
+                rent.minimum_balance(space),
+                space as u64,
+                &spl_token_2022::ID,
+            ),
+            spl_token_2022::instruction::initialize_account3(
+                &spl_token_2022::ID,
+                &comptoken_token_account.pubkey(),
+                &self.addresses.mint,
+                &wallet.pubkey(),
+            )
+            .unwrap(),
+        ];
+        self.process(&instructions, &[&comptoken_token_account])
+            .await
+            .expect("comptoken token account is created");
+        User {
+            wallet,
+            comptoken_token_account: comptoken_token_account.pubkey(),
+        }
+    }
+}
+
+// deploys the program like `solana program deploy` would, so that it has program data with an upgrade authority
+fn add_upgradeable_program(
+    program_test: &mut ProgramTest, program_name: &str, addresses: &Addresses, upgrade_authority: &Pubkey,
+) {
+    let sbf_out_dir = std::env::var("SBF_OUT_DIR").expect("SBF_OUT_DIR is set");
+    let elf =
+        std::fs::read(PathBuf::from(sbf_out_dir).join(format!("{program_name}.so"))).expect("the program is built");
+    let rent = Rent::default();
+    let account = |data: Vec<u8>, executable| Account {
+        lamports: rent.minimum_balance(data.len()),
+        data,
+        owner: bpf_loader_upgradeable::ID,
+        executable,
+        rent_epoch: 0,
+    };
+
+    let program = UpgradeableLoaderState::Program { programdata_address: addresses.comptoken_program_data };
+    program_test.add_account(addresses.comptoken_program, account(bincode::serialize(&program).unwrap(), true));
+    let program_data =
+        UpgradeableLoaderState::ProgramData { slot: 0, upgrade_authority_address: Some(*upgrade_authority) };
+    let mut data = bincode::serialize(&program_data).unwrap();
+    data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+    data.extend(elf);
+    program_test.add_account(addresses.comptoken_program_data, account(data, false));
+}
+
+pub fn initialize_comptoken_program(
+    addresses: &Addresses, payer: &Pubkey, upgrade_authority: &Pubkey, day_boundary_offset: i64,
+) -> Instruction {
+    let mut data = vec![INITIALIZE_COMPTOKEN_PROGRAM];
+    data.extend(day_boundary_offset.to_le_bytes());
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*upgrade_authority, true),
+            AccountMeta::new_readonly(addresses.comptoken_program, false),
+            AccountMeta::new_readonly(addresses.comptoken_program_data, false),
+            AccountMeta::new(addresses.mint, false),
+            AccountMeta::new(addresses.global_data, false),
+            AccountMeta::new(addresses.interest_bank, false),
+            AccountMeta::new(addresses.verified_human_ubi_bank, false),
+            AccountMeta::new(addresses.future_ubi_bank, false),
+            AccountMeta::new_readonly(addresses.transfer_hook_program, false),
+            AccountMeta::new(addresses.extra_account_metas, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
+            AccountMeta::new_readonly(sysvar::slot_hashes::ID, false),
+        ],
+        data,
+    }
+}
+
+pub fn create_user_data_account(
+    addresses: &Addresses, payer: &Pubkey, user_wallet: &Pubkey, comptoken_token_account: &Pubkey, proof_capacity: u64,
+) -> Instruction {
+    let mut data = vec![CREATE_USER_DATA_ACCOUNT];
+    data.extend(proof_capacity.to_le_bytes());
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(addresses.global_data, false),
+            AccountMeta::new_readonly(*user_wallet, true),
+            AccountMeta::new_readonly(*comptoken_token_account, false),
+            AccountMeta::new(addresses.user_data(comptoken_token_account), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
+
+// grows by at most `MAX_PERMITTED_DATA_INCREASE` bytes, so it may need to be sent more than once
+pub fn grow_user_data_account(
+    addresses: &Addresses, payer: &Pubkey, user_wallet: &Pubkey, comptoken_token_account: &Pubkey, proof_capacity: u64,
+) -> Instruction {
+    let mut instruction =
+        create_user_data_account(addresses, payer, user_wallet, comptoken_token_account, proof_capacity);
+    instruction.data[0] = GROW_USER_DATA_ACCOUNT;
+    instruction
+}
+
+// the freed rent goes to `user_wallet`
+pub fn shrink_user_data_account(
+    addresses: &Addresses, user_wallet: &Pubkey, comptoken_token_account: &Pubkey, proof_capacity: u64,
+) -> Instruction {
+    let mut data = vec![SHRINK_USER_DATA_ACCOUNT];
+    data.extend(proof_capacity.to_le_bytes());
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new_readonly(addresses.global_data, false),
+            AccountMeta::new(*user_wallet, true),
+            AccountMeta::new_readonly(*comptoken_token_account, false),
+            AccountMeta::new(addresses.user_data(comptoken_token_account), false),
+        ],
+        data,
+    }
+}
+
+pub fn migrate_global_data(addresses: &Addresses, payer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(addresses.global_data, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: vec![MIGRATE_GLOBAL_DATA],
+    }
+}
+
+pub fn migrate_legacy_global_data(addresses: &Addresses, payer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(addresses.global_data, false),
+            AccountMeta::new_readonly(addresses.interest_bank, false),
+            AccountMeta::new_readonly(addresses.verified_human_ubi_bank, false),
+            AccountMeta::new_readonly(addresses.future_ubi_bank, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(addresses.mint, false),
+        ],
+        data: vec![MIGRATE_LEGACY_GLOBAL_DATA],
+    }
+}
+
+// anyone can pay to migrate a user's data, so the user doesn't sign
+pub fn migrate_user_data(addresses: &Addresses, payer: &Pubkey, comptoken_token_account: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(addresses.global_data, false),
+            AccountMeta::new_readonly(*comptoken_token_account, false),
+            AccountMeta::new(addresses.user_data(comptoken_token_account), false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: vec![MIGRATE_USER_DATA],
+    }
+}
+
+// only the upgrade authority may choose the admin
+pub fn set_admin(addresses: &Addresses, upgrade_authority: &Pubkey, admin: &Pubkey) -> Instruction {
+    let mut data = vec![SET_ADMIN];
+    data.extend(admin.as_ref());
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new_readonly(*upgrade_authority, true),
+            AccountMeta::new_readonly(addresses.comptoken_program_data, false),
+            AccountMeta::new(addresses.global_data, false),
+        ],
+        data,
+    }
+}
+
+// `TestContext::start_initialized` makes the payer the admin
+pub fn update_metadata_field(addresses: &Addresses, admin: &Pubkey, field: Field, value: &str) -> Instruction {
+    let mut data = vec![UPDATE_METADATA_FIELD];
+    match field {
+        Field::Name => data.push(0),
+        Field::Symbol => data.push(1),
+        Field::Uri => data.push(2),
+        Field::Key(key) => {
+            data.extend([3, key.len() as u8]);
+            data.extend(key.as_bytes());
+        }
+    }
+    data.extend(value.as_bytes());
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new(addresses.mint, false),
+            AccountMeta::new_readonly(addresses.global_data, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
+        ],
+        data,
+    }
+}
+
+pub fn remove_metadata_key(addresses: &Addresses, admin: &Pubkey, key: &str) -> Instruction {
+    let mut data = vec![REMOVE_METADATA_KEY];
+    data.extend(key.as_bytes());
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new_readonly(*admin, true),
+            AccountMeta::new(addresses.mint, false),
+            AccountMeta::new_readonly(addresses.global_data, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
+        ],
+        data,
+    }
+}
+
+// `extra_account_metas` are the accounts passed to the transfer hook after the comptoken accounts
+pub fn update_extra_account_metas(
+    addresses: &Addresses, admin: &Pubkey, extra_account_metas: &[ExtraAccountMeta],
+) -> Instruction {
+    let mut data = vec![UPDATE_EXTRA_ACCOUNT_METAS];
+    data.extend(bytemuck::cast_slice(extra_account_metas));
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(addresses.mint, false),
+            AccountMeta::new_readonly(addresses.global_data, false),
+            AccountMeta::new_readonly(addresses.transfer_hook_program, false),
+            AccountMeta::new(addresses.extra_account_metas, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
+
+// anyone can pay to migrate the extra account metas, so the admin doesn't sign
+pub fn migrate_extra_account_metas(addresses: &Addresses, payer: &Pubkey) -> Instruction {
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(addresses.mint, false),
+            AccountMeta::new_readonly(addresses.global_data, false),
+            AccountMeta::new_readonly(addresses.transfer_hook_program, false),
+            AccountMeta::new(addresses.extra_account_metas, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data: vec![MIGRATE_EXTRA_ACCOUNT_METAS],
+    }
+}
+
+// `account` is the comptoken token account to exempt from the transfer hook's checks
+pub fn add_exempt_account(addresses: &Addresses, admin: &Pubkey, account: &Pubkey) -> Instruction {
+    let mut data = vec![ADD_EXEMPT_ACCOUNT];
+    data.extend(account.as_ref());
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(addresses.global_data, false),
+            AccountMeta::new(addresses.exempt_accounts, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+        ],
+        data,
+    }
+}
+
+pub fn remove_exempt_account(addresses: &Addresses, admin: &Pubkey, account: &Pubkey) -> Instruction {
+    let mut data = vec![REMOVE_EXEMPT_ACCOUNT];
+    data.extend(account.as_ref());
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new(*admin, true),
+            AccountMeta::new_readonly(addresses.global_data, false),
+            AccountMeta::new(addresses.exempt_accounts, false),
+        ],
+        data,
+    }
+}
+
+// only exists in testmode builds
+pub fn test_mint(
+    addresses: &Addresses, user_wallet: &Pubkey, comptoken_token_account: &Pubkey, amount: u64,
+) -> Instruction {
+    let mut data = vec![TEST];
+    data.extend(amount.to_le_bytes());
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new(addresses.mint, false),
+            AccountMeta::new_readonly(addresses.global_data, false),
+            AccountMeta::new_readonly(*user_wallet, true),
+            AccountMeta::new(*comptoken_token_account, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
+        ],
+        data,
+    }
+}
+
+// `caller` is the wallet and comptoken token account to receive the crank reward
+pub fn daily_distribution_event(
+    addresses: &Addresses, payer: &Pubkey, distribution_time: i64, caller: Option<(&Pubkey, &Pubkey)>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*payer, true),
+        AccountMeta::new(addresses.mint, false),
+        AccountMeta::new(addresses.global_data, false),
+        AccountMeta::new(addresses.interest_bank, false),
+        AccountMeta::new(addresses.verified_human_ubi_bank, false),
+        AccountMeta::new(addresses.future_ubi_bank, false),
+        AccountMeta::new(addresses.distribution_record(distribution_time), false),
+        AccountMeta::new_readonly(system_program::ID, false),
+        AccountMeta::new_readonly(spl_token_2022::ID, false),
+        AccountMeta::new_readonly(sysvar::slot_hashes::ID, false),
+    ];
+    if let Some((caller_wallet, caller_comptoken_token_account)) = caller {
+        accounts.push(AccountMeta::new_readonly(*caller_wallet, true));
+        accounts.push(AccountMeta::new(*caller_comptoken_token_account, false));
+    }
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts,
+        data: vec![DAILY_DISTRIBUTION_EVENT],
+    }
+}
+
+pub fn get_owed_comptokens(
+    addresses: &Addresses, user_wallet: &Pubkey, comptoken_token_account: &Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: addresses.comptoken_program,
+        accounts: vec![
+            AccountMeta::new_readonly(addresses.comptoken_program, false),
+            AccountMeta::new_readonly(addresses.mint, false),
+            AccountMeta::new(addresses.global_data, false),
+            AccountMeta::new(addresses.interest_bank, false),
+            AccountMeta::new(addresses.verified_human_ubi_bank, false),
+            AccountMeta::new_readonly(addresses.user_data(&addresses.interest_bank), false),
+            AccountMeta::new_readonly(addresses.user_data(&addresses.verified_human_ubi_bank), false),
+            AccountMeta::new_readonly(*user_wallet, true),
+            AccountMeta::new(*comptoken_token_account, false),
+            AccountMeta::new(addresses.user_data(comptoken_token_account), false),
+            AccountMeta::new_readonly(addresses.transfer_hook_program, false),
+            AccountMeta::new_readonly(addresses.extra_account_metas, false),
+            AccountMeta::new_readonly(addresses.exempt_accounts, false),
+            AccountMeta::new_readonly(spl_token_2022::ID, false),
+        ],
+        data: vec![GET_OWED_COMPTOKENS],
+    }
+}
+
+// `recipient` receives the user data's lamports
+pub fn close_user_data_account(
+    addresses: &Addresses, user_wallet: &Pubkey, comptoken_token_account: &Pubkey, recipient: &Pubkey,
+    forfeit_verified_human: bool,
+) -> Instruction {
+    let mut instruction = get_owed_comptokens(addresses, user_wallet, comptoken_token_account);
+    instruction.accounts.push(AccountMeta::new(*recipient, false));
+    instruction.data = vec![CLOSE_USER_DATA_ACCOUNT, forfeit_verified_human as u8];
+    instruction
+}
+
+// a comptoken transfer, with the extra accounts the transfer hook needs
+pub fn transfer(
+    addresses: &Addresses, source: &Pubkey, destination: &Pubkey, authority: &Pubkey, amount: u64,
+) -> Instruction {
+    let mut instruction = spl_token_2022::instruction::transfer_checked(
+        &spl_token_2022::ID,
+        source,
+        &addresses.mint,
+        destination,
+        authority,
+        &[],
+        amount,
+        MINT_DECIMALS,
+    )
+    .unwrap();
+    instruction.accounts.extend([
+        AccountMeta::new_readonly(addresses.comptoken_program, false),
+        AccountMeta::new_readonly(addresses.user_data(source), false),
+        AccountMeta::new_readonly(addresses.user_data(destination), false),
+        AccountMeta::new_readonly(addresses.transfer_hook_program, false),
+        AccountMeta::new_readonly(addresses.extra_account_metas, false),
+        AccountMeta::new_readonly(addresses.exempt_accounts, false),
+    ]);
+    instruction
+}
+
+pub fn is_instruction_error(result: &Result<Vec<ComptokenEvent>, TransactionError>) -> bool {
+    matches!(result, Err(TransactionError::InstructionError(..)))
+}
@@ -0,0 +1,128 @@
+use solana_sdk::{
+    instruction::{AccountMeta, InstructionError},
+    signature::Signer,
+    transaction::TransactionError,
+};
+
+use comptoken::{
+    constants::{CRANK_REWARD, MINING_AMOUNT},
+    global_data::daily_distribution_data::HistoricDistribution,
+};
+use comptoken_program_tests::{
+    daily_distribution_event, is_instruction_error, test_mint, TestContext, User, DAY_BOUNDARY_OFFSET,
+    DEFAULT_START_TIME,
+};
+use comptoken_utils::{events::ComptokenEvent, normalize_time, SEC_PER_DAY};
+
+async fn start_with_mined_comptokens() -> (TestContext, User) {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+    let instruction = test_mint(&test.addresses, &user.wallet.pubkey(), &user.comptoken_token_account, MINING_AMOUNT);
+    test.process(&[instruction], &[&user.wallet]).await.expect("mint succeeds");
+    (test, user)
+}
+
+#[tokio::test]
+async fn test_daily_distribution_event() {
+    let (mut test, user) = start_with_mined_comptokens().await;
+    test.warp_days(1).await;
+    let distribution_time = normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET) + SEC_PER_DAY;
+
+    let caller = Some((&user.wallet.pubkey(), &user.comptoken_token_account));
+    let instruction = daily_distribution_event(&test.addresses, &test.payer(), distribution_time, caller);
+    let events = test.process(&[instruction], &[&user.wallet]).await.expect("distribution succeeds");
+
+    let Some(ComptokenEvent::DailyDistribution {
+        distribution_time: event_distribution_time,
+        interest_distribution,
+        ubi_for_verified_humans,
+        future_ubi_distribution,
+        crank_reward,
+        supply: event_supply,
+        high_water_mark,
+    }) = events.into_iter().find(|event| matches!(event, ComptokenEvent::DailyDistribution { .. }))
+    else {
+        panic!("the distribution emits an event");
+    };
+    assert_eq!(event_distribution_time, distribution_time);
+    assert!(interest_distribution > 0);
+    assert_eq!(ubi_for_verified_humans, 0, "there are no verified humans");
+    assert!(future_ubi_distribution > 0);
+    assert_eq!(crank_reward, CRANK_REWARD);
+
+    let addresses = &test.addresses;
+    let banks = [addresses.interest_bank, addresses.verified_human_ubi_bank, addresses.future_ubi_bank];
+    let distributed = [interest_distribution, ubi_for_verified_humans, future_ubi_distribution];
+    for (bank, distributed) in banks.iter().zip(distributed) {
+        assert_eq!(test.token_balance(bank).await, distributed);
+    }
+    assert_eq!(test.token_balance(&user.comptoken_token_account).await, MINING_AMOUNT + CRANK_REWARD);
+    let supply = test.mint_supply().await;
+    assert_eq!(supply, MINING_AMOUNT + interest_distribution + future_ubi_distribution + crank_reward);
+    assert_eq!(event_supply, supply, "the event has the supply once the distribution is minted");
+    assert_eq!(high_water_mark, MINING_AMOUNT);
+
+    let record = test.distribution_record(distribution_time).await.expect("the distribution is recorded");
+    assert_eq!(record.distribution_time, distribution_time);
+    assert_eq!(record.supply, MINING_AMOUNT);
+    assert_eq!(record.high_water_mark_increase, MINING_AMOUNT);
+    assert_eq!(record.interest_distribution, interest_distribution);
+    assert_eq!(record.crank_reward, CRANK_REWARD);
+    test.with_global_data(|global_data| {
+        let daily_distribution_data = &global_data.daily_distribution_data;
+        assert_eq!(daily_distribution_data.last_daily_distribution_time, distribution_time);
+        assert_eq!(daily_distribution_data.high_water_mark, MINING_AMOUNT);
+        assert_eq!(daily_distribution_data.yesterday_supply, supply);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_daily_distribution_event_caller_without_token_account() {
+    let (mut test, user) = start_with_mined_comptokens().await;
+    test.warp_days(1).await;
+    let distribution_time = normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET) + SEC_PER_DAY;
+
+    let mut instruction = daily_distribution_event(&test.addresses, &test.payer(), distribution_time, None);
+    instruction.accounts.push(AccountMeta::new_readonly(user.wallet.pubkey(), true));
+    let result = test.process(&[instruction], &[&user.wallet]).await;
+
+    assert_eq!(result, Err(TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)));
+    assert_eq!(test.distribution_record(distribution_time).await, None);
+}
+
+#[tokio::test]
+async fn test_early_daily_distribution_event() {
+    let (mut test, _user) = start_with_mined_comptokens().await;
+    let distribution_time = normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET);
+
+    let instruction = daily_distribution_event(&test.addresses, &test.payer(), distribution_time, None);
+    let result = test.process(&[instruction], &[]).await;
+
+    assert!(is_instruction_error(&result), "the program was initialized today");
+    assert_eq!(test.distribution_record(distribution_time).await, None);
+}
+
+#[tokio::test]
+async fn test_missed_daily_distribution_event() {
+    let (mut test, _user) = start_with_mined_comptokens().await;
+    test.warp_days(3).await;
+    let distribution_time = normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET) + 3 * SEC_PER_DAY;
+
+    let instruction = daily_distribution_event(&test.addresses, &test.payer(), distribution_time, None);
+    test.process(&[instruction], &[]).await.expect("distribution succeeds");
+
+    test.with_global_data(|global_data| {
+        let daily_distribution_data = &global_data.daily_distribution_data;
+        assert_eq!(daily_distribution_data.last_daily_distribution_time, distribution_time);
+        // the 2 missed days and today
+        assert_eq!(daily_distribution_data.oldest_historic_index, 3);
+        let empty_distribution = HistoricDistribution { interest_rate: 1., ubi: 0 };
+        assert_eq!(daily_distribution_data.historic_distributions[0], empty_distribution);
+        assert_eq!(daily_distribution_data.historic_distributions[1], empty_distribution);
+    })
+    .await;
+    // there is only a record for the day the distribution ran
+    assert_eq!(test.distribution_record(distribution_time - SEC_PER_DAY).await, None);
+    assert!(test.distribution_record(distribution_time).await.is_some());
+}
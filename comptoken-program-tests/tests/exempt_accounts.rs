@@ -0,0 +1,114 @@
+use solana_sdk::signature::Signer;
+
+use comptoken_program_tests::{
+    add_exempt_account, daily_distribution_event, get_owed_comptokens, is_instruction_error, remove_exempt_account,
+    test_mint, transfer, TestContext, DAY_BOUNDARY_OFFSET, DEFAULT_START_TIME,
+};
+use comptoken_utils::{normalize_time, SEC_PER_DAY};
+
+#[tokio::test]
+async fn test_add_and_remove_exempt_accounts() {
+    let mut test = TestContext::start_initialized().await;
+    let admin = test.payer();
+    let escrow = test.create_user().await;
+    let pool = test.create_user().await;
+
+    let instructions = [
+        add_exempt_account(&test.addresses, &admin, &escrow.comptoken_token_account),
+        add_exempt_account(&test.addresses, &admin, &pool.comptoken_token_account),
+    ];
+    test.process(&instructions, &[]).await.expect("the admin can exempt accounts");
+
+    let mut expected = [escrow.comptoken_token_account, pool.comptoken_token_account];
+    expected.sort();
+    test.with_exempt_accounts(|exempt_accounts| assert_eq!(exempt_accounts.accounts(), expected))
+        .await;
+    let exempt_accounts_address = test.addresses.exempt_accounts;
+    let exempt_accounts = test.get_account(&exempt_accounts_address).await.unwrap();
+    assert_eq!(exempt_accounts.lamports, test.rent().await.minimum_balance(exempt_accounts.data.len()));
+
+    let instruction = remove_exempt_account(&test.addresses, &admin, &escrow.comptoken_token_account);
+    test.process(&[instruction], &[]).await.expect("the admin can remove an exempt account");
+
+    test.with_exempt_accounts(|exempt_accounts| {
+        assert_eq!(exempt_accounts.accounts(), [pool.comptoken_token_account]);
+    })
+    .await;
+    let exempt_accounts = test.get_account(&exempt_accounts_address).await.unwrap();
+    assert_eq!(
+        exempt_accounts.lamports,
+        test.rent().await.minimum_balance(exempt_accounts.data.len()),
+        "the admin gets the freed rent back"
+    );
+}
+
+#[tokio::test]
+async fn test_add_exempt_account_not_admin() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+
+    let instruction = add_exempt_account(&test.addresses, &user.wallet.pubkey(), &user.comptoken_token_account);
+    let result = test.process(&[instruction], &[&user.wallet]).await;
+
+    assert!(is_instruction_error(&result), "only the admin can exempt accounts");
+    let exempt_accounts = test.addresses.exempt_accounts;
+    assert!(test.get_account(&exempt_accounts).await.is_none());
+}
+
+#[tokio::test]
+async fn test_exempt_accounts_transfer_without_claiming() {
+    let mut test = TestContext::start_initialized().await;
+    let admin = test.payer();
+    let escrow = test.create_user().await;
+    let user = test.create_user().await;
+    let instruction = test_mint(&test.addresses, &escrow.wallet.pubkey(), &escrow.comptoken_token_account, 100);
+    test.process(&[instruction], &[&escrow.wallet]).await.expect("mint succeeds");
+
+    test.warp_days(1).await;
+    let distribution_time = normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET) + SEC_PER_DAY;
+    let instruction = daily_distribution_event(&test.addresses, &test.payer(), distribution_time, None);
+    test.process(&[instruction], &[]).await.expect("distribution succeeds");
+    let instruction = get_owed_comptokens(&test.addresses, &user.wallet.pubkey(), &user.comptoken_token_account);
+    test.process(&[instruction], &[&user.wallet]).await.expect("claiming succeeds");
+
+    let instruction = add_exempt_account(&test.addresses, &admin, &escrow.comptoken_token_account);
+    test.process(&[instruction], &[]).await.expect("the admin can exempt accounts");
+
+    let instruction = transfer(
+        &test.addresses,
+        &escrow.comptoken_token_account,
+        &user.comptoken_token_account,
+        &escrow.wallet.pubkey(),
+        40,
+    );
+    test.process(&[instruction], &[&escrow.wallet])
+        .await
+        .expect("an exempt source doesn't need to claim");
+
+    let instruction = transfer(
+        &test.addresses,
+        &user.comptoken_token_account,
+        &escrow.comptoken_token_account,
+        &user.wallet.pubkey(),
+        15,
+    );
+    test.process(&[instruction], &[&user.wallet])
+        .await
+        .expect("an exempt destination doesn't need to claim");
+
+    let user_balance = test.token_balance(&user.comptoken_token_account).await;
+    assert_eq!(test.token_balance(&escrow.comptoken_token_account).await, 75);
+
+    let instruction = remove_exempt_account(&test.addresses, &admin, &escrow.comptoken_token_account);
+    test.process(&[instruction], &[]).await.expect("the admin can remove an exempt account");
+    let instruction = transfer(
+        &test.addresses,
+        &user.comptoken_token_account,
+        &escrow.comptoken_token_account,
+        &user.wallet.pubkey(),
+        10,
+    );
+    let result = test.process(&[instruction], &[&user.wallet]).await;
+    assert!(is_instruction_error(&result), "the escrow is no longer exempt");
+    assert_eq!(test.token_balance(&user.comptoken_token_account).await, user_balance);
+}
@@ -0,0 +1,44 @@
+use solana_sdk::signature::Signer;
+
+use comptoken::constants::MINING_AMOUNT;
+use comptoken_program_tests::{
+    daily_distribution_event, get_owed_comptokens, test_mint, TestContext, DAY_BOUNDARY_OFFSET, DEFAULT_START_TIME,
+};
+use comptoken_utils::{events::ComptokenEvent, normalize_time, SEC_PER_DAY};
+
+#[tokio::test]
+async fn test_get_owed_comptokens() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+    let instruction = test_mint(&test.addresses, &user.wallet.pubkey(), &user.comptoken_token_account, MINING_AMOUNT);
+    test.process(&[instruction], &[&user.wallet]).await.expect("mint succeeds");
+
+    test.warp_days(1).await;
+    let distribution_time = normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET) + SEC_PER_DAY;
+    let instruction = daily_distribution_event(&test.addresses, &test.payer(), distribution_time, None);
+    test.process(&[instruction], &[]).await.expect("distribution succeeds");
+
+    let interest_bank = test.addresses.interest_bank;
+    let interest_bank_balance = test.token_balance(&interest_bank).await;
+    let expected_interest = test
+        .with_global_data(|global_data| global_data.daily_distribution_data.get_interest_for_n_days(1, MINING_AMOUNT))
+        .await;
+    assert!(expected_interest > 0);
+
+    let instruction = get_owed_comptokens(&test.addresses, &user.wallet.pubkey(), &user.comptoken_token_account);
+    let events = test.process(&[instruction], &[&user.wallet]).await.expect("claiming succeeds");
+
+    assert_eq!(
+        events,
+        [ComptokenEvent::InterestClaimed {
+            user_comptoken_wallet: user.comptoken_token_account,
+            interest: expected_interest,
+            ubi: 0,
+        }]
+    );
+    assert_eq!(test.token_balance(&user.comptoken_token_account).await, MINING_AMOUNT + expected_interest);
+    assert_eq!(test.token_balance(&interest_bank).await, interest_bank_balance - expected_interest);
+    let user_data = test.addresses.user_data(&user.comptoken_token_account);
+    test.with_user_data(&user_data, |user_data| assert_eq!(user_data.last_interest_payout_date, distribution_time))
+        .await;
+}
@@ -0,0 +1,161 @@
+use solana_sdk::{
+    instruction::InstructionError, program_option::COption, pubkey::Pubkey, signature::Signer,
+    transaction::TransactionError,
+};
+use spl_token_2022::{
+    extension::{
+        metadata_pointer::MetadataPointer, transfer_hook::TransferHook, BaseStateWithExtensions, ExtensionType,
+        StateWithExtensions,
+    },
+    state::{Account as TokenAccount, Mint},
+};
+use spl_token_metadata_interface::state::TokenMetadata;
+
+use comptoken::{
+    constants::{COMPTOKEN_NAME, COMPTOKEN_SYMBOL, COMPTOKEN_URI, COMPTO_GLOBAL_DATA_ACCOUNT_SEED, MINT_DECIMALS},
+    global_data::GlobalData,
+};
+use comptoken_program_tests::{
+    initialize_comptoken_program, is_instruction_error, TestContext, DAY_BOUNDARY_OFFSET, DEFAULT_START_TIME,
+};
+use comptoken_utils::{normalize_time, versioned_account::account_size, SEC_PER_DAY};
+
+#[tokio::test]
+async fn test_initialize_comptoken_program() {
+    let mut test = TestContext::start().await;
+    let rent = test.rent().await;
+    let upgrade_authority = test.upgrade_authority.insecure_clone();
+    let instruction =
+        initialize_comptoken_program(&test.addresses, &test.payer(), &upgrade_authority.pubkey(), DAY_BOUNDARY_OFFSET);
+    test.process(&[instruction], &[&upgrade_authority])
+        .await
+        .expect("initialization succeeds");
+
+    let addresses = &test.addresses;
+    let (comptoken_program, transfer_hook_program, mint, global_data) =
+        (addresses.comptoken_program, addresses.transfer_hook_program, addresses.mint, addresses.global_data);
+    let banks = [addresses.interest_bank, addresses.verified_human_ubi_bank, addresses.future_ubi_bank];
+    let extra_account_metas = addresses.extra_account_metas;
+
+    let mint_account = test.get_account(&mint).await.expect("mint is created");
+    assert_eq!(mint_account.owner, spl_token_2022::ID);
+    let mint_state = StateWithExtensions::<Mint>::unpack(&mint_account.data).unwrap();
+    assert_eq!(mint_state.base.mint_authority, COption::Some(global_data));
+    assert_eq!(mint_state.base.freeze_authority, COption::None);
+    assert_eq!(mint_state.base.decimals, MINT_DECIMALS);
+    let transfer_hook = mint_state.get_extension::<TransferHook>().unwrap();
+    assert_eq!(Option::<Pubkey>::from(transfer_hook.program_id), Some(transfer_hook_program));
+    assert_eq!(Option::<Pubkey>::from(transfer_hook.authority), Some(global_data));
+    let metadata_pointer = mint_state.get_extension::<MetadataPointer>().unwrap();
+    assert_eq!(Option::<Pubkey>::from(metadata_pointer.metadata_address), Some(mint), "the mint holds its metadata");
+    assert_eq!(Option::<Pubkey>::from(metadata_pointer.authority), Some(global_data));
+    let metadata = mint_state.get_variable_len_extension::<TokenMetadata>().unwrap();
+    assert_eq!(Option::<Pubkey>::from(metadata.update_authority), Some(global_data));
+    assert_eq!(metadata.mint, mint);
+    assert_eq!(
+        (metadata.name.as_str(), metadata.symbol.as_str(), metadata.uri.as_str()),
+        (COMPTOKEN_NAME, COMPTOKEN_SYMBOL, COMPTOKEN_URI)
+    );
+    assert_eq!(mint_account.lamports, test.rent().await.minimum_balance(mint_account.data.len()));
+
+    let global_data_account = test.get_account(&global_data).await.expect("global data is created");
+    assert_eq!(global_data_account.owner, comptoken_program);
+    assert_eq!(global_data_account.data.len(), account_size::<GlobalData>());
+    assert_eq!(
+        global_data_account.lamports,
+        rent.minimum_balance(account_size::<GlobalData>()),
+        "the rent is computed by the program"
+    );
+    test.with_global_data(|global_data| {
+        let daily_distribution_data = &global_data.daily_distribution_data;
+        assert_eq!(
+            daily_distribution_data.last_daily_distribution_time,
+            normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET)
+        );
+        assert_eq!(daily_distribution_data.yesterday_supply, 0);
+        assert_ne!(global_data.valid_blockhashes.valid_blockhash, Default::default());
+        assert_eq!(global_data.comptoken_mint, mint);
+        assert_eq!(global_data.transfer_hook_program, transfer_hook_program);
+        assert_eq!(global_data.admin, upgrade_authority.pubkey(), "the upgrade authority administers the program");
+        assert_eq!(global_data.day_boundary_offset, DAY_BOUNDARY_OFFSET);
+        let canonical_bump = |seed: &[u8], program: &Pubkey| Pubkey::find_program_address(&[seed], program).1;
+        let bumps = global_data.bumps;
+        assert_eq!(bumps.global_data, canonical_bump(COMPTO_GLOBAL_DATA_ACCOUNT_SEED, &comptoken_program));
+        assert_eq!(bumps.interest_bank_data, canonical_bump(banks[0].as_ref(), &comptoken_program));
+        let (_, extra_account_metas_bump) =
+            Pubkey::find_program_address(&[b"extra-account-metas", mint.as_ref()], &transfer_hook_program);
+        assert_eq!(bumps.extra_account_metas, extra_account_metas_bump);
+    })
+    .await;
+
+    let bank_size =
+        ExtensionType::try_calculate_account_len::<TokenAccount>(&[ExtensionType::TransferHookAccount]).unwrap();
+    let bank_rent = test.rent().await.minimum_balance(bank_size);
+    for bank in banks {
+        let bank_account = test.get_account(&bank).await.expect("bank is created");
+        assert_eq!(bank_account.owner, spl_token_2022::ID);
+        assert_eq!(bank_account.data.len(), bank_size, "the bank is sized for the mint's transfer hook");
+        assert_eq!(bank_account.lamports, bank_rent, "each bank is funded for its own rent");
+        let bank = StateWithExtensions::<TokenAccount>::unpack(&bank_account.data).unwrap().base;
+        assert_eq!(bank.owner, global_data, "the global data account owns the banks");
+        assert_eq!(bank.amount, 0);
+    }
+
+    let extra_account_metas_account =
+        test.get_account(&extra_account_metas).await.expect("extra account metas are created");
+    assert_eq!(extra_account_metas_account.owner, transfer_hook_program);
+}
+
+#[tokio::test]
+async fn test_initialize_comptoken_program_not_upgrade_authority() {
+    let mut test = TestContext::start().await;
+
+    // otherwise anyone could initialize the program first, and so administer it
+    let instruction = initialize_comptoken_program(&test.addresses, &test.payer(), &test.payer(), DAY_BOUNDARY_OFFSET);
+    let result = test.process(&[instruction], &[]).await;
+    assert!(is_instruction_error(&result), "only the upgrade authority can initialize the program");
+    let global_data = test.addresses.global_data;
+    assert!(test.get_account(&global_data).await.is_none());
+}
+
+#[tokio::test]
+async fn test_initialize_comptoken_program_day_boundary_offset() {
+    let mut test = TestContext::start().await;
+    let upgrade_authority = test.upgrade_authority.insecure_clone();
+
+    // days start at 6:00 UTC+0
+    let day_boundary_offset = 6 * 60 * 60;
+    let instruction =
+        initialize_comptoken_program(&test.addresses, &test.payer(), &upgrade_authority.pubkey(), day_boundary_offset);
+    test.process(&[instruction], &[&upgrade_authority])
+        .await
+        .expect("initialization succeeds");
+
+    test.with_global_data(|global_data| {
+        assert_eq!(global_data.day_boundary_offset, day_boundary_offset);
+        let today = normalize_time(DEFAULT_START_TIME, day_boundary_offset);
+        assert_eq!(today.rem_euclid(SEC_PER_DAY), day_boundary_offset);
+        assert_eq!(global_data.daily_distribution_data.last_daily_distribution_time, today);
+        assert_eq!(global_data.bank_liabilities.last_dust_sweep_time, today);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_initialize_comptoken_program_invalid_day_boundary_offset() {
+    let mut test = TestContext::start().await;
+    let upgrade_authority = test.upgrade_authority.insecure_clone();
+
+    for day_boundary_offset in [-1, SEC_PER_DAY] {
+        let instruction = initialize_comptoken_program(
+            &test.addresses,
+            &test.payer(),
+            &upgrade_authority.pubkey(),
+            day_boundary_offset,
+        );
+        let result = test.process(&[instruction], &[&upgrade_authority]).await;
+        assert_eq!(result, Err(TransactionError::InstructionError(0, InstructionError::InvalidArgument)));
+    }
+    let global_data = test.addresses.global_data;
+    assert!(test.get_account(&global_data).await.is_none());
+}
@@ -0,0 +1,120 @@
+use comptoken::global_data::{Bumps, GlobalData, LEGACY_GLOBAL_DATA_SIZE};
+use comptoken_program_tests::{
+    add_exempt_account, migrate_global_data, migrate_legacy_global_data, migrate_user_data, set_admin, TestContext,
+};
+use comptoken_utils::{
+    events::ComptokenEvent,
+    user_data::UserData,
+    versioned_account::{VersionedAccount, ACCOUNT_HEADER_SIZE},
+};
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+#[tokio::test]
+async fn test_migrate_current_accounts() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+    let global_data = test.addresses.global_data;
+    let user_data = test.addresses.user_data(&user.comptoken_token_account);
+    let global_data_before = test.get_account(&global_data).await.unwrap();
+    let user_data_before = test.get_account(&user_data).await.unwrap();
+
+    // accounts that are already current are left as they are, however many times they are migrated
+    let migrate_global_data = migrate_global_data(&test.addresses, &test.payer());
+    let migrate_user_data = migrate_user_data(&test.addresses, &test.payer(), &user.comptoken_token_account);
+    let instructions = [
+        migrate_global_data.clone(),
+        migrate_user_data.clone(),
+        migrate_global_data,
+        migrate_user_data,
+    ];
+    let events = test.process(&instructions, &[]).await.expect("migrating succeeds");
+
+    assert_eq!(events, []);
+    assert_eq!(test.get_account(&global_data).await.unwrap(), global_data_before);
+    assert_eq!(test.get_account(&user_data).await.unwrap(), user_data_before);
+}
+
+#[tokio::test]
+async fn test_migrate_legacy_global_data() {
+    let mut test = TestContext::start_initialized().await;
+    let global_data = test.addresses.global_data;
+    let current = test.get_account(&global_data).await.unwrap();
+
+    // the legacy global data was only the valid blockhashes and the daily distribution data
+    let mut legacy = current.clone();
+    let legacy_start = ACCOUNT_HEADER_SIZE + 2 * std::mem::size_of::<Pubkey>() + std::mem::size_of::<Bumps>();
+    legacy.data = current.data[legacy_start..legacy_start + LEGACY_GLOBAL_DATA_SIZE].to_vec();
+    test.set_account(&global_data, &legacy);
+
+    let instruction = migrate_legacy_global_data(&test.addresses, &test.payer());
+    let events = test.process(&[instruction], &[]).await.expect("migrating succeeds");
+
+    assert_eq!(
+        events,
+        [ComptokenEvent::AccountMigrated {
+            account: global_data,
+            version: GlobalData::VERSION,
+            new_size: current.data.len() as u64,
+        }]
+    );
+    // the banks are empty and the clock hasn't moved, so only the admin is lost
+    let mut expected = current.data.clone();
+    let admin_start = expected.len() - std::mem::size_of::<Pubkey>();
+    expected[admin_start..].fill(0);
+    assert_eq!(test.get_account(&global_data).await.unwrap().data, expected);
+
+    // the upgrade authority gives the migrated program its admin back
+    let upgrade_authority = test.upgrade_authority.insecure_clone();
+    let user = test.create_user().await;
+    let instructions = [
+        set_admin(&test.addresses, &upgrade_authority.pubkey(), &test.payer()),
+        add_exempt_account(&test.addresses, &test.payer(), &user.comptoken_token_account),
+    ];
+    test.process(&instructions, &[&upgrade_authority])
+        .await
+        .expect("the new admin can exempt accounts");
+}
+
+#[tokio::test]
+async fn test_migrate_legacy_global_data_when_current() {
+    let mut test = TestContext::start_initialized().await;
+
+    let instruction = migrate_legacy_global_data(&test.addresses, &test.payer());
+    assert!(test.process(&[instruction], &[]).await.is_err(), "current global data isn't legacy");
+}
+
+#[tokio::test]
+async fn test_migrate_legacy_user_data() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+    let user_data = test.addresses.user_data(&user.comptoken_token_account);
+    let current = test.get_account(&user_data).await.unwrap();
+    let (last_interest_payout_date, bump) = test
+        .with_user_data(&user_data, |user_data| (user_data.last_interest_payout_date, user_data.bump))
+        .await;
+
+    // the legacy user data had no header, and the bump was padding
+    let mut legacy = current.clone();
+    legacy.data = current.data[ACCOUNT_HEADER_SIZE..].to_vec();
+    legacy.data[9..16].fill(0);
+    test.set_account(&user_data, &legacy);
+
+    let instruction = migrate_user_data(&test.addresses, &test.payer(), &user.comptoken_token_account);
+    let events = test.process(&[instruction], &[]).await.expect("migrating succeeds");
+
+    assert_eq!(
+        events,
+        [ComptokenEvent::AccountMigrated {
+            account: user_data,
+            version: UserData::VERSION,
+            new_size: current.data.len() as u64,
+        }]
+    );
+    test.with_user_data(&user_data, |user_data| {
+        assert_eq!(user_data.last_interest_payout_date, last_interest_payout_date);
+        assert_eq!(user_data.bump, bump);
+        assert_eq!(user_data.length, 0);
+        assert_eq!(user_data.proofs.len(), 1);
+    })
+    .await;
+}
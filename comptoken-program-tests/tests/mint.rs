@@ -0,0 +1,29 @@
+use comptoken_program_tests::{is_instruction_error, test_mint, TestContext};
+use comptoken_utils::events::ComptokenEvent;
+use solana_sdk::signature::{Keypair, Signer};
+
+#[tokio::test]
+async fn test_mint_to_user() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+
+    let instruction = test_mint(&test.addresses, &user.wallet.pubkey(), &user.comptoken_token_account, 3);
+    let events = test.process(&[instruction], &[&user.wallet]).await.expect("mint succeeds");
+
+    assert_eq!(test.token_balance(&user.comptoken_token_account).await, 3);
+    assert_eq!(test.mint_supply().await, 3);
+    assert_eq!(events, [ComptokenEvent::TokensMinted { destination: user.comptoken_token_account, amount: 3 }]);
+}
+
+#[tokio::test]
+async fn test_mint_to_user_requires_owner() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+    let not_the_owner = Keypair::new();
+
+    let instruction = test_mint(&test.addresses, &not_the_owner.pubkey(), &user.comptoken_token_account, 3);
+    let result = test.process(&[instruction], &[&not_the_owner]).await;
+
+    assert!(is_instruction_error(&result));
+    assert_eq!(test.token_balance(&user.comptoken_token_account).await, 0);
+}
@@ -0,0 +1,33 @@
+use solana_sdk::{pubkey::Pubkey, signature::Signer};
+
+use comptoken_program_tests::{add_exempt_account, is_instruction_error, set_admin, TestContext};
+
+#[tokio::test]
+async fn test_set_admin() {
+    let mut test = TestContext::start_initialized().await;
+    let upgrade_authority = test.upgrade_authority.insecure_clone();
+    let admin = Pubkey::new_unique();
+
+    let instruction = set_admin(&test.addresses, &upgrade_authority.pubkey(), &admin);
+    test.process(&[instruction], &[&upgrade_authority])
+        .await
+        .expect("the upgrade authority can set the admin");
+
+    assert_eq!(test.with_global_data(|global_data| global_data.admin).await, admin);
+    // the payer was the admin until now
+    let user = test.create_user().await;
+    let instruction = add_exempt_account(&test.addresses, &test.payer(), &user.comptoken_token_account);
+    let result = test.process(&[instruction], &[]).await;
+    assert!(is_instruction_error(&result), "the previous admin is no longer the admin");
+}
+
+#[tokio::test]
+async fn test_set_admin_not_upgrade_authority() {
+    let mut test = TestContext::start_initialized().await;
+
+    // not even the admin may choose the next admin
+    let instruction = set_admin(&test.addresses, &test.payer(), &Pubkey::new_unique());
+    let result = test.process(&[instruction], &[]).await;
+    assert!(is_instruction_error(&result), "only the upgrade authority can set the admin");
+    assert_eq!(test.with_global_data(|global_data| global_data.admin).await, test.payer());
+}
@@ -0,0 +1,85 @@
+use solana_sdk::signature::Signer;
+use spl_token_metadata_interface::state::Field;
+
+use comptoken::constants::{APR_METADATA_KEY, COMPTOKEN_NAME, MINING_AMOUNT, VERIFIED_HUMANS_METADATA_KEY};
+use comptoken_program_tests::{
+    daily_distribution_event, is_instruction_error, remove_metadata_key, test_mint, update_metadata_field, TestContext,
+    DAY_BOUNDARY_OFFSET, DEFAULT_START_TIME,
+};
+use comptoken_utils::{normalize_time, SEC_PER_DAY};
+
+#[tokio::test]
+async fn test_update_metadata_field() {
+    let mut test = TestContext::start_initialized().await;
+    let admin = test.payer();
+
+    let instructions = [
+        update_metadata_field(&test.addresses, &admin, Field::Uri, "https://compto.com/metadata.json"),
+        update_metadata_field(&test.addresses, &admin, Field::Key("website".to_string()), "https://compto.com"),
+    ];
+    test.process(&instructions, &[]).await.expect("the admin can update the metadata");
+
+    let metadata = test.token_metadata().await;
+    assert_eq!(metadata.name, COMPTOKEN_NAME);
+    assert_eq!(metadata.uri, "https://compto.com/metadata.json");
+    assert_eq!(metadata.additional_metadata, [("website".to_string(), "https://compto.com".to_string())]);
+    let mint = test.addresses.mint;
+    let mint_account = test.get_account(&mint).await.unwrap();
+    assert_eq!(
+        mint_account.lamports,
+        test.rent().await.minimum_balance(mint_account.data.len()),
+        "the admin pays for the larger metadata"
+    );
+
+    let instruction = remove_metadata_key(&test.addresses, &admin, "website");
+    test.process(&[instruction], &[]).await.expect("the admin can remove a key");
+    assert_eq!(test.token_metadata().await.additional_metadata, []);
+}
+
+#[tokio::test]
+async fn test_update_metadata_field_not_admin() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+
+    let instruction = update_metadata_field(&test.addresses, &user.wallet.pubkey(), Field::Name, "Not Comptoken");
+    let result = test.process(&[instruction], &[&user.wallet]).await;
+
+    assert!(is_instruction_error(&result), "only the admin can update the metadata");
+    assert_eq!(test.token_metadata().await.name, COMPTOKEN_NAME);
+}
+
+#[tokio::test]
+async fn test_daily_distribution_refreshes_metadata() {
+    let mut test = TestContext::start_initialized().await;
+    let admin = test.payer();
+    let user = test.create_user().await;
+    let instructions = [
+        update_metadata_field(&test.addresses, &admin, Field::Key(APR_METADATA_KEY.to_string()), ""),
+        update_metadata_field(&test.addresses, &admin, Field::Key(VERIFIED_HUMANS_METADATA_KEY.to_string()), ""),
+        test_mint(&test.addresses, &user.wallet.pubkey(), &user.comptoken_token_account, MINING_AMOUNT),
+    ];
+    test.process(&instructions, &[&user.wallet]).await.expect("setup succeeds");
+
+    test.warp_days(1).await;
+    let distribution_time = normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET) + SEC_PER_DAY;
+    let instruction = daily_distribution_event(&test.addresses, &test.payer(), distribution_time, None);
+    test.process(&[instruction], &[]).await.expect("distribution succeeds");
+
+    let metadata = test.token_metadata().await;
+    let value = |key: &str| metadata.additional_metadata.iter().find(|(k, _)| k == key).map(|(_, v)| v.as_str());
+    let apr = value(APR_METADATA_KEY).expect("the apr is kept");
+    assert!(apr.ends_with('%') && apr != "0.00%", "the apr is refreshed, not {apr}");
+    assert_eq!(value(VERIFIED_HUMANS_METADATA_KEY), Some("0"));
+}
+
+#[tokio::test]
+async fn test_daily_distribution_without_refreshed_metadata() {
+    let mut test = TestContext::start_initialized().await;
+
+    test.warp_days(1).await;
+    let distribution_time = normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET) + SEC_PER_DAY;
+    let instruction = daily_distribution_event(&test.addresses, &test.payer(), distribution_time, None);
+    test.process(&[instruction], &[]).await.expect("distribution succeeds");
+
+    assert_eq!(test.token_metadata().await.additional_metadata, [], "the admin hasn't added any keys to refresh");
+}
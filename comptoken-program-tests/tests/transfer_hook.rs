@@ -0,0 +1,267 @@
+use solana_sdk::{
+    instruction::{AccountMeta, InstructionError},
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::TransactionError,
+};
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, state::ExtraAccountMetaList};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+
+use comptoken_program_tests::{
+    create_user_data_account, daily_distribution_event, get_owed_comptokens, is_instruction_error,
+    migrate_extra_account_metas, test_mint, transfer, update_extra_account_metas, TestContext, DAY_BOUNDARY_OFFSET,
+    DEFAULT_START_TIME,
+};
+use comptoken_utils::{normalize_time, SEC_PER_DAY};
+
+// MAGIC NUMBER: remain consistent with `TransferHookError::RecipientWithoutUserData` in comptoken-transfer-hook.rs
+const RECIPIENT_WITHOUT_USER_DATA: u32 = 1000;
+
+#[tokio::test]
+async fn test_transfer() {
+    let mut test = TestContext::start_initialized().await;
+    let sender = test.create_user().await;
+    let receiver = test.create_user().await;
+    let instruction = test_mint(&test.addresses, &sender.wallet.pubkey(), &sender.comptoken_token_account, 100);
+    test.process(&[instruction], &[&sender.wallet]).await.expect("mint succeeds");
+
+    let instruction = transfer(
+        &test.addresses,
+        &sender.comptoken_token_account,
+        &receiver.comptoken_token_account,
+        &sender.wallet.pubkey(),
+        40,
+    );
+    test.process(&[instruction], &[&sender.wallet]).await.expect("transfer succeeds");
+
+    assert_eq!(test.token_balance(&sender.comptoken_token_account).await, 60);
+    assert_eq!(test.token_balance(&receiver.comptoken_token_account).await, 40);
+}
+
+#[tokio::test]
+async fn test_transfer_requires_owed_comptokens_to_be_claimed() {
+    let mut test = TestContext::start_initialized().await;
+    let sender = test.create_user().await;
+    let receiver = test.create_user().await;
+    let instruction = test_mint(&test.addresses, &sender.wallet.pubkey(), &sender.comptoken_token_account, 100);
+    test.process(&[instruction], &[&sender.wallet]).await.expect("mint succeeds");
+
+    test.warp_days(1).await;
+    let distribution_time = normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET) + SEC_PER_DAY;
+    let instruction = daily_distribution_event(&test.addresses, &test.payer(), distribution_time, None);
+    test.process(&[instruction], &[]).await.expect("distribution succeeds");
+
+    let transfer_instruction = |test: &TestContext, amount| {
+        transfer(
+            &test.addresses,
+            &sender.comptoken_token_account,
+            &receiver.comptoken_token_account,
+            &sender.wallet.pubkey(),
+            amount,
+        )
+    };
+    let instruction = transfer_instruction(&test, 40);
+    let result = test.process(&[instruction], &[&sender.wallet]).await;
+    assert!(is_instruction_error(&result), "neither user has claimed today's interest");
+
+    for user in [&sender, &receiver] {
+        let instruction = get_owed_comptokens(&test.addresses, &user.wallet.pubkey(), &user.comptoken_token_account);
+        test.process(&[instruction], &[&user.wallet]).await.expect("claiming succeeds");
+    }
+    let sender_balance = test.token_balance(&sender.comptoken_token_account).await;
+    let instruction = transfer_instruction(&test, 41);
+    test.process(&[instruction], &[&sender.wallet]).await.expect("transfer succeeds");
+
+    assert_eq!(test.token_balance(&sender.comptoken_token_account).await, sender_balance - 41);
+    assert_eq!(test.token_balance(&receiver.comptoken_token_account).await, 41);
+}
+
+#[tokio::test]
+async fn test_transfer_to_first_time_recipient() {
+    let mut test = TestContext::start_initialized().await;
+    let sender = test.create_user().await;
+    let recipient = test.create_user_without_user_data().await;
+    let instruction = test_mint(&test.addresses, &sender.wallet.pubkey(), &sender.comptoken_token_account, 100);
+    test.process(&[instruction], &[&sender.wallet]).await.expect("mint succeeds");
+
+    test.warp_days(1).await;
+    let distribution_time = normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET) + SEC_PER_DAY;
+    let instruction = daily_distribution_event(&test.addresses, &test.payer(), distribution_time, None);
+    test.process(&[instruction], &[]).await.expect("distribution succeeds");
+    let instruction = get_owed_comptokens(&test.addresses, &sender.wallet.pubkey(), &sender.comptoken_token_account);
+    test.process(&[instruction], &[&sender.wallet]).await.expect("claiming succeeds");
+
+    let send = |test: &TestContext| {
+        transfer(
+            &test.addresses,
+            &sender.comptoken_token_account,
+            &recipient.comptoken_token_account,
+            &sender.wallet.pubkey(),
+            40,
+        )
+    };
+    let result = test.process(&[send(&test)], &[&sender.wallet]).await;
+    assert_eq!(
+        result,
+        Err(TransactionError::InstructionError(0, InstructionError::Custom(RECIPIENT_WITHOUT_USER_DATA))),
+        "the wallet is told the recipient needs a user data account"
+    );
+    assert_eq!(test.token_balance(&recipient.comptoken_token_account).await, 0);
+
+    let instruction = create_user_data_account(
+        &test.addresses,
+        &test.payer(),
+        &recipient.wallet.pubkey(),
+        &recipient.comptoken_token_account,
+        1,
+    );
+    test.process(&[instruction], &[&recipient.wallet])
+        .await
+        .expect("user data account is created");
+    let user_data = test.addresses.user_data(&recipient.comptoken_token_account);
+    test.with_user_data(&user_data, |user_data| assert_eq!(user_data.last_interest_payout_date, distribution_time))
+        .await;
+    test.process(&[send(&test)], &[&sender.wallet])
+        .await
+        .expect("the recipient can be sent comptokens once its user data account is created");
+    assert_eq!(test.token_balance(&recipient.comptoken_token_account).await, 40);
+}
+
+#[tokio::test]
+async fn test_update_extra_account_metas() {
+    let mut test = TestContext::start_initialized().await;
+    let admin = test.payer();
+    let sender = test.create_user().await;
+    let receiver = test.create_user().await;
+    let instruction = test_mint(&test.addresses, &sender.wallet.pubkey(), &sender.comptoken_token_account, 100);
+    test.process(&[instruction], &[&sender.wallet]).await.expect("mint succeeds");
+    let comptoken_account_metas = test.extra_account_metas().await;
+
+    let config = Pubkey::new_unique();
+    let config_meta = ExtraAccountMeta::new_with_pubkey(&config, false, false).unwrap();
+    let instruction = update_extra_account_metas(&test.addresses, &admin, &[config_meta]);
+    test.process(&[instruction], &[]).await.expect("the admin can add an account");
+
+    let extra_account_metas = test.extra_account_metas().await;
+    assert_eq!(extra_account_metas[..comptoken_account_metas.len()], comptoken_account_metas);
+    assert_eq!(extra_account_metas[comptoken_account_metas.len()..], [config_meta]);
+    let extra_account_metas_address = test.addresses.extra_account_metas;
+    let extra_account_metas_account = test.get_account(&extra_account_metas_address).await.unwrap();
+    assert_eq!(
+        extra_account_metas_account.lamports,
+        test.rent().await.minimum_balance(extra_account_metas_account.data.len()),
+        "the admin pays for the longer list"
+    );
+
+    let transfer_instruction = |test: &TestContext, amount| {
+        transfer(
+            &test.addresses,
+            &sender.comptoken_token_account,
+            &receiver.comptoken_token_account,
+            &sender.wallet.pubkey(),
+            amount,
+        )
+    };
+    let instruction = transfer_instruction(&test, 40);
+    let result = test.process(&[instruction], &[&sender.wallet]).await;
+    assert!(is_instruction_error(&result), "every transfer needs the added account");
+
+    let mut instruction = transfer_instruction(&test, 40);
+    instruction.accounts.push(AccountMeta::new_readonly(config, false));
+    test.process(&[instruction], &[&sender.wallet]).await.expect("transfer succeeds");
+    assert_eq!(test.token_balance(&receiver.comptoken_token_account).await, 40);
+
+    let instruction = update_extra_account_metas(&test.addresses, &admin, &[]);
+    test.process(&[instruction], &[]).await.expect("the admin can remove the account");
+    assert_eq!(test.extra_account_metas().await, comptoken_account_metas);
+    let extra_account_metas_account = test.get_account(&extra_account_metas_address).await.unwrap();
+    assert_eq!(
+        extra_account_metas_account.lamports,
+        test.rent().await.minimum_balance(extra_account_metas_account.data.len()),
+        "the admin gets the freed rent back"
+    );
+
+    let instruction = transfer_instruction(&test, 20);
+    test.process(&[instruction], &[&sender.wallet]).await.expect("transfer succeeds");
+    assert_eq!(test.token_balance(&receiver.comptoken_token_account).await, 60);
+}
+
+#[tokio::test]
+async fn test_update_extra_account_metas_not_admin() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+    let comptoken_account_metas = test.extra_account_metas().await;
+
+    let config_meta = ExtraAccountMeta::new_with_pubkey(&Pubkey::new_unique(), false, false).unwrap();
+    let instruction = update_extra_account_metas(&test.addresses, &user.wallet.pubkey(), &[config_meta]);
+    let result = test.process(&[instruction], &[&user.wallet]).await;
+
+    assert!(is_instruction_error(&result), "only the admin can change the transfer hook's accounts");
+    assert_eq!(test.extra_account_metas().await, comptoken_account_metas);
+}
+
+#[tokio::test]
+async fn test_migrate_legacy_extra_account_metas() {
+    let mut test = TestContext::start_initialized().await;
+    let sender = test.create_user().await;
+    let receiver = test.create_user().await;
+    let instruction = test_mint(&test.addresses, &sender.wallet.pubkey(), &sender.comptoken_token_account, 100);
+    test.process(&[instruction], &[&sender.wallet]).await.expect("mint succeeds");
+    let comptoken_account_metas = test.extra_account_metas().await;
+
+    // before the exempt accounts, the list held the comptoken program and the sender's and receiver's user data,
+    // followed by the accounts the admin added
+    let config = Pubkey::new_unique();
+    let config_meta = ExtraAccountMeta::new_with_pubkey(&config, false, false).unwrap();
+    let legacy_account_metas = [&comptoken_account_metas[..3], &[config_meta]].concat();
+    let mut data = vec![0; ExtraAccountMetaList::size_of(legacy_account_metas.len()).unwrap()];
+    ExtraAccountMetaList::init::<ExecuteInstruction>(&mut data, &legacy_account_metas).unwrap();
+    let extra_account_metas_address = test.addresses.extra_account_metas;
+    let mut extra_account_metas_account = test.get_account(&extra_account_metas_address).await.unwrap();
+    extra_account_metas_account.lamports = test.rent().await.minimum_balance(data.len());
+    extra_account_metas_account.data = data;
+    test.set_account(&extra_account_metas_address, &extra_account_metas_account);
+
+    let transfer_instruction = |test: &TestContext, amount| {
+        let mut instruction = transfer(
+            &test.addresses,
+            &sender.comptoken_token_account,
+            &receiver.comptoken_token_account,
+            &sender.wallet.pubkey(),
+            amount,
+        );
+        instruction.accounts.push(AccountMeta::new_readonly(config, false));
+        instruction
+    };
+    let instruction = transfer_instruction(&test, 40);
+    let result = test.process(&[instruction], &[&sender.wallet]).await;
+    assert!(is_instruction_error(&result), "the legacy list doesn't pass the exempt accounts");
+
+    // anyone may migrate
+    let instruction = migrate_extra_account_metas(&test.addresses, &sender.wallet.pubkey());
+    test.process(&[instruction], &[&sender.wallet]).await.expect("migration succeeds");
+
+    let extra_account_metas = test.extra_account_metas().await;
+    assert_eq!(extra_account_metas[..comptoken_account_metas.len()], comptoken_account_metas);
+    assert_eq!(extra_account_metas[comptoken_account_metas.len()..], [config_meta], "the admin's accounts are kept");
+    let extra_account_metas_account = test.get_account(&extra_account_metas_address).await.unwrap();
+    assert_eq!(
+        extra_account_metas_account.data.len(),
+        ExtraAccountMetaList::size_of(comptoken_account_metas.len() + 1).unwrap()
+    );
+    assert_eq!(
+        extra_account_metas_account.lamports,
+        test.rent().await.minimum_balance(extra_account_metas_account.data.len()),
+        "the payer pays for the longer list"
+    );
+
+    let instruction = transfer_instruction(&test, 40);
+    test.process(&[instruction], &[&sender.wallet]).await.expect("transfer succeeds");
+    assert_eq!(test.token_balance(&receiver.comptoken_token_account).await, 40);
+
+    let instruction = migrate_extra_account_metas(&test.addresses, &receiver.wallet.pubkey());
+    test.process(&[instruction], &[&receiver.wallet])
+        .await
+        .expect("migrating a current list does nothing");
+    assert_eq!(test.extra_account_metas().await, extra_account_metas);
+}
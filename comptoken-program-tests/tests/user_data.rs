@@ -0,0 +1,222 @@
+use solana_sdk::{
+    entrypoint::MAX_PERMITTED_DATA_INCREASE, hash::HASH_BYTES, instruction::InstructionError, pubkey::Pubkey,
+    signature::Signer, transaction::TransactionError,
+};
+
+use comptoken::constants::MINING_AMOUNT;
+use comptoken_program_tests::{
+    close_user_data_account, create_user_data_account, daily_distribution_event, grow_user_data_account,
+    is_instruction_error, shrink_user_data_account, test_mint, TestContext, DAY_BOUNDARY_OFFSET, DEFAULT_START_TIME,
+};
+use comptoken_utils::{events::ComptokenEvent, normalize_time, user_data::USER_DATA_MIN_SIZE, SEC_PER_DAY};
+
+#[tokio::test]
+async fn test_create_user_data_account() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+    let user_data = test.addresses.user_data(&user.comptoken_token_account);
+    let (_, bump) =
+        Pubkey::find_program_address(&[user.comptoken_token_account.as_ref()], &test.addresses.comptoken_program);
+
+    let user_data_account = test.get_account(&user_data).await.expect("user data is created");
+    assert_eq!(user_data_account.owner, test.addresses.comptoken_program);
+    assert_eq!(user_data_account.data.len(), USER_DATA_MIN_SIZE);
+    assert_eq!(user_data_account.lamports, test.rent().await.minimum_balance(USER_DATA_MIN_SIZE));
+    test.with_user_data(&user_data, |user_data| {
+        assert_eq!(user_data.last_interest_payout_date, normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET));
+        assert!(!user_data.is_verified_human());
+        assert_eq!(user_data.bump, bump, "the canonical bump is stored");
+        assert_eq!(user_data.length, 0);
+        assert_eq!(user_data.proofs.len(), 1);
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn test_create_user_data_account_too_large() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user_without_user_data().await;
+    let user_data = test.addresses.user_data(&user.comptoken_token_account);
+
+    // larger than an account created through a CPI can be
+    let proof_capacity = (MAX_PERMITTED_DATA_INCREASE / HASH_BYTES) as u64 + 1;
+    let instruction = create_user_data_account(
+        &test.addresses,
+        &test.payer(),
+        &user.wallet.pubkey(),
+        &user.comptoken_token_account,
+        proof_capacity,
+    );
+    let result = test.process(&[instruction], &[&user.wallet]).await;
+
+    assert_eq!(result, Err(TransactionError::InstructionError(0, InstructionError::InvalidArgument)));
+    assert_eq!(test.get_account(&user_data).await, None);
+}
+
+#[tokio::test]
+async fn test_grow_user_data_account() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+    let user_data = test.addresses.user_data(&user.comptoken_token_account);
+    let new_size = USER_DATA_MIN_SIZE + 2 * HASH_BYTES;
+    let rent = test.rent().await;
+
+    let grow = |test: &TestContext, proof_capacity| {
+        grow_user_data_account(
+            &test.addresses,
+            &test.payer(),
+            &user.wallet.pubkey(),
+            &user.comptoken_token_account,
+            proof_capacity,
+        )
+    };
+    let events = test.process(&[grow(&test, 3)], &[&user.wallet]).await.expect("growing succeeds");
+
+    assert_eq!(events, [ComptokenEvent::UserDataResized { user_data, new_size: new_size as u64 }]);
+    let user_data_account = test.get_account(&user_data).await.unwrap();
+    assert_eq!(user_data_account.data.len(), new_size);
+    assert_eq!(user_data_account.lamports, rent.minimum_balance(new_size), "the rent is computed by the program");
+    test.with_user_data(&user_data, |user_data| assert_eq!(user_data.proofs.len(), 3)).await;
+
+    // growing to the current capacity does nothing
+    let events = test.process(&[grow(&test, 3)], &[&user.wallet]).await.expect("growing succeeds");
+    assert_eq!(events, []);
+
+    // growing only grows
+    assert!(is_instruction_error(&test.process(&[grow(&test, 2)], &[&user.wallet]).await));
+}
+
+#[tokio::test]
+async fn test_grow_user_data_account_past_increase_limit() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+    let user_data = test.addresses.user_data(&user.comptoken_token_account);
+    // more than `MAX_PERMITTED_DATA_INCREASE` bytes of proofs
+    let proof_capacity = 400;
+    let new_size = USER_DATA_MIN_SIZE + (proof_capacity - 1) * HASH_BYTES;
+    let first_size = USER_DATA_MIN_SIZE + MAX_PERMITTED_DATA_INCREASE;
+
+    let grow = grow_user_data_account(
+        &test.addresses,
+        &test.payer(),
+        &user.wallet.pubkey(),
+        &user.comptoken_token_account,
+        proof_capacity as u64,
+    );
+    let events = test.process(&[grow.clone(), grow], &[&user.wallet]).await.expect("growing succeeds");
+
+    assert_eq!(
+        events,
+        [
+            ComptokenEvent::UserDataResized { user_data, new_size: first_size as u64 },
+            ComptokenEvent::UserDataResized { user_data, new_size: new_size as u64 },
+        ]
+    );
+    let user_data_account = test.get_account(&user_data).await.unwrap();
+    assert_eq!(user_data_account.lamports, test.rent().await.minimum_balance(new_size));
+    test.with_user_data(&user_data, |user_data| assert_eq!(user_data.proofs.len(), proof_capacity))
+        .await;
+}
+
+#[tokio::test]
+async fn test_shrink_user_data_account() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+    let user_data = test.addresses.user_data(&user.comptoken_token_account);
+    let rent = test.rent().await;
+    let grow = grow_user_data_account(
+        &test.addresses,
+        &test.payer(),
+        &user.wallet.pubkey(),
+        &user.comptoken_token_account,
+        40,
+    );
+    // the wallet is unfunded, so enough has to be freed for it to be rent exempt
+    test.process(&[grow], &[&user.wallet]).await.expect("growing succeeds");
+
+    let shrink = |test: &TestContext, proof_capacity| {
+        shrink_user_data_account(&test.addresses, &user.wallet.pubkey(), &user.comptoken_token_account, proof_capacity)
+    };
+    let events = test.process(&[shrink(&test, 2)], &[&user.wallet]).await.expect("shrinking succeeds");
+
+    let new_size = USER_DATA_MIN_SIZE + HASH_BYTES;
+    assert_eq!(events, [ComptokenEvent::UserDataResized { user_data, new_size: new_size as u64 }]);
+    let user_data_account = test.get_account(&user_data).await.unwrap();
+    assert_eq!(user_data_account.data.len(), new_size);
+    assert_eq!(user_data_account.lamports, rent.minimum_balance(new_size));
+    let freed_rent = rent.minimum_balance(USER_DATA_MIN_SIZE + 39 * HASH_BYTES) - rent.minimum_balance(new_size);
+    assert_eq!(test.get_account(&user.wallet.pubkey()).await.unwrap().lamports, freed_rent, "the wallet gets the rent");
+    test.with_user_data(&user_data, |user_data| assert_eq!(user_data.proofs.len(), 2)).await;
+
+    // shrinking only shrinks, and there is always room for one proof
+    assert!(is_instruction_error(&test.process(&[shrink(&test, 3)], &[&user.wallet]).await));
+    assert!(is_instruction_error(&test.process(&[shrink(&test, 0)], &[&user.wallet]).await));
+}
+
+#[tokio::test]
+async fn test_close_user_data_account() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+    let user_data = test.addresses.user_data(&user.comptoken_token_account);
+    let instruction = test_mint(&test.addresses, &user.wallet.pubkey(), &user.comptoken_token_account, MINING_AMOUNT);
+    test.process(&[instruction], &[&user.wallet]).await.expect("mint succeeds");
+
+    test.warp_days(1).await;
+    let distribution_time = normalize_time(DEFAULT_START_TIME, DAY_BOUNDARY_OFFSET) + SEC_PER_DAY;
+    let instruction = daily_distribution_event(&test.addresses, &test.payer(), distribution_time, None);
+    test.process(&[instruction], &[]).await.expect("distribution succeeds");
+    let expected_interest = test
+        .with_global_data(|global_data| global_data.daily_distribution_data.get_interest_for_n_days(1, MINING_AMOUNT))
+        .await;
+    let lamports = test.get_account(&user_data).await.unwrap().lamports;
+
+    let recipient = Pubkey::new_unique();
+    let instruction = close_user_data_account(
+        &test.addresses,
+        &user.wallet.pubkey(),
+        &user.comptoken_token_account,
+        &recipient,
+        false,
+    );
+    let events = test.process(&[instruction], &[&user.wallet]).await.expect("closing succeeds");
+
+    assert_eq!(
+        events,
+        [
+            ComptokenEvent::InterestClaimed {
+                user_comptoken_wallet: user.comptoken_token_account,
+                interest: expected_interest,
+                ubi: 0,
+            },
+            ComptokenEvent::UserDataClosed {
+                user_comptoken_wallet: user.comptoken_token_account,
+                user_data,
+                lamports,
+                forfeited_verified_human: false,
+            },
+        ]
+    );
+    assert_eq!(test.token_balance(&user.comptoken_token_account).await, MINING_AMOUNT + expected_interest);
+    assert_eq!(test.get_account(&user_data).await, None);
+    assert_eq!(test.get_account(&recipient).await.unwrap().lamports, lamports);
+}
+
+#[tokio::test]
+async fn test_close_user_data_account_without_recipient() {
+    let mut test = TestContext::start_initialized().await;
+    let user = test.create_user().await;
+    let user_data = test.addresses.user_data(&user.comptoken_token_account);
+
+    let mut instruction = close_user_data_account(
+        &test.addresses,
+        &user.wallet.pubkey(),
+        &user.comptoken_token_account,
+        &Pubkey::new_unique(),
+        false,
+    );
+    instruction.accounts.pop();
+    let result = test.process(&[instruction], &[&user.wallet]).await;
+
+    assert_eq!(result, Err(TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)));
+    assert!(test.get_account(&user_data).await.is_some());
+}
@@ -0,0 +1,167 @@
+use serde::{Deserialize, Serialize};
+
+use comptoken::{
+    constants::{CRANK_REWARD, FUTURE_UBI_VERIFIED_HUMANS},
+    global_data::daily_distribution_data::{DailyDistributionData, HistoricDistribution, HISTORY_SIZE},
+};
+use comptoken_utils::SEC_PER_DAY;
+
+// the (normalized) time the simulated program is initialized at. the first distribution is the day after
+pub const START_TIME: i64 = 1_721_865_600;
+
+// a row of the scenario. its values are used every day from `day` until the `day` of the next row
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Phase {
+    pub day: u64,
+    pub comptokens_mined_per_day: u64,
+    pub humans_verified_per_day: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DayResult {
+    pub day: u64,
+    // the supply before the day's distribution
+    pub supply: u64,
+    pub comptokens_mined: u64,
+    pub high_water_mark: u64,
+    pub high_water_mark_increase: u64,
+    pub interest_distribution: u64,
+    pub ubi_for_verified_humans: u64,
+    pub future_ubi_distribution: u64,
+    pub crank_reward: u64,
+    pub interest_rate: f64,
+    pub ubi: u64,
+    pub verified_humans: u64,
+    pub future_ubi_bank: u64,
+}
+
+// the parts of the program's state that the monetary policy depends on
+pub struct Simulation {
+    pub supply: u64,
+    pub future_ubi_bank: u64,
+    pub daily_distribution_data: Box<DailyDistributionData>,
+}
+
+impl Default for Simulation {
+    fn default() -> Self {
+        Self {
+            supply: 0,
+            future_ubi_bank: 0,
+            daily_distribution_data: Box::new(DailyDistributionData {
+                yesterday_supply: 0,
+                high_water_mark: 0,
+                last_daily_distribution_time: START_TIME,
+                verified_humans: 0,
+                oldest_historic_index: 0,
+                historic_distributions: [HistoricDistribution::default(); HISTORY_SIZE],
+            }),
+        }
+    }
+}
+
+impl Simulation {
+    // `day` starts at 1, the first day after initialization
+    pub fn run_day(&mut self, day: u64, comptokens_mined: u64, humans_verified: u64) -> DayResult {
+        for _ in 0..humans_verified {
+            self.verify_human();
+        }
+        self.supply += comptokens_mined;
+
+        let supply = self.supply;
+        let data = &mut self.daily_distribution_data;
+        let mut distribution = data.distribute(START_TIME + day as i64 * SEC_PER_DAY, supply, self.future_ubi_bank);
+        // someone always calls the daily distribution to collect the reward, see `GlobalData::daily_distribution_event`
+        distribution.crank_reward = CRANK_REWARD;
+        data.yesterday_supply += CRANK_REWARD;
+        let record = data.distribution_record(supply, &distribution);
+
+        self.supply += distribution.total_distributed();
+        self.future_ubi_bank += distribution.future_ubi_distribution;
+
+        DayResult {
+            day,
+            supply,
+            comptokens_mined,
+            high_water_mark: record.high_water_mark,
+            high_water_mark_increase: record.high_water_mark_increase,
+            interest_distribution: record.interest_distribution,
+            ubi_for_verified_humans: record.ubi_for_verified_humans,
+            future_ubi_distribution: record.future_ubi_distribution,
+            crank_reward: record.crank_reward,
+            interest_rate: record.interest_rate,
+            ubi: record.ubi,
+            verified_humans: record.verified_humans,
+            future_ubi_bank: self.future_ubi_bank,
+        }
+    }
+
+    // mirrors the payout from the future ubi bank in `verify_human`
+    fn verify_human(&mut self) {
+        let verified_humans = &mut self.daily_distribution_data.verified_humans;
+        if *verified_humans < FUTURE_UBI_VERIFIED_HUMANS {
+            self.future_ubi_bank -= self.future_ubi_bank / (FUTURE_UBI_VERIFIED_HUMANS - *verified_humans);
+        }
+        *verified_humans += 1;
+    }
+}
+
+// simulates `days` days, starting from a freshly initialized program
+pub fn simulate(phases: &[Phase], days: u64) -> Vec<DayResult> {
+    assert!(phases.first().is_some_and(|phase| phase.day <= 1), "the first phase must start by day 1");
+    assert!(phases.windows(2).all(|phases| phases[0].day < phases[1].day), "phases must be in order");
+
+    let mut simulation = Simulation::default();
+    (1..=days)
+        .map(|day| {
+            let phase = phases.iter().rev().find(|phase| phase.day <= day).unwrap();
+            simulation.run_day(day, phase.comptokens_mined_per_day, phase.humans_verified_per_day)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use comptoken::constants::COMPTOKEN_DISTRIBUTION_MULTIPLIER;
+
+    use super::*;
+
+    fn phase(day: u64, comptokens_mined_per_day: u64, humans_verified_per_day: u64) -> Phase {
+        Phase { day, comptokens_mined_per_day, humans_verified_per_day }
+    }
+
+    #[test]
+    fn test_simulate_years() {
+        let phases = [
+            phase(0, 10_000, 0),
+            phase(100, 1_000_000, 10),
+            phase(400, 0, 0),
+            phase(600, 50_000_000, 1_000),
+        ];
+        let results = simulate(&phases, 3 * 365);
+        assert_eq!(results.len(), 3 * 365);
+
+        for (yesterday, today) in results.iter().zip(&results[1..]) {
+            assert!(today.supply >= yesterday.supply, "supply never decreases");
+            assert!(today.high_water_mark >= yesterday.high_water_mark, "high water mark never decreases");
+            assert_eq!(today.supply, yesterday.supply + yesterday_distributed(yesterday) + today.comptokens_mined);
+        }
+        // the high water mark only increases when more is mined than ever before. `results[0]` is day 1
+        assert!(results[399..599].iter().all(|result| result.high_water_mark_increase == 0));
+        assert_eq!(results.last().unwrap().verified_humans, 10 * 300 + 1_000 * 496);
+    }
+
+    fn yesterday_distributed(result: &DayResult) -> u64 {
+        result.interest_distribution
+            + result.ubi_for_verified_humans
+            + result.future_ubi_distribution
+            + result.crank_reward
+    }
+
+    #[test]
+    fn test_first_day() {
+        let results = simulate(&[phase(1, 10_000, 0)], 1);
+        assert_eq!(results[0].supply, 10_000);
+        assert_eq!(results[0].high_water_mark_increase, 10_000);
+        assert_eq!(yesterday_distributed(&results[0]), 10_000 * COMPTOKEN_DISTRIBUTION_MULTIPLIER + CRANK_REWARD);
+    }
+}
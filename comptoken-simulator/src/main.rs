@@ -0,0 +1,22 @@
+use std::{error::Error, process::ExitCode};
+
+use comptoken_simulator::{simulate, Phase};
+use comptoken_utils::quiet_logs::silence_program_logs;
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    // the distribution code would otherwise log every simulated day
+    silence_program_logs();
+    let args: Vec<String> = std::env::args().collect();
+    let [_, scenario_path, days, output_path] = args.as_slice() else {
+        eprintln!("usage: comptoken-simulator <scenario.csv> <days> <output.csv>");
+        return Ok(ExitCode::FAILURE);
+    };
+
+    let phases = csv::Reader::from_path(scenario_path)?.deserialize().collect::<Result<Vec<Phase>, _>>()?;
+    let mut writer = csv::Writer::from_path(output_path)?;
+    for result in simulate(&phases, days.parse()?) {
+        writer.serialize(result)?;
+    }
+    writer.flush()?;
+    Ok(ExitCode::SUCCESS)
+}
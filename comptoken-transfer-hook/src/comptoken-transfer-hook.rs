@@ -1,40 +1,63 @@
-mod generated;
 mod verify_accounts;
 
 use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList};
-use spl_token_2022::solana_program::{
-    account_info::{next_account_info, AccountInfo},
-    entrypoint,
-    entrypoint::ProgramResult,
-    msg,
-    pubkey::Pubkey,
-    rent::Rent,
-    sysvar::Sysvar,
+use spl_token_2022::{
+    extension::StateWithExtensions,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint,
+        entrypoint::ProgramResult,
+        msg,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction,
+        sysvar::Sysvar,
+    },
+    state::{Account, Mint},
 };
 use spl_transfer_hook_interface::instruction::{ExecuteInstruction, TransferHookInstruction};
+use spl_type_length_value::state::TlvStateBorrowed;
 
-use comptoken_utils::{create_pda, user_data::UserData};
-
-use generated::{
-    COMPTOKEN_ID, COMPTO_FUTURE_UBI_BANK_ACCOUNT_PUBKEY, COMPTO_INTEREST_BANK_ACCOUNT_PUBKEY,
-    COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_PUBKEY, EXTRA_ACCOUNT_METAS_ACCOUNT_SEEDS,
+use comptoken_utils::{
+    create_pda,
+    exempt_accounts::{ExemptAccounts, EXEMPT_ACCOUNTS_SEED},
+    invoke_signed_verified,
+    user_data::UserDataFields,
 };
+
 use verify_accounts::{
-    verify_account_meta_storage_account, verify_comptoken_mint, verify_comptoken_program, verify_destination_account,
-    verify_mint_account, verify_mint_authority, verify_source_account, verify_source_authority_account,
-    verify_user_data_account, VerifiedAccountInfo,
+    verify_account_meta_storage_account, verify_comptoken_program, verify_destination_account,
+    verify_exempt_accounts_account, verify_mint_account, verify_mint_authority, verify_source_account,
+    verify_source_authority_account, verify_system_program, verify_uncreated_user_data_account,
+    verify_user_data_account, VerifiedAccountInfo, EXTRA_ACCOUNT_METAS_SEED,
 };
 
 entrypoint!(process_instruction);
+
+// returned as `ProgramError::Custom`, which token-2022 passes on from the hook as it is. token-2022 numbers its own
+// errors from 0, so these start well above them
+#[repr(u32)]
+pub enum TransferHookError {
+    // the recipient has no user data account yet. it has to create one (see the comptoken program's create user data
+    // account instruction) before it can be sent comptokens
+    RecipientWithoutUserData = 1000,
+}
+
+impl From<TransferHookError> for ProgramError {
+    fn from(error: TransferHookError) -> Self {
+        ProgramError::Custom(error as u32)
+    }
+}
+
 pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
-    msg!("{:?}", instruction_data);
     match TransferHookInstruction::unpack(instruction_data)? {
         TransferHookInstruction::Execute { amount } => process_execute(program_id, accounts, amount),
         TransferHookInstruction::InitializeExtraAccountMetaList { extra_account_metas } => {
             process_initialize_extra_account_meta_list(program_id, accounts, extra_account_metas)
         }
-        TransferHookInstruction::UpdateExtraAccountMetaList { extra_account_metas: _ } => {
-            panic!("instruction not implemented");
+        TransferHookInstruction::UpdateExtraAccountMetaList { extra_account_metas } => {
+            process_update_extra_account_meta_list(program_id, accounts, extra_account_metas)
         }
     }
 }
@@ -48,38 +71,62 @@ fn process_execute(program_id: &Pubkey, accounts: &[AccountInfo], _amount: u64)
     //      []: account meta storage account
     //      []: Comptoken Program
     //      []: Source Data Account
-    //      []: Destination Data Account
+    //      []: Destination Data Account (a recipient without one is rejected with `RecipientWithoutUserData`)
+    //      []: Exempt Accounts Account
 
     let account_info_iter = &mut accounts.iter();
     let source_account = verify_source_account(next_account_info(account_info_iter)?);
     // required as part of the transferhook API to identify that comptokens are being transferred
-    let _comptoken_mint_account = verify_comptoken_mint(next_account_info(account_info_iter)?);
+    let comptoken_mint_account = verify_mint_account(next_account_info(account_info_iter)?);
     let destination_account = verify_destination_account(next_account_info(account_info_iter)?);
     // also required as part of the transferhook API but we don't use
     let _source_account_authority = verify_source_authority_account(next_account_info(account_info_iter)?);
     // used by transferhook to get the comptoken program and the PDAs before it gets here
-    let _account_meta_storage_account =
-        verify_account_meta_storage_account(next_account_info(account_info_iter)?, program_id, false);
+    let (account_meta_storage_account, _) = verify_account_meta_storage_account(
+        next_account_info(account_info_iter)?,
+        &comptoken_mint_account,
+        program_id,
+        false,
+    );
     // used by transferhook to generate the PDAs before it gets here
-    let _comptoken_program = verify_comptoken_program(next_account_info(account_info_iter)?);
-    let source_data_account = verify_user_data_account(next_account_info(account_info_iter)?, &source_account);
-    let destination_data_account =
-        verify_user_data_account(next_account_info(account_info_iter)?, &destination_account);
-
-    // Account must either be a bank account or have no unpaid interest or UBI amounts to do a transfer
-    if !is_bank(source_account.key) {
-        let source_user_data: &UserData = (&source_data_account).into();
-        assert!(source_user_data.is_current());
-        if !is_bank(destination_account.key) {
-            let destination_user_data: &UserData = (&destination_data_account).into();
+    let comptoken_program =
+        verify_comptoken_program(next_account_info(account_info_iter)?, &account_meta_storage_account);
+    // banks don't have data accounts, so these are only verified when they are used
+    let source_data_account = next_account_info(account_info_iter)?;
+    let destination_data_account = next_account_info(account_info_iter)?;
+    let exempt_accounts_account =
+        verify_exempt_accounts_account(next_account_info(account_info_iter)?, &comptoken_program);
+
+    // Account must either be a bank account, be exempted by the admin, or have no unpaid interest or UBI amounts to do a
+    // transfer
+    if !is_bank(&source_account, &comptoken_mint_account) {
+        if !is_exempt(&source_account, &exempt_accounts_account) {
+            let source_data_account =
+                verify_user_data_account(source_data_account, &source_account, &comptoken_program);
+            let source_user_data: &UserDataFields = (&source_data_account).into();
+            assert!(source_user_data.is_current());
+        }
+        if !is_bank(&destination_account, &comptoken_mint_account)
+            && !is_exempt(&destination_account, &exempt_accounts_account)
+        {
+            // the hook can't create the account, since it has no payer, so the wallet is told to create it instead
+            if destination_data_account.data_is_empty() {
+                verify_uncreated_user_data_account(destination_data_account, &destination_account, &comptoken_program);
+                msg!("the recipient has no user data account");
+                return Err(TransferHookError::RecipientWithoutUserData.into());
+            }
+            let destination_data_account =
+                verify_user_data_account(destination_data_account, &destination_account, &comptoken_program);
+            let destination_user_data: &UserDataFields = (&destination_data_account).into();
             assert!(destination_user_data.is_current());
         }
     }
     Ok(())
 }
 
+// the comptoken accounts always come first, followed by `extra_account_metas`
 fn process_initialize_extra_account_meta_list(
-    program_id: &Pubkey, accounts: &[AccountInfo], _extra_account_metas: Vec<ExtraAccountMeta>,
+    program_id: &Pubkey, accounts: &[AccountInfo], extra_account_metas: Vec<ExtraAccountMeta>,
 ) -> ProgramResult {
     //  Accounts
     //      [w]: account meta storage account
@@ -87,6 +134,7 @@ fn process_initialize_extra_account_meta_list(
     //      [s]: Mint authority
     //      []: System program
     //      [sw]: payer account (not part of the standard)
+    //      []: Comptoken Program (not part of the standard)
 
     let account_info_iter = &mut accounts.iter();
     let account_meta_storage_account = next_account_info(account_info_iter)?;
@@ -94,13 +142,104 @@ fn process_initialize_extra_account_meta_list(
     let mint_authority = next_account_info(account_info_iter)?;
     let _system_program = next_account_info(account_info_iter)?;
     let payer_account = next_account_info(account_info_iter)?;
+    let comptoken_program = next_account_info(account_info_iter)?;
 
     let mint_account = verify_mint_account(mint_account);
-    let account_meta_storage_account =
-        verify_account_meta_storage_account(account_meta_storage_account, program_id, true);
+    let (account_meta_storage_account, account_meta_storage_bump) =
+        verify_account_meta_storage_account(account_meta_storage_account, &mint_account, program_id, true);
     let _mint_authority = verify_mint_authority(mint_authority, &mint_account, true, false);
     let payer_account = VerifiedAccountInfo::verify_account_signer_or_writable(payer_account, true, true);
+    // the mint authority decides which comptoken program the transfers are checked against
+    let comptoken_program = VerifiedAccountInfo::verify_account_signer_or_writable(comptoken_program, false, false);
+
+    let mut account_metas = comptoken_account_metas(comptoken_program.key)?.to_vec();
+    account_metas.extend(extra_account_metas);
+
+    let account_size = ExtraAccountMetaList::size_of(account_metas.len())? as u64;
+
+    let lamports = Rent::get()?.minimum_balance(account_size as usize);
+
+    let signer_seeds: &[&[&[u8]]] =
+        &[&[EXTRA_ACCOUNT_METAS_SEED, mint_account.key.as_ref(), &[account_meta_storage_bump]]];
+
+    create_pda(&payer_account, &account_meta_storage_account, lamports, account_size, program_id, signer_seeds)?;
 
+    ExtraAccountMetaList::init::<ExecuteInstruction>(
+        &mut account_meta_storage_account.try_borrow_mut_data()?,
+        &account_metas,
+    )?;
+
+    Ok(())
+}
+
+// replaces the accounts that follow the comptoken accounts with `extra_account_metas`, e.g. to pass a new account to
+// every transfer. the comptoken program recorded at initialization can't be changed
+fn process_update_extra_account_meta_list(
+    program_id: &Pubkey, accounts: &[AccountInfo], extra_account_metas: Vec<ExtraAccountMeta>,
+) -> ProgramResult {
+    //  Accounts
+    //      [w]: account meta storage account
+    //      []: Mint
+    //      [s]: Mint authority
+    //      [sw]: payer account (not part of the standard, pays for growth and receives any freed rent)
+    //      []: System program (not part of the standard)
+
+    let account_info_iter = &mut accounts.iter();
+    let account_meta_storage_account = next_account_info(account_info_iter)?;
+    let mint_account = next_account_info(account_info_iter)?;
+    let mint_authority = next_account_info(account_info_iter)?;
+    let payer_account = next_account_info(account_info_iter)?;
+    let system_program = next_account_info(account_info_iter)?;
+
+    let mint_account = verify_mint_account(mint_account);
+    let (account_meta_storage_account, _) =
+        verify_account_meta_storage_account(account_meta_storage_account, &mint_account, program_id, true);
+    let _mint_authority = verify_mint_authority(mint_authority, &mint_account, true, false);
+    let payer_account = VerifiedAccountInfo::verify_account_signer_or_writable(payer_account, true, true);
+    let system_program = verify_system_program(system_program);
+
+    let comptoken_program = {
+        let data = account_meta_storage_account.try_borrow_data()?;
+        let tlv_state = TlvStateBorrowed::unpack(&data)?;
+        let account_metas = ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(&tlv_state)?;
+        Pubkey::new_from_array(account_metas.data()[0].address_config)
+    };
+    let mut account_metas = comptoken_account_metas(&comptoken_program)?.to_vec();
+    account_metas.extend(extra_account_metas);
+
+    // the list is rewritten in place, so the account grows before the list is rewritten and shrinks after
+    let old_size = account_meta_storage_account.data_len();
+    let new_size = ExtraAccountMetaList::size_of(account_metas.len())?;
+    if new_size > old_size {
+        let lamports = Rent::get()?
+            .minimum_balance(new_size)
+            .saturating_sub(account_meta_storage_account.lamports());
+        invoke_signed_verified(
+            &system_instruction::transfer(payer_account.key, account_meta_storage_account.key, lamports),
+            &[&payer_account, &account_meta_storage_account, &system_program],
+            &[],
+        )?;
+        account_meta_storage_account.realloc(new_size, false)?;
+    }
+    ExtraAccountMetaList::update::<ExecuteInstruction>(
+        &mut account_meta_storage_account.try_borrow_mut_data()?,
+        &account_metas,
+    )?;
+    if new_size < old_size {
+        account_meta_storage_account.realloc(new_size, false)?;
+        let excess = account_meta_storage_account
+            .lamports()
+            .saturating_sub(Rent::get()?.minimum_balance(new_size));
+        **account_meta_storage_account.try_borrow_mut_lamports()? -= excess;
+        **payer_account.try_borrow_mut_lamports()? += excess;
+    }
+
+    Ok(())
+}
+
+// the accounts `process_execute` reads: the comptoken program, the sender's and receiver's user data, and the exempt
+// accounts
+fn comptoken_account_metas(comptoken_program: &Pubkey) -> Result<[ExtraAccountMeta; 4], ProgramError> {
     const SENDER_ACCOUNT_INDEX: u8 = 0;
     // mint = 1
     const RECEIVER_ACCOUNT_INDEX: u8 = 2;
@@ -108,9 +247,9 @@ fn process_initialize_extra_account_meta_list(
     // account meta storage account = 4
     const COMPTOKEN_PROGRAM_INDEX: u8 = 5;
 
-    let account_metas = vec![
+    Ok([
         // index: 5
-        ExtraAccountMeta::new_with_pubkey(&COMPTOKEN_ID, false, false)?,
+        ExtraAccountMeta::new_with_pubkey(comptoken_program, false, false)?,
         // index: 6
         ExtraAccountMeta::new_external_pda_with_seeds(
             COMPTOKEN_PROGRAM_INDEX,
@@ -125,26 +264,27 @@ fn process_initialize_extra_account_meta_list(
             false,
             false,
         )?,
-    ];
-
-    let account_size = ExtraAccountMetaList::size_of(account_metas.len())? as u64;
-
-    let lamports = Rent::get()?.minimum_balance(account_size as usize);
-
-    let signer_seeds: &[&[&[u8]]] = &[EXTRA_ACCOUNT_METAS_ACCOUNT_SEEDS];
-
-    create_pda(&payer_account, &account_meta_storage_account, lamports, account_size, program_id, signer_seeds)?;
-
-    ExtraAccountMetaList::init::<ExecuteInstruction>(
-        &mut account_meta_storage_account.try_borrow_mut_data()?,
-        &account_metas,
-    )?;
+        // index: 8
+        ExtraAccountMeta::new_external_pda_with_seeds(
+            COMPTOKEN_PROGRAM_INDEX,
+            &[Seed::Literal { bytes: EXEMPT_ACCOUNTS_SEED.to_vec() }],
+            false,
+            false,
+        )?,
+    ])
+}
 
-    Ok(())
+fn is_exempt(token_account: &VerifiedAccountInfo, exempt_accounts_account: &VerifiedAccountInfo) -> bool {
+    !exempt_accounts_account.data_is_empty()
+        && ExemptAccounts::from(exempt_accounts_account).contains(token_account.key)
 }
 
-fn is_bank(address: &Pubkey) -> bool {
-    *address == COMPTO_INTEREST_BANK_ACCOUNT_PUBKEY
-        || *address == COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_PUBKEY
-        || *address == COMPTO_FUTURE_UBI_BANK_ACCOUNT_PUBKEY
+// the banks are the only token accounts owned by the mint authority (the comptoken global data), so they can be
+// recognized without knowing the comptoken program's addresses at build time
+fn is_bank(token_account: &VerifiedAccountInfo, mint: &VerifiedAccountInfo) -> bool {
+    let token_account_data = token_account.try_borrow_data().unwrap();
+    let token_account = StateWithExtensions::<Account>::unpack(&token_account_data).unwrap();
+    let mint_data = mint.try_borrow_data().unwrap();
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data).unwrap();
+    mint.base.mint_authority.contains(&token_account.base.owner)
 }
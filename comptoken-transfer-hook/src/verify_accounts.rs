@@ -1,20 +1,27 @@
+use spl_tlv_account_resolution::state::ExtraAccountMetaList;
 use spl_token_2022::{
     extension::StateWithExtensions,
-    solana_program::{account_info::AccountInfo, pubkey::Pubkey},
+    solana_program::{account_info::AccountInfo, pubkey::Pubkey, system_program},
     state::Mint,
 };
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use spl_type_length_value::state::TlvStateBorrowed;
 
 pub use comptoken_utils::verify_accounts::VerifiedAccountInfo;
+use comptoken_utils::{
+    exempt_accounts::{ExemptAccountsFields, EXEMPT_ACCOUNTS_SEED},
+    user_data::UserDataFields,
+};
 
-use crate::generated::{COMPTOKEN_ID, EXTRA_ACCOUNT_METAS_ACCOUNT_SEEDS, MINT_ADDRESS};
+pub const EXTRA_ACCOUNT_METAS_SEED: &[u8] = b"extra-account-metas";
 
 pub fn verify_account_meta_storage_account<'a>(
-    account: &AccountInfo<'a>, program_id: &Pubkey, needs_writable: bool,
-) -> VerifiedAccountInfo<'a> {
-    VerifiedAccountInfo::verify_pda_with_bump(
+    account: &AccountInfo<'a>, mint: &VerifiedAccountInfo<'a>, program_id: &Pubkey, needs_writable: bool,
+) -> (VerifiedAccountInfo<'a>, u8) {
+    VerifiedAccountInfo::verify_pda(
         account,
         program_id,
-        EXTRA_ACCOUNT_METAS_ACCOUNT_SEEDS,
+        &[EXTRA_ACCOUNT_METAS_SEED, mint.key.as_ref()],
         false,
         needs_writable,
     )
@@ -25,10 +32,6 @@ pub fn verify_mint_account<'a>(account: &AccountInfo<'a>) -> VerifiedAccountInfo
     VerifiedAccountInfo::verify_account_signer_or_writable(account, false, false)
 }
 
-pub fn verify_comptoken_mint<'a>(account: &AccountInfo<'a>) -> VerifiedAccountInfo<'a> {
-    VerifiedAccountInfo::verify_specific_address(account, &MINT_ADDRESS, false, false)
-}
-
 pub fn verify_mint_authority<'a>(
     account: &AccountInfo<'a>, mint: &VerifiedAccountInfo, needs_signer: bool, needs_writable: bool,
 ) -> VerifiedAccountInfo<'a> {
@@ -38,6 +41,10 @@ pub fn verify_mint_authority<'a>(
     VerifiedAccountInfo::verify_account_signer_or_writable(account, needs_signer, needs_writable)
 }
 
+pub fn verify_system_program<'a>(account: &AccountInfo<'a>) -> VerifiedAccountInfo<'a> {
+    VerifiedAccountInfo::verify_specific_address(account, &system_program::ID, false, false)
+}
+
 pub fn verify_source_account<'a>(account: &AccountInfo<'a>) -> VerifiedAccountInfo<'a> {
     VerifiedAccountInfo::verify_account_signer_or_writable(account, false, false)
 }
@@ -50,12 +57,61 @@ pub fn verify_source_authority_account<'a>(account: &AccountInfo<'a>) -> Verifie
     VerifiedAccountInfo::verify_account_signer_or_writable(account, false, false)
 }
 
-pub fn verify_comptoken_program<'a>(account: &AccountInfo<'a>) -> VerifiedAccountInfo<'a> {
-    VerifiedAccountInfo::verify_specific_address(account, &COMPTOKEN_ID, false, false)
+// the comptoken program is the first of the extra account metas recorded by the mint authority
+pub fn verify_comptoken_program<'a>(
+    account: &AccountInfo<'a>, account_meta_storage: &VerifiedAccountInfo<'a>,
+) -> VerifiedAccountInfo<'a> {
+    let data = account_meta_storage.try_borrow_data().unwrap();
+    let tlv_state = TlvStateBorrowed::unpack(&data).unwrap();
+    let extra_account_metas = ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(&tlv_state).unwrap();
+    assert_eq!(extra_account_metas.data()[0].address_config, account.key.to_bytes());
+    VerifiedAccountInfo::verify_account_signer_or_writable(account, false, false)
 }
 
+// the user data knows its own bump, so its address is checked without searching for the bump
 pub fn verify_user_data_account<'a>(
-    account: &AccountInfo<'a>, user_account: &VerifiedAccountInfo<'a>,
+    account: &AccountInfo<'a>, user_account: &VerifiedAccountInfo<'a>, comptoken_program: &VerifiedAccountInfo<'a>,
 ) -> VerifiedAccountInfo<'a> {
-    VerifiedAccountInfo::verify_pda(account, &COMPTOKEN_ID, &[user_account.key.as_ref()], false, false).0
+    assert_eq!(account.owner, comptoken_program.key);
+    let bump = {
+        let data = account.try_borrow_data().unwrap();
+        UserDataFields::from_account_data(&data).unwrap().bump
+    };
+    VerifiedAccountInfo::verify_pda_with_bump(
+        account,
+        comptoken_program.key,
+        &[user_account.key.as_ref(), &[bump]],
+        false,
+        false,
+    )
+}
+
+// a user data account that hasn't been created yet, e.g. for a first time recipient
+pub fn verify_uncreated_user_data_account<'a>(
+    account: &AccountInfo<'a>, user_account: &VerifiedAccountInfo<'a>, comptoken_program: &VerifiedAccountInfo<'a>,
+) -> VerifiedAccountInfo<'a> {
+    assert!(account.data_is_empty());
+    VerifiedAccountInfo::verify_pda(account, comptoken_program.key, &[user_account.key.as_ref()], false, false).0
+}
+
+// the exempt accounts aren't created until the admin exempts the first account
+pub fn verify_exempt_accounts_account<'a>(
+    account: &AccountInfo<'a>, comptoken_program: &VerifiedAccountInfo<'a>,
+) -> VerifiedAccountInfo<'a> {
+    if account.data_is_empty() {
+        return VerifiedAccountInfo::verify_pda(account, comptoken_program.key, &[EXEMPT_ACCOUNTS_SEED], false, false)
+            .0;
+    }
+    assert_eq!(account.owner, comptoken_program.key);
+    let bump = {
+        let data = account.try_borrow_data().unwrap();
+        ExemptAccountsFields::from_account_data(&data).unwrap().bump
+    };
+    VerifiedAccountInfo::verify_pda_with_bump(
+        account,
+        comptoken_program.key,
+        &[EXEMPT_ACCOUNTS_SEED, &[bump]],
+        false,
+        false,
+    )
 }
@@ -1,5 +1,11 @@
+pub mod distribution_record;
+pub mod events;
+pub mod exempt_accounts;
+#[cfg(feature = "off_chain")]
+pub mod quiet_logs;
 pub mod user_data;
 pub mod verify_accounts;
+pub mod versioned_account;
 
 use spl_token_2022::solana_program::{
     entrypoint::ProgramResult, instruction::Instruction, program::invoke_signed, pubkey::Pubkey, system_instruction,
@@ -39,6 +45,24 @@ pub fn get_current_time() -> i64 {
     1_721_940_656
 }
 
-pub fn normalize_time(time: i64) -> i64 {
-    time - time % SEC_PER_DAY // midnight UTC+0
+// the start of the day `time` is in. each day starts `day_boundary_offset` seconds after midnight UTC+0
+pub fn normalize_time(time: i64, day_boundary_offset: i64) -> i64 {
+    time - (time - day_boundary_offset).rem_euclid(SEC_PER_DAY)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_normalize_time() {
+        for day_boundary_offset in [0, 1, 6 * 3_600, SEC_PER_DAY - 1] {
+            let day_start = 1_721_865_600 + day_boundary_offset;
+            assert_eq!(normalize_time(day_start, day_boundary_offset), day_start);
+            assert_eq!(normalize_time(day_start + 1, day_boundary_offset), day_start);
+            assert_eq!(normalize_time(day_start + SEC_PER_DAY - 1, day_boundary_offset), day_start);
+            assert_eq!(normalize_time(day_start + SEC_PER_DAY, day_boundary_offset), day_start + SEC_PER_DAY);
+            assert_eq!(normalize_time(day_start - 1, day_boundary_offset), day_start - SEC_PER_DAY);
+        }
+    }
 }
@@ -0,0 +1,40 @@
+use bytemuck::{Pod, Zeroable};
+
+use crate::versioned_account::{account_size, from_account_data, VersionedAccount};
+
+pub const DISTRIBUTION_RECORD_SEED: &[u8] = b"distribution record";
+
+// a permanent record of a single daily distribution, stored in a PDA seeded by
+// `[DISTRIBUTION_RECORD_SEED, distribution_time.to_le_bytes()]`, so the whole monetary history can be reconstructed
+// even after it has rotated out of `historic_distributions`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+// CHANGES TO THIS STRUCT NEED A NEW `VERSION` AND TO BE REFLECTED IN accounts.js
+pub struct DistributionRecord {
+    pub distribution_time: i64,
+    // the supply before the distribution was minted
+    pub supply: u64,
+    pub high_water_mark: u64,
+    pub high_water_mark_increase: u64,
+    pub interest_distribution: u64,
+    pub ubi_for_verified_humans: u64,
+    pub future_ubi_distribution: u64,
+    pub crank_reward: u64,
+    pub verified_humans: u64,
+    pub interest_rate: f64,
+    pub ubi: u64,
+}
+
+impl VersionedAccount for DistributionRecord {
+    const DISCRIMINATOR: [u8; 8] = *b"DistRcrd";
+    const VERSION: u8 = 1;
+}
+
+pub const DISTRIBUTION_RECORD_SIZE: usize = account_size::<DistributionRecord>();
+
+impl<'a> From<&'a [u8]> for &'a DistributionRecord {
+    fn from(data: &'a [u8]) -> Self {
+        assert_eq!(data.len(), DISTRIBUTION_RECORD_SIZE);
+        from_account_data(data).unwrap()
+    }
+}
@@ -0,0 +1,286 @@
+use base64::{engine::general_purpose::STANDARD, Engine};
+use spl_token_2022::solana_program::{hash::Hash, log::sol_log_data, pubkey::Pubkey};
+
+// the first field of every event, so events can be told apart from other `sol_log_data` output
+pub const EVENT_TAG: &[u8] = b"comptoken event";
+
+const LOG_PREFIX: &str = "Program data: ";
+
+// emitted with `sol_log_data`, so indexers can parse what happened without relying on `msg!` output
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComptokenEvent {
+    ProofAccepted {
+        user_comptoken_wallet: Pubkey,
+        proof: Hash,
+    },
+    TokensMinted {
+        destination: Pubkey,
+        amount: u64,
+    },
+    DailyDistribution {
+        distribution_time: i64,
+        interest_distribution: u64,
+        ubi_for_verified_humans: u64,
+        future_ubi_distribution: u64,
+        crank_reward: u64,
+        // the program's supply and high water mark once the distribution is minted. holders can burn their comptokens
+        // without the program seeing it, so indexers resync from these
+        supply: u64,
+        high_water_mark: u64,
+    },
+    InterestClaimed {
+        user_comptoken_wallet: Pubkey,
+        interest: u64,
+        ubi: u64,
+    },
+    HumanVerified {
+        user_comptoken_wallet: Pubkey,
+        future_ubi: u64,
+    },
+    UserDataResized {
+        user_data: Pubkey,
+        new_size: u64,
+    },
+    AccountMigrated {
+        account: Pubkey,
+        version: u8,
+        new_size: u64,
+    },
+    UserDataClosed {
+        user_comptoken_wallet: Pubkey,
+        user_data: Pubkey,
+        lamports: u64,
+        forfeited_verified_human: bool,
+    },
+}
+
+impl ComptokenEvent {
+    pub fn emit(&self) {
+        sol_log_data(&[EVENT_TAG, &self.to_bytes()]);
+    }
+
+    // 1 byte: <variant>
+    // followed by the variant's fields, in order, as little endian integers or raw bytes
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        match self {
+            ComptokenEvent::ProofAccepted { user_comptoken_wallet, proof } => {
+                bytes.push(0);
+                bytes.extend_from_slice(user_comptoken_wallet.as_ref());
+                bytes.extend_from_slice(proof.as_ref());
+            }
+            ComptokenEvent::TokensMinted { destination, amount } => {
+                bytes.push(1);
+                bytes.extend_from_slice(destination.as_ref());
+                bytes.extend_from_slice(&amount.to_le_bytes());
+            }
+            ComptokenEvent::DailyDistribution {
+                distribution_time,
+                interest_distribution,
+                ubi_for_verified_humans,
+                future_ubi_distribution,
+                crank_reward,
+                supply,
+                high_water_mark,
+            } => {
+                bytes.push(2);
+                bytes.extend_from_slice(&distribution_time.to_le_bytes());
+                bytes.extend_from_slice(&interest_distribution.to_le_bytes());
+                bytes.extend_from_slice(&ubi_for_verified_humans.to_le_bytes());
+                bytes.extend_from_slice(&future_ubi_distribution.to_le_bytes());
+                bytes.extend_from_slice(&crank_reward.to_le_bytes());
+                bytes.extend_from_slice(&supply.to_le_bytes());
+                bytes.extend_from_slice(&high_water_mark.to_le_bytes());
+            }
+            ComptokenEvent::InterestClaimed { user_comptoken_wallet, interest, ubi } => {
+                bytes.push(3);
+                bytes.extend_from_slice(user_comptoken_wallet.as_ref());
+                bytes.extend_from_slice(&interest.to_le_bytes());
+                bytes.extend_from_slice(&ubi.to_le_bytes());
+            }
+            ComptokenEvent::HumanVerified { user_comptoken_wallet, future_ubi } => {
+                bytes.push(4);
+                bytes.extend_from_slice(user_comptoken_wallet.as_ref());
+                bytes.extend_from_slice(&future_ubi.to_le_bytes());
+            }
+            ComptokenEvent::UserDataResized { user_data, new_size } => {
+                bytes.push(5);
+                bytes.extend_from_slice(user_data.as_ref());
+                bytes.extend_from_slice(&new_size.to_le_bytes());
+            }
+            ComptokenEvent::AccountMigrated { account, version, new_size } => {
+                bytes.push(6);
+                bytes.extend_from_slice(account.as_ref());
+                bytes.push(*version);
+                bytes.extend_from_slice(&new_size.to_le_bytes());
+            }
+            ComptokenEvent::UserDataClosed {
+                user_comptoken_wallet,
+                user_data,
+                lamports,
+                forfeited_verified_human,
+            } => {
+                bytes.push(7);
+                bytes.extend_from_slice(user_comptoken_wallet.as_ref());
+                bytes.extend_from_slice(user_data.as_ref());
+                bytes.extend_from_slice(&lamports.to_le_bytes());
+                bytes.push(*forfeited_verified_human as u8);
+            }
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (variant, bytes) = bytes.split_first()?;
+        let mut reader = Reader(bytes);
+        let event = match variant {
+            0 => ComptokenEvent::ProofAccepted {
+                user_comptoken_wallet: reader.pubkey()?,
+                proof: reader.hash()?,
+            },
+            1 => ComptokenEvent::TokensMinted { destination: reader.pubkey()?, amount: reader.u64()? },
+            2 => ComptokenEvent::DailyDistribution {
+                distribution_time: reader.i64()?,
+                interest_distribution: reader.u64()?,
+                ubi_for_verified_humans: reader.u64()?,
+                future_ubi_distribution: reader.u64()?,
+                crank_reward: reader.u64()?,
+                supply: reader.u64()?,
+                high_water_mark: reader.u64()?,
+            },
+            3 => ComptokenEvent::InterestClaimed {
+                user_comptoken_wallet: reader.pubkey()?,
+                interest: reader.u64()?,
+                ubi: reader.u64()?,
+            },
+            4 => ComptokenEvent::HumanVerified {
+                user_comptoken_wallet: reader.pubkey()?,
+                future_ubi: reader.u64()?,
+            },
+            5 => ComptokenEvent::UserDataResized { user_data: reader.pubkey()?, new_size: reader.u64()? },
+            6 => ComptokenEvent::AccountMigrated {
+                account: reader.pubkey()?,
+                version: reader.u8()?,
+                new_size: reader.u64()?,
+            },
+            7 => ComptokenEvent::UserDataClosed {
+                user_comptoken_wallet: reader.pubkey()?,
+                user_data: reader.pubkey()?,
+                lamports: reader.u64()?,
+                forfeited_verified_human: reader.bool()?,
+            },
+            _ => return None,
+        };
+        reader.0.is_empty().then_some(event)
+    }
+
+    // parses a log message written by `emit`. returns `None` for any other log message
+    pub fn from_log(log: &str) -> Option<Self> {
+        let mut fields = log.strip_prefix(LOG_PREFIX)?.split(' ').map(|field| STANDARD.decode(field).ok());
+        if fields.next()?? != EVENT_TAG {
+            return None;
+        }
+        let event = Self::from_bytes(&fields.next()??)?;
+        fields.next().is_none().then_some(event)
+    }
+}
+
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn read<const N: usize>(&mut self) -> Option<[u8; N]> {
+        let bytes = self.0.get(..N)?.try_into().ok()?;
+        self.0 = &self.0[N..];
+        Some(bytes)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.read().map(u8::from_le_bytes)
+    }
+
+    fn bool(&mut self) -> Option<bool> {
+        match self.u8()? {
+            0 => Some(false),
+            1 => Some(true),
+            _ => None,
+        }
+    }
+
+    fn u64(&mut self) -> Option<u64> {
+        self.read().map(u64::from_le_bytes)
+    }
+
+    fn i64(&mut self) -> Option<i64> {
+        self.read().map(i64::from_le_bytes)
+    }
+
+    fn pubkey(&mut self) -> Option<Pubkey> {
+        self.read().map(Pubkey::new_from_array)
+    }
+
+    fn hash(&mut self) -> Option<Hash> {
+        self.read().map(Hash::new_from_array)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn all_events() -> [ComptokenEvent; 8] {
+        [
+            ComptokenEvent::ProofAccepted {
+                user_comptoken_wallet: Pubkey::new_unique(),
+                proof: Hash::new_unique(),
+            },
+            ComptokenEvent::TokensMinted { destination: Pubkey::new_unique(), amount: 10_000 },
+            ComptokenEvent::DailyDistribution {
+                distribution_time: 1_721_865_600,
+                interest_distribution: 1,
+                ubi_for_verified_humans: 2,
+                future_ubi_distribution: 3,
+                crank_reward: 4,
+                supply: 10_010,
+                high_water_mark: 5,
+            },
+            ComptokenEvent::InterestClaimed {
+                user_comptoken_wallet: Pubkey::new_unique(),
+                interest: 5,
+                ubi: 6,
+            },
+            ComptokenEvent::HumanVerified { user_comptoken_wallet: Pubkey::new_unique(), future_ubi: 7 },
+            ComptokenEvent::UserDataResized { user_data: Pubkey::new_unique(), new_size: 120 },
+            ComptokenEvent::AccountMigrated { account: Pubkey::new_unique(), version: 2, new_size: 152 },
+            ComptokenEvent::UserDataClosed {
+                user_comptoken_wallet: Pubkey::new_unique(),
+                user_data: Pubkey::new_unique(),
+                lamports: 1_614_720,
+                forfeited_verified_human: true,
+            },
+        ]
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        for event in all_events() {
+            let bytes = event.to_bytes();
+            assert_eq!(ComptokenEvent::from_bytes(&bytes), Some(event));
+            assert_eq!(ComptokenEvent::from_bytes(&bytes[..bytes.len() - 1]), None);
+            assert_eq!(ComptokenEvent::from_bytes(&[bytes.as_slice(), &[0]].concat()), None);
+        }
+        assert_eq!(ComptokenEvent::from_bytes(&[8]), None);
+        assert_eq!(ComptokenEvent::from_bytes(&[]), None);
+    }
+
+    #[test]
+    fn test_from_log() {
+        for event in all_events() {
+            let log = format!("{}{} {}", LOG_PREFIX, STANDARD.encode(EVENT_TAG), STANDARD.encode(event.to_bytes()));
+            assert_eq!(ComptokenEvent::from_log(&log), Some(event));
+        }
+        let event = STANDARD.encode(all_events()[0].to_bytes());
+        assert_eq!(ComptokenEvent::from_log(&format!("{}{} {}", LOG_PREFIX, STANDARD.encode(b"other"), event)), None);
+        assert_eq!(ComptokenEvent::from_log(&format!("{}{}", LOG_PREFIX, STANDARD.encode(EVENT_TAG))), None);
+        assert_eq!(ComptokenEvent::from_log("Program log: Interest: 5"), None);
+    }
+}
@@ -0,0 +1,186 @@
+use std::ops::Deref;
+
+use bytemuck::{Pod, Zeroable};
+use spl_token_2022::solana_program::{program_error::ProgramError, pubkey::Pubkey, pubkey::PUBKEY_BYTES};
+
+use crate::{
+    versioned_account::{from_body, from_body_mut, AccountHeader, VersionedAccount, ACCOUNT_HEADER_SIZE},
+    VerifiedAccountInfo,
+};
+
+pub const EXEMPT_ACCOUNTS_SEED: &[u8] = b"Exempt Accounts";
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+// CHANGES TO THIS STRUCT NEED A NEW `ExemptAccounts::VERSION` AND TO BE REFLECTED IN accounts.js
+pub struct ExemptAccountsFields {
+    // stored so that the address can be checked without searching for the bump
+    pub bump: u8,
+    _padding: [u8; 7],
+    pub length: usize,
+}
+
+// the size of an exempt accounts account with room for `capacity` accounts
+pub const fn exempt_accounts_size(capacity: usize) -> usize {
+    ACCOUNT_HEADER_SIZE + std::mem::size_of::<ExemptAccountsFields>() + capacity * PUBKEY_BYTES
+}
+
+// the comptoken token accounts, e.g. escrows and pools, that the transfer hook lets send and receive comptokens
+// without their owed comptokens being claimed first. stored in a PDA of the comptoken program seeded by
+// `[EXEMPT_ACCOUNTS_SEED]` and maintained by the admin. the `length` accounts are kept sorted, so the transfer hook
+// finds them by binary search, and any room after them is unused
+#[derive(Debug)]
+pub struct ExemptAccounts<'a> {
+    fields: &'a mut ExemptAccountsFields,
+    accounts: &'a mut [Pubkey],
+}
+
+impl VersionedAccount for ExemptAccounts<'_> {
+    const DISCRIMINATOR: [u8; 8] = *b"ExmptAct";
+    const VERSION: u8 = 1;
+}
+
+impl<'a> ExemptAccounts<'a> {
+    fn from_body(body: &'a mut [u8]) -> Result<Self, ProgramError> {
+        if body.len() < std::mem::size_of::<ExemptAccountsFields>() {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let (fields, accounts) = body.split_at_mut(std::mem::size_of::<ExemptAccountsFields>());
+        let fields: &mut ExemptAccountsFields = from_body_mut(fields)?;
+        let accounts: &mut [Pubkey] =
+            bytemuck::try_cast_slice_mut(accounts).map_err(|_| ProgramError::InvalidAccountData)?;
+        if fields.length > accounts.len() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self { fields, accounts })
+    }
+
+    // for a newly created account, whose data is still zeroed
+    pub fn initialize(data: &'a mut [u8], bump: u8) -> Result<Self, ProgramError> {
+        let exempt_accounts = Self::from_body(AccountHeader::initialize::<Self>(data)?)?;
+        exempt_accounts.fields.bump = bump;
+        Ok(exempt_accounts)
+    }
+
+    pub fn accounts(&self) -> &[Pubkey] {
+        &self.accounts[..self.fields.length]
+    }
+
+    pub fn contains(&self, account: &Pubkey) -> bool {
+        self.accounts().binary_search(account).is_ok()
+    }
+
+    pub fn insert(&mut self, account: &Pubkey) {
+        let length = self.fields.length;
+        let index = self.accounts().binary_search(account).expect_err("account should not be exempt already");
+        assert!(length < self.accounts.len(), "Exempt Accounts Account not large enough");
+
+        // shift the larger accounts up a slot to keep the accounts sorted
+        self.accounts.copy_within(index..length, index + 1);
+        self.accounts[index] = *account;
+        self.fields.length += 1;
+    }
+
+    // leaves the last slot unused, so the account can shrink
+    pub fn remove(&mut self, account: &Pubkey) {
+        let length = self.fields.length;
+        let index = self.accounts().binary_search(account).expect("account should be exempt");
+
+        self.accounts.copy_within(index + 1..length, index);
+        self.accounts[length - 1] = Pubkey::default();
+        self.fields.length -= 1;
+    }
+}
+
+impl ExemptAccountsFields {
+    // only reads the fields, so the account doesn't need to be borrowed mutably
+    pub fn from_account_data(data: &[u8]) -> Result<&Self, ProgramError> {
+        from_body(AccountHeader::body::<ExemptAccounts>(data)?)
+    }
+}
+
+impl Deref for ExemptAccounts<'_> {
+    type Target = ExemptAccountsFields;
+
+    fn deref(&self) -> &Self::Target {
+        self.fields
+    }
+}
+
+impl<'a> TryFrom<&'a mut [u8]> for ExemptAccounts<'a> {
+    type Error = ProgramError;
+
+    fn try_from(data: &'a mut [u8]) -> Result<Self, Self::Error> {
+        Self::from_body(AccountHeader::body_mut::<Self>(data)?)
+    }
+}
+
+impl<'a> From<&VerifiedAccountInfo<'a>> for ExemptAccounts<'a> {
+    fn from(account: &VerifiedAccountInfo<'a>) -> Self {
+        account.data_unchecked().try_into().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    // account data is 8 byte aligned, so the tests' data needs to be as well
+    fn aligned_data(capacity: usize) -> Vec<u64> {
+        vec![0; exempt_accounts_size(capacity) / std::mem::size_of::<u64>()]
+    }
+
+    #[test]
+    fn test_insert_sorted() {
+        let mut data = aligned_data(3);
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        let mut exempt_accounts = ExemptAccounts::initialize(data, 255).unwrap();
+        let mut accounts = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        for account in accounts.iter().rev() {
+            exempt_accounts.insert(account);
+        }
+        accounts.sort();
+
+        assert_eq!(exempt_accounts.bump, 255);
+        assert_eq!(exempt_accounts.accounts(), accounts);
+        assert!(accounts.iter().all(|account| exempt_accounts.contains(account)));
+        assert!(!exempt_accounts.contains(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut data = aligned_data(3);
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        let mut exempt_accounts = ExemptAccounts::initialize(data, 255).unwrap();
+        let mut accounts = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        accounts.sort();
+        accounts.iter().for_each(|account| exempt_accounts.insert(account));
+
+        exempt_accounts.remove(&accounts[1]);
+        assert_eq!(exempt_accounts.accounts(), [accounts[0], accounts[2]]);
+        assert!(!exempt_accounts.contains(&accounts[1]));
+        // the freed slot is at the end
+        assert_eq!(exempt_accounts.accounts[2], Pubkey::default());
+    }
+
+    #[test]
+    #[should_panic(expected = "account should not be exempt already")]
+    fn test_insert_duplicate() {
+        let mut data = aligned_data(2);
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        let mut exempt_accounts = ExemptAccounts::initialize(data, 255).unwrap();
+        let account = Pubkey::new_unique();
+        exempt_accounts.insert(&account);
+        exempt_accounts.insert(&account);
+    }
+
+    #[test]
+    #[should_panic(expected = "Exempt Accounts Account not large enough")]
+    fn test_insert_full() {
+        let mut data = aligned_data(1);
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        let mut exempt_accounts = ExemptAccounts::initialize(data, 255).unwrap();
+        exempt_accounts.insert(&Pubkey::new_unique());
+        exempt_accounts.insert(&Pubkey::new_unique());
+    }
+}
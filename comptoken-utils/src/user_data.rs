@@ -1,118 +1,189 @@
-use spl_token_2022::solana_program::{hash::Hash, hash::HASH_BYTES, program_error::ProgramError};
+use std::ops::{Deref, DerefMut};
 
-use crate::VerifiedAccountInfo;
+use bytemuck::{Pod, Zeroable};
+use spl_pod::primitives::PodBool;
+use spl_token_2022::solana_program::{hash::Hash, hash::HASH_BYTES, msg, program_error::ProgramError};
+
+use crate::{
+    versioned_account::{
+        from_body, from_body_mut, migrate_legacy_account_data, AccountHeader, MigratableAccount, VersionedAccount,
+        ACCOUNT_HEADER_SIZE,
+    },
+    VerifiedAccountInfo,
+};
 
 #[repr(C)]
-#[derive(Debug)]
-// CHANGES TO THE SIZE OF THIS STRUCT NEED TO BE REFLECTED IN test_client.js and accounts.js
-pub struct UserDataBase<T: ?Sized> {
-    // capacity is stored in the fat pointer
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+// CHANGES TO THIS STRUCT NEED A NEW `UserData::VERSION` AND TO BE REFLECTED IN accounts.js
+pub struct UserDataFields {
     pub last_interest_payout_date: i64,
-    pub is_verified_human: bool,
-    // padding: [u8; 7],
+    pub is_verified_human: PodBool,
+    // stored so that the address can be checked without searching for the bump
+    pub bump: u8,
+    // accounts from before the proofs were kept sorted have this zeroed, so their proofs are sorted on the next insert
+    proofs_sorted: PodBool,
+    _padding: [u8; 5],
     pub length: usize,
     pub recent_blockhash: Hash,
-    pub proofs: T,
 }
 
-pub const USER_DATA_MIN_SIZE: usize = std::mem::size_of::<UserDataBase<Hash>>();
+// the header, the fields, and room for at least one proof
+pub const USER_DATA_MIN_SIZE: usize = ACCOUNT_HEADER_SIZE + std::mem::size_of::<UserDataFields>() + HASH_BYTES;
+
+// user data from before the account header had the same fields and proofs, without the header or the bump. its size is
+// never the size of user data with a header, so it is recognised by its size
+const LEGACY_USER_DATA_MIN_SIZE: usize = USER_DATA_MIN_SIZE - ACCOUNT_HEADER_SIZE;
+
+// the size of a user data account with room for `proof_capacity` proofs. `None` if the capacity is 0 or too large
+pub fn user_data_size(proof_capacity: usize) -> Option<usize> {
+    let extra_proofs = proof_capacity.checked_sub(1)?;
+    extra_proofs.checked_mul(HASH_BYTES)?.checked_add(USER_DATA_MIN_SIZE)
+}
+
+// a user data account: the fields are followed by the proofs, which fill the rest of the account, so the capacity is
+// `proofs.len()`. the `length` proofs of `recent_blockhash` are kept sorted, so a duplicate is found by binary search
+#[derive(Debug)]
+pub struct UserData<'a> {
+    fields: &'a mut UserDataFields,
+    pub proofs: &'a mut [Hash],
+}
+
+impl VersionedAccount for UserData<'_> {
+    const DISCRIMINATOR: [u8; 8] = *b"UserData";
+    const VERSION: u8 = 1;
+}
+
+// a new version adds the step from the version before it here, keeping the proofs after the new fields
+impl MigratableAccount for UserData<'_> {
+    fn migrated_size(_version: u8, data: &[u8]) -> usize {
+        data.len()
+    }
+
+    fn migrate_body(version: u8, _body: &mut [u8]) -> Result<(), ProgramError> {
+        msg!("no migration from user data version {}", version);
+        Err(ProgramError::InvalidAccountData)
+    }
+}
+
+impl<'a> UserData<'a> {
+    fn from_body(body: &'a mut [u8]) -> Result<Self, ProgramError> {
+        if body.len() < USER_DATA_MIN_SIZE - ACCOUNT_HEADER_SIZE {
+            return Err(ProgramError::AccountDataTooSmall);
+        }
+        let (fields, proofs) = body.split_at_mut(std::mem::size_of::<UserDataFields>());
+        let fields: &mut UserDataFields = from_body_mut(fields)?;
+        let proofs: &mut [Hash] = bytemuck::try_cast_slice_mut(proofs).map_err(|_| ProgramError::InvalidAccountData)?;
+        if fields.length > proofs.len() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(Self { fields, proofs })
+    }
+
+    // for a newly created account, whose data is still zeroed. `today` is the start of the current day
+    pub fn initialize(data: &'a mut [u8], bump: u8, today: i64) -> Result<Self, ProgramError> {
+        let user_data = Self::from_body(AccountHeader::initialize::<Self>(data)?)?;
+        user_data.fields.last_interest_payout_date = today;
+        user_data.fields.is_verified_human = false.into();
+        user_data.fields.bump = bump;
+        user_data.fields.proofs_sorted = true.into();
+        Ok(user_data)
+    }
 
-pub type UserData = UserDataBase<[Hash]>;
+    pub fn is_legacy(data: &[u8]) -> bool {
+        data.len()
+            .checked_sub(LEGACY_USER_DATA_MIN_SIZE)
+            .is_some_and(|extra_proofs_size| extra_proofs_size % HASH_BYTES == 0)
+    }
+
+    // for legacy user data, which has already grown by `ACCOUNT_HEADER_SIZE` bytes. its proofs were stored in the order
+    // they were submitted, so they are left marked as unsorted
+    pub fn migrate_legacy(data: &'a mut [u8], bump: u8) -> Result<Self, ProgramError> {
+        let legacy_size = data.len().checked_sub(ACCOUNT_HEADER_SIZE).ok_or(ProgramError::AccountDataTooSmall)?;
+        let user_data = Self::from_body(migrate_legacy_account_data::<Self>(data, legacy_size, 0)?)?;
+        user_data.fields.bump = bump;
+        user_data.fields.proofs_sorted = false.into();
+        user_data.fields._padding = [0; 5];
+        Ok(user_data)
+    }
 
-impl UserData {
     pub fn update(&mut self, new_blockhash: &Hash) {
         if self.recent_blockhash != *new_blockhash {
             self.recent_blockhash = *new_blockhash;
             self.length = 0;
+            self.proofs_sorted = true.into();
         }
     }
 
     pub fn insert(&mut self, new_proof: &Hash, new_blockhash: &Hash) {
         // new_proof and new_blockhash have already been verified
         self.update(new_blockhash);
-        assert!(!self.contains(new_proof), "proof should be new");
-
-        match self.proofs.get_mut(self.length) {
-            // If proofs is not full, write the new proof into the next slot
-            Some(proof) => *proof = *new_proof,
-            None => panic!("User Data Account not large enough, consider reallocing"),
+        let length = self.fields.length;
+        if !bool::from(self.proofs_sorted) {
+            self.proofs[..length].sort_unstable();
+            self.proofs_sorted = true.into();
         }
+        let index = self.proofs[..length].binary_search(new_proof).expect_err("proof should be new");
+        assert!(!self.is_full(), "User Data Account not large enough, consider reallocing or minting with a payer");
+
+        // shift the larger proofs up a slot to keep the proofs sorted
+        self.proofs.copy_within(index..length, index + 1);
+        self.proofs[index] = *new_proof;
         self.length += 1;
     }
 
-    fn contains(&self, new_proof: &Hash) -> bool {
-        self.into_iter().any(|proof| proof == new_proof)
+    // whether another proof of the current blockhash needs the account to grow first
+    pub fn is_full(&self) -> bool {
+        self.length >= self.proofs.len()
     }
+}
 
-    pub fn initialize(&mut self) {
-        self.last_interest_payout_date = crate::normalize_time(crate::get_current_time());
-        self.is_verified_human = false;
+impl UserDataFields {
+    // only reads the fields, so the account doesn't need to be borrowed mutably
+    pub fn from_account_data(data: &[u8]) -> Result<&Self, ProgramError> {
+        from_body(AccountHeader::body::<UserData>(data)?)
     }
 
+    pub fn is_verified_human(&self) -> bool {
+        self.is_verified_human.into()
+    }
+
+    // the payout date is the start of a day, so this doesn't need to know where the program's days start
     pub fn is_current(&self) -> bool {
-        self.last_interest_payout_date == crate::normalize_time(crate::get_current_time())
+        (0..crate::SEC_PER_DAY).contains(&(crate::get_current_time() - self.last_interest_payout_date))
     }
 }
 
-impl TryFrom<&mut [u8]> for &mut UserData {
-    type Error = ProgramError;
+impl Deref for UserData<'_> {
+    type Target = UserDataFields;
 
-    fn try_from(data: &mut [u8]) -> Result<Self, Self::Error> {
-        assert!(data.len() >= USER_DATA_MIN_SIZE);
-        assert!((data.len() - USER_DATA_MIN_SIZE) % HASH_BYTES == 0);
-
-        let capacity = ((data.len() - USER_DATA_MIN_SIZE) / HASH_BYTES) + 1;
-        // Two step process to dynamically create ProofStorage from the account data array of bytes
-        // Step 1: Create a slice from the account data array of bytes, so the wide pointer extra capacity
-        // field is correct after step 2
-        // This slice is not a strictly accurate representation of the data, since the size is incorrect,
-        // but step 2 will correct this
-        let data_hashes = unsafe { std::slice::from_raw_parts_mut(data.as_mut_ptr() as *mut _, capacity) };
-        // Step 2: Create a ProofStorage from the slice
-        // the chaining of `as` first converts a reference to a pointer, and then converts the pointer to a *ProofStorage* pointer
-        // Then we convert the ProofStorage pointer to a mutable reference to a ProofStorage
-        // This is how the rust docs say to do it... :/
-        // https://doc.rust-lang.org/std/mem/fn.transmute.html
-        let result = unsafe { &mut *(data_hashes as *mut _ as *mut UserData) };
-        assert!(result.length <= result.proofs.len());
-        Ok(result)
+    fn deref(&self) -> &Self::Target {
+        self.fields
     }
 }
 
-impl TryFrom<&[u8]> for &UserData {
+impl DerefMut for UserData<'_> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        self.fields
+    }
+}
+
+impl<'a> TryFrom<&'a mut [u8]> for UserData<'a> {
     type Error = ProgramError;
 
-    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
-        assert!(data.len() >= USER_DATA_MIN_SIZE);
-        assert!((data.len() - USER_DATA_MIN_SIZE) % HASH_BYTES == 0);
-
-        let capacity = ((data.len() - USER_DATA_MIN_SIZE) / HASH_BYTES) + 1;
-        // Two step process to dynamically create ProofStorage from the account data array of bytes
-        // Step 1: Create a slice from the account data array of bytes, so the wide pointer extra capacity
-        // field is correct after step 2
-        // This slice is not a strictly accurate representation of the data, since the size is incorrect,
-        // but step 2 will correct this
-        let data_hashes = unsafe { std::slice::from_raw_parts(data.as_ptr() as *const _, capacity) };
-        // Step 2: Create a ProofStorage from the slice
-        // the chaining of `as` first converts a reference to a pointer, and then converts the pointer to a *ProofStorage* pointer
-        // Then we convert the ProofStorage pointer to a mutable reference to a ProofStorage
-        // This is how the rust docs say to do it... :/
-        // https://doc.rust-lang.org/std/mem/fn.transmute.html
-        let result = unsafe { &*(data_hashes as *const _ as *const UserData) };
-        assert!(result.length <= result.proofs.len());
-        Ok(result)
+    fn try_from(data: &'a mut [u8]) -> Result<Self, Self::Error> {
+        Self::from_body(AccountHeader::body_mut::<Self>(data)?)
     }
 }
 
-impl<'a> From<&VerifiedAccountInfo<'a>> for &'a mut UserData {
-    fn from(account: &VerifiedAccountInfo) -> Self {
-        account.data.borrow_mut().as_mut().try_into().unwrap()
+impl<'a> From<&VerifiedAccountInfo<'a>> for UserData<'a> {
+    fn from(account: &VerifiedAccountInfo<'a>) -> Self {
+        account.data_unchecked().try_into().unwrap()
     }
 }
 
-impl<'a> From<&VerifiedAccountInfo<'a>> for &'a UserData {
-    fn from(account: &VerifiedAccountInfo) -> Self {
-        account.data.borrow().as_ref().try_into().unwrap()
+impl<'a> From<&VerifiedAccountInfo<'a>> for &'a UserDataFields {
+    fn from(account: &VerifiedAccountInfo<'a>) -> Self {
+        UserDataFields::from_account_data(account.data_unchecked()).unwrap()
     }
 }
 
@@ -140,21 +211,21 @@ impl<'a> Iterator for MutHashIter<'a> {
     }
 }
 
-impl<'a> IntoIterator for &'a UserData {
+impl<'a> IntoIterator for &'a UserData<'_> {
     type Item = &'a Hash;
     type IntoIter = HashIter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
-        HashIter { iter: self.proofs.iter().take(self.length) }
+        HashIter { iter: self.proofs.iter().take(self.fields.length) }
     }
 }
 
-impl<'a> IntoIterator for &'a mut UserData {
+impl<'a> IntoIterator for &'a mut UserData<'_> {
     type Item = &'a mut Hash;
     type IntoIter = MutHashIter<'a>;
 
     fn into_iter(self) -> Self::IntoIter {
-        MutHashIter { iter: self.proofs.iter_mut().take(self.length) }
+        MutHashIter { iter: self.proofs.iter_mut().take(self.fields.length) }
     }
 }
 
@@ -163,7 +234,11 @@ mod test {
 
     use super::*;
     use hex_literal::hex;
-    use std::cmp::max;
+    use proptest::prelude::*;
+    use std::{
+        cmp::max,
+        panic::{catch_unwind, AssertUnwindSafe},
+    };
 
     #[derive(Debug)]
     struct ProofAndBlockhash {
@@ -202,20 +277,15 @@ mod test {
         Hash::new_from_array(hex!("4b227777d4dd1fc61c6f884f48641d02b4d121d3fd328cb08b5531fcacdabf8a")),
     ];
 
-    /// # SAFETY
-    ///
-    /// data must be large enough to hold a ProofStorage of length proofs.len()
-    unsafe fn write_data(data: &mut [u8], length: usize, blockhash: &Hash, proofs: &[Hash]) {
-        let len_ptr = data.as_mut_ptr().offset(16) as *mut usize;
-        *len_ptr = length;
-
-        let blockhash_ptr = data.as_mut_ptr().offset(24) as *mut Hash;
-        *blockhash_ptr = *blockhash;
-
-        for (i, proof) in proofs.iter().enumerate() {
-            let proof_ptr = data.as_mut_ptr().add(56 + i * HASH_BYTES) as *mut Hash;
-            *proof_ptr = *proof;
-        }
+    // writes the header and the fields directly, so that invalid fields can be written too
+    fn write_data(data: &mut [u8], length: usize, blockhash: &Hash, proofs: &[Hash]) {
+        let body = AccountHeader::initialize::<UserData>(data).unwrap();
+        let (fields, stored_proofs) = body.split_at_mut(std::mem::size_of::<UserDataFields>());
+        let fields: &mut UserDataFields = from_body_mut(fields).unwrap();
+        fields.length = length;
+        fields.recent_blockhash = *blockhash;
+        let stored_proofs: &mut [Hash] = bytemuck::cast_slice_mut(stored_proofs);
+        stored_proofs[..proofs.len()].copy_from_slice(proofs);
     }
 
     fn run_test(test_values: TestValues) {
@@ -227,15 +297,15 @@ mod test {
             "input data len is not large enough for the test"
         );
 
-        unsafe { write_data(input.data, input.length, &input.stored_blockhash, input.proofs) }
+        write_data(input.data, input.length, &input.stored_blockhash, input.proofs);
 
-        let user_data: &mut UserData = input.data.try_into().expect("panicked already if failed");
+        let mut user_data: UserData = input.data.try_into().expect("panicked already if failed");
 
         for pow in input.new_proofs {
             user_data.insert(&pow.proof, &pow.blockhash);
         }
 
-        let user_data: &UserData = user_data;
+        let user_data = &user_data;
         let output = output.expect("panicked already if not Some");
 
         assert_eq!(user_data.length, output.length, "hash_storage is the correct length");
@@ -253,7 +323,7 @@ mod test {
     fn test_try_from() {
         run_test(TestValues {
             input: TestValuesInput {
-                data: &mut [0_u8; USER_DATA_MIN_SIZE],
+                data: as_bytes(&mut aligned_data(1)),
                 length: 1,
                 stored_blockhash: POSSIBLE_BLOCKHASHES[0],
                 proofs: &[POSSIBLE_PROOFS[0]],
@@ -267,11 +337,19 @@ mod test {
         })
     }
 
+    #[test]
+    fn test_user_data_size() {
+        assert_eq!(user_data_size(0), None);
+        assert_eq!(user_data_size(1), Some(USER_DATA_MIN_SIZE));
+        assert_eq!(user_data_size(3), Some(USER_DATA_MIN_SIZE + 2 * HASH_BYTES));
+        assert_eq!(user_data_size(usize::MAX), None);
+    }
+
     #[test]
     fn test_insert() {
         run_test(TestValues {
             input: TestValuesInput {
-                data: &mut [0_u8; USER_DATA_MIN_SIZE],
+                data: as_bytes(&mut aligned_data(1)),
                 length: 0,
                 stored_blockhash: POSSIBLE_BLOCKHASHES[0],
                 proofs: &[],
@@ -292,7 +370,7 @@ mod test {
     fn test_insert_new() {
         run_test(TestValues {
             input: TestValuesInput {
-                data: &mut [0_u8; USER_DATA_MIN_SIZE],
+                data: as_bytes(&mut aligned_data(1)),
                 length: 1,
                 stored_blockhash: POSSIBLE_BLOCKHASHES[0],
                 proofs: &[POSSIBLE_PROOFS[0]],
@@ -309,6 +387,89 @@ mod test {
         })
     }
 
+    #[test]
+    fn test_insert_sorted() {
+        let mut proofs = POSSIBLE_PROOFS;
+        proofs.sort();
+        run_test(TestValues {
+            input: TestValuesInput {
+                data: as_bytes(&mut aligned_data(2)),
+                length: 0,
+                stored_blockhash: POSSIBLE_BLOCKHASHES[0],
+                proofs: &[],
+                new_proofs: &[
+                    ProofAndBlockhash { proof: proofs[1], blockhash: POSSIBLE_BLOCKHASHES[0] },
+                    ProofAndBlockhash { proof: proofs[0], blockhash: POSSIBLE_BLOCKHASHES[0] },
+                ],
+            },
+            output: Some(TestValuesOutput {
+                length: 2,
+                stored_blockhash: POSSIBLE_BLOCKHASHES[0],
+                proofs: &proofs,
+            }),
+        })
+    }
+
+    #[test]
+    fn test_insert_sorts_unsorted_proofs() {
+        let mut proofs = POSSIBLE_PROOFS;
+        proofs.sort();
+        let new_proof = Hash::new_from_array([0; HASH_BYTES]);
+        // write_data leaves `proofs_sorted` zeroed, like the accounts that stored the proofs in submission order
+        run_test(TestValues {
+            input: TestValuesInput {
+                data: as_bytes(&mut aligned_data(3)),
+                length: 2,
+                stored_blockhash: POSSIBLE_BLOCKHASHES[0],
+                proofs: &[proofs[1], proofs[0]],
+                new_proofs: &[ProofAndBlockhash { proof: new_proof, blockhash: POSSIBLE_BLOCKHASHES[0] }],
+            },
+            output: Some(TestValuesOutput {
+                length: 3,
+                stored_blockhash: POSSIBLE_BLOCKHASHES[0],
+                proofs: &[new_proof, proofs[0], proofs[1]],
+            }),
+        })
+    }
+
+    #[test]
+    fn test_migrate_legacy() {
+        let mut data = aligned_data(2);
+        let data = as_bytes(&mut data);
+        // the legacy layout: last_interest_payout_date, is_verified_human, padding, length, recent_blockhash, proofs
+        let legacy_size = data.len() - ACCOUNT_HEADER_SIZE;
+        assert!(UserData::is_legacy(&data[..legacy_size]));
+        data[..8].copy_from_slice(&5_i64.to_le_bytes());
+        data[8] = 1;
+        data[16..24].copy_from_slice(&2_usize.to_le_bytes());
+        data[24..56].copy_from_slice(POSSIBLE_BLOCKHASHES[0].as_ref());
+        data[56..88].copy_from_slice(POSSIBLE_PROOFS[0].as_ref());
+        data[88..120].copy_from_slice(POSSIBLE_PROOFS[1].as_ref());
+
+        let user_data = UserData::migrate_legacy(data, 254).unwrap();
+        assert_eq!(user_data.last_interest_payout_date, 5);
+        assert!(user_data.is_verified_human());
+        assert_eq!(user_data.bump, 254);
+        assert_eq!(user_data.length, 2);
+        assert_eq!(user_data.recent_blockhash, POSSIBLE_BLOCKHASHES[0]);
+        assert_eq!(user_data.proofs, POSSIBLE_PROOFS);
+        assert!(!bool::from(user_data.proofs_sorted));
+        // user data with a header is never mistaken for legacy user data
+        assert!(!UserData::is_legacy(data));
+    }
+
+    #[test]
+    fn test_is_full() {
+        let mut data = aligned_data(1);
+        let data = as_bytes(&mut data);
+        write_data(data, 1, &POSSIBLE_BLOCKHASHES[0], &[POSSIBLE_PROOFS[0]]);
+        let mut user_data: UserData = data.try_into().unwrap();
+        assert!(user_data.is_full());
+        // the proof of an old blockhash doesn't take up room
+        user_data.update(&POSSIBLE_BLOCKHASHES[1]);
+        assert!(!user_data.is_full());
+    }
+
     #[test]
     #[should_panic(expected = "proof should be new")]
     fn test_insert_duplicate() {
@@ -316,7 +477,7 @@ mod test {
             input: TestValuesInput {
                 // size is 1 proof bigger than it needs to be so that we can test the duplicate
                 // failure case specifically and not worry about getting an out-of-size error.
-                data: &mut [0_u8; USER_DATA_MIN_SIZE + HASH_BYTES],
+                data: as_bytes(&mut aligned_data(2)),
                 length: 1,
                 stored_blockhash: POSSIBLE_BLOCKHASHES[0],
                 proofs: &[POSSIBLE_PROOFS[0]],
@@ -328,4 +489,91 @@ mod test {
             output: None,
         })
     }
+
+    // account data is 8 byte aligned, so the tests' data needs to be as well
+    fn aligned_data(capacity: usize) -> Vec<u64> {
+        vec![0; user_data_size(capacity).unwrap() / std::mem::size_of::<u64>()]
+    }
+
+    fn as_bytes(data: &mut [u64]) -> &mut [u8] {
+        // SAFETY: u8 has no alignment or validity requirements, and the byte slice covers exactly the same memory
+        unsafe { std::slice::from_raw_parts_mut(data.as_mut_ptr() as *mut u8, std::mem::size_of_val(data)) }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_try_from(
+            capacity in 1_usize..16,
+            length in 0_usize..32,
+            proofs in prop::collection::vec(any::<[u8; HASH_BYTES]>().prop_map(Hash::new_from_array), 16),
+        ) {
+            let mut data = aligned_data(capacity);
+            let data = as_bytes(&mut data);
+            write_data(data, length, &POSSIBLE_BLOCKHASHES[0], &proofs[..capacity]);
+
+            if length > capacity {
+                let result = catch_unwind(AssertUnwindSafe(|| {
+                    let _: UserData = (&mut *data).try_into().unwrap();
+                }));
+                prop_assert!(result.is_err(), "length larger than the capacity should be rejected");
+                return Ok(());
+            }
+
+            let user_data: UserData = data.try_into().unwrap();
+            prop_assert_eq!(user_data.proofs.len(), capacity);
+            prop_assert_eq!(user_data.length, length);
+            prop_assert_eq!(user_data.recent_blockhash, POSSIBLE_BLOCKHASHES[0]);
+            prop_assert!(user_data.into_iter().eq(&proofs[..length]));
+        }
+
+        #[test]
+        fn prop_try_from_invalid_size(size in 0_usize..(USER_DATA_MIN_SIZE + 4 * HASH_BYTES)) {
+            prop_assume!(size < USER_DATA_MIN_SIZE || !(size - USER_DATA_MIN_SIZE).is_multiple_of(HASH_BYTES));
+            let mut data = aligned_data(5);
+            // a valid header, so that only the size is wrong
+            AccountHeader::initialize::<UserData>(as_bytes(&mut data)).unwrap();
+            let data = &mut as_bytes(&mut data)[..size];
+
+            let result = catch_unwind(AssertUnwindSafe(|| {
+                let _: UserData = data.try_into().unwrap();
+            }));
+            prop_assert!(result.is_err(), "data of an invalid size should be rejected");
+        }
+
+        // compares a series of inserts against a simple model of the proof storage
+        #[test]
+        fn prop_insert(
+            capacity in 1_usize..8,
+            inserts in prop::collection::vec((0_u8..4, 0_usize..POSSIBLE_BLOCKHASHES.len()), 0..24),
+        ) {
+            let mut data = aligned_data(capacity);
+            let data = as_bytes(&mut data);
+            write_data(data, 0, &POSSIBLE_BLOCKHASHES[0], &[]);
+            let mut user_data: UserData = data.try_into().unwrap();
+
+            let mut expected_blockhash = POSSIBLE_BLOCKHASHES[0];
+            let mut expected_proofs = Vec::new();
+            for (proof, blockhash) in inserts {
+                let proof = Hash::new_from_array([proof; HASH_BYTES]);
+                let blockhash = POSSIBLE_BLOCKHASHES[blockhash];
+                if blockhash != expected_blockhash {
+                    expected_blockhash = blockhash;
+                    expected_proofs.clear();
+                }
+
+                if expected_proofs.contains(&proof) || expected_proofs.len() == capacity {
+                    let result = catch_unwind(AssertUnwindSafe(|| user_data.insert(&proof, &blockhash)));
+                    prop_assert!(result.is_err(), "duplicate proofs and inserts past the capacity should be rejected");
+                    return Ok(());
+                }
+
+                user_data.insert(&proof, &blockhash);
+                expected_proofs.push(proof);
+                expected_proofs.sort();
+                prop_assert_eq!(user_data.recent_blockhash, expected_blockhash);
+                prop_assert_eq!(user_data.length, expected_proofs.len());
+                prop_assert!(user_data.into_iter().eq(&expected_proofs));
+            }
+        }
+    }
 }
@@ -48,6 +48,15 @@ impl<'a> VerifiedAccountInfo<'a> {
     }
 }
 
+impl<'a> VerifiedAccountInfo<'a> {
+    // the account's data for the rest of the instruction. the layouts in it are checked with bytemuck, but rust can't
+    // see past the `RefCell` borrow, so callers must not hold two references into the same data at once
+    pub fn data_unchecked(&self) -> &'a mut [u8] {
+        // SAFETY: the runtime keeps the data in place for the whole instruction
+        unsafe { &mut **self.data.as_ptr() }
+    }
+}
+
 impl<'a> Deref for VerifiedAccountInfo<'a> {
     type Target = AccountInfo<'a>;
 
@@ -0,0 +1,291 @@
+use bytemuck::{Pod, Zeroable};
+use spl_token_2022::solana_program::{msg, program_error::ProgramError};
+
+// every account the comptoken program owns starts with this header, so that one kind of account can't be passed in
+// place of another, and so that a layout can change without the old accounts being misread
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Pod, Zeroable)]
+// MAGIC NUMBER: CHANGES TO THIS STRUCT NEED TO BE REFLECTED IN accounts.js
+pub struct AccountHeader {
+    pub discriminator: [u8; 8],
+    pub version: u8,
+    _padding: [u8; 7],
+}
+
+pub const ACCOUNT_HEADER_SIZE: usize = std::mem::size_of::<AccountHeader>();
+
+// an account whose data is an `AccountHeader` followed by the layout of `VERSION`
+pub trait VersionedAccount {
+    const DISCRIMINATOR: [u8; 8];
+    // must be incremented whenever the layout after the header changes
+    const VERSION: u8;
+}
+
+impl AccountHeader {
+    pub fn new<T: VersionedAccount>() -> Self {
+        Self {
+            discriminator: T::DISCRIMINATOR,
+            version: T::VERSION,
+            _padding: [0; 7],
+        }
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<&Self, ProgramError> {
+        let header = data.get(..ACCOUNT_HEADER_SIZE).ok_or(ProgramError::AccountDataTooSmall)?;
+        bytemuck::try_from_bytes(header).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    fn from_bytes_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        let header = data.get_mut(..ACCOUNT_HEADER_SIZE).ok_or(ProgramError::AccountDataTooSmall)?;
+        bytemuck::try_from_bytes_mut(header).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    fn check_discriminator<T: VersionedAccount>(&self) -> Result<(), ProgramError> {
+        if self.discriminator != T::DISCRIMINATOR {
+            msg!("wrong kind of account: {:?}", self.discriminator);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    fn check<T: VersionedAccount>(&self) -> Result<(), ProgramError> {
+        self.check_discriminator::<T>()?;
+        if self.version != T::VERSION {
+            msg!("account has version {}, expected {}", self.version, T::VERSION);
+            return Err(ProgramError::InvalidAccountData);
+        }
+        Ok(())
+    }
+
+    // checks that `data` holds a `T` and returns the data after the header
+    pub fn body<T: VersionedAccount>(data: &[u8]) -> Result<&[u8], ProgramError> {
+        Self::from_bytes(data)?.check::<T>()?;
+        Ok(&data[ACCOUNT_HEADER_SIZE..])
+    }
+
+    pub fn body_mut<T: VersionedAccount>(data: &mut [u8]) -> Result<&mut [u8], ProgramError> {
+        Self::from_bytes_mut(data)?.check::<T>()?;
+        Ok(&mut data[ACCOUNT_HEADER_SIZE..])
+    }
+
+    // writes the header of a newly created `T` and returns the data after it
+    pub fn initialize<T: VersionedAccount>(data: &mut [u8]) -> Result<&mut [u8], ProgramError> {
+        let header = Self::from_bytes_mut(data)?;
+        if *header != Self::zeroed() {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        *header = Self::new::<T>();
+        Ok(&mut data[ACCOUNT_HEADER_SIZE..])
+    }
+}
+
+// a `VersionedAccount` whose older layouts can be upgraded in place to the current `VERSION`
+pub trait MigratableAccount: VersionedAccount {
+    // the size of the data, including the header, once `data` of an older `version` has been migrated
+    fn migrated_size(version: u8, data: &[u8]) -> usize;
+    // rewrites `body` from the layout of `version` into the current layout. `body` is the larger of the old and the
+    // migrated size, and any bytes past the old data are zeroed
+    fn migrate_body(version: u8, body: &mut [u8]) -> Result<(), ProgramError>;
+}
+
+// the size of the data of a fixed size account, including the header
+pub const fn account_size<T: Pod>() -> usize {
+    ACCOUNT_HEADER_SIZE + std::mem::size_of::<T>()
+}
+
+// the start of an account's data after the header, for layouts that are followed by more data
+pub fn from_body<T: Pod>(body: &[u8]) -> Result<&T, ProgramError> {
+    let body = body.get(..std::mem::size_of::<T>()).ok_or(ProgramError::AccountDataTooSmall)?;
+    bytemuck::try_from_bytes(body).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+pub fn from_body_mut<T: Pod>(body: &mut [u8]) -> Result<&mut T, ProgramError> {
+    let body = body.get_mut(..std::mem::size_of::<T>()).ok_or(ProgramError::AccountDataTooSmall)?;
+    bytemuck::try_from_bytes_mut(body).map_err(|_| ProgramError::InvalidAccountData)
+}
+
+pub fn from_account_data<T: VersionedAccount + Pod>(data: &[u8]) -> Result<&T, ProgramError> {
+    from_body(AccountHeader::body::<T>(data)?)
+}
+
+pub fn from_account_data_mut<T: VersionedAccount + Pod>(data: &mut [u8]) -> Result<&mut T, ProgramError> {
+    from_body_mut(AccountHeader::body_mut::<T>(data)?)
+}
+
+// for a newly created account, whose data is still zeroed
+pub fn initialize_account_data<T: VersionedAccount + Pod>(data: &mut [u8]) -> Result<&mut T, ProgramError> {
+    from_body_mut(AccountHeader::initialize::<T>(data)?)
+}
+
+// the version of an account that holds some version of `T`, which may be older than `T::VERSION`
+pub fn account_version<T: VersionedAccount>(data: &[u8]) -> Result<u8, ProgramError> {
+    let header = AccountHeader::from_bytes(data)?;
+    header.check_discriminator::<T>()?;
+    if header.version == 0 || header.version > T::VERSION {
+        msg!("account has version {}, expected at most {}", header.version, T::VERSION);
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(header.version)
+}
+
+// upgrades the data of a `T` at `version` to the current layout. `data` must already be sized as
+// `MigratableAccount::migrate_body` expects
+pub fn migrate_account_data<T: MigratableAccount>(data: &mut [u8], version: u8) -> Result<(), ProgramError> {
+    if account_version::<T>(data)? != version {
+        return Err(ProgramError::InvalidAccountData);
+    }
+    T::migrate_body(version, &mut data[ACCOUNT_HEADER_SIZE..])?;
+    AccountHeader::from_bytes_mut(data)?.version = T::VERSION;
+    Ok(())
+}
+
+// moves the data of an account from before the header existed, which fills the first `legacy_size` bytes of `data`, to
+// `offset` bytes into the body behind a header of the current version. `data` must already be sized for the current
+// layout, and everything around the moved data is zeroed for the caller to fill in. returns the body
+pub fn migrate_legacy_account_data<T: VersionedAccount>(
+    data: &mut [u8], legacy_size: usize, offset: usize,
+) -> Result<&mut [u8], ProgramError> {
+    let start = ACCOUNT_HEADER_SIZE + offset;
+    let end = start
+        .checked_add(legacy_size)
+        .filter(|end| *end <= data.len())
+        .ok_or(ProgramError::AccountDataTooSmall)?;
+    data.copy_within(..legacy_size, start);
+    data[..start].fill(0);
+    data[end..].fill(0);
+    *AccountHeader::from_bytes_mut(data)? = AccountHeader::new::<T>();
+    Ok(&mut data[ACCOUNT_HEADER_SIZE..])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+    struct TestAccount {
+        value: u64,
+    }
+
+    impl VersionedAccount for TestAccount {
+        const DISCRIMINATOR: [u8; 8] = *b"TestAcct";
+        const VERSION: u8 = 1;
+    }
+
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+    struct OtherAccount {
+        value: u64,
+    }
+
+    impl VersionedAccount for OtherAccount {
+        const DISCRIMINATOR: [u8; 8] = *b"OtherAct";
+        const VERSION: u8 = 1;
+    }
+
+    // the next version of `TestAccount`
+    #[repr(C)]
+    #[derive(Debug, Clone, Copy, PartialEq, Pod, Zeroable)]
+    struct TestAccountV2 {
+        value: u64,
+        doubled: u64,
+    }
+
+    impl VersionedAccount for TestAccountV2 {
+        const DISCRIMINATOR: [u8; 8] = TestAccount::DISCRIMINATOR;
+        const VERSION: u8 = 2;
+    }
+
+    impl MigratableAccount for TestAccountV2 {
+        fn migrated_size(_version: u8, _data: &[u8]) -> usize {
+            account_size::<Self>()
+        }
+
+        fn migrate_body(version: u8, body: &mut [u8]) -> Result<(), ProgramError> {
+            assert_eq!(version, 1);
+            let account: &mut Self = from_body_mut(body)?;
+            account.doubled = account.value * 2;
+            Ok(())
+        }
+    }
+
+    // account data is 8 byte aligned, so the tests' data needs to be as well
+    fn account_data() -> [u64; account_size::<TestAccount>() / 8] {
+        [0; account_size::<TestAccount>() / 8]
+    }
+
+    #[test]
+    fn test_initialize() {
+        let mut data = account_data();
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        initialize_account_data::<TestAccount>(data).unwrap().value = 5;
+
+        assert_eq!(*AccountHeader::from_bytes(data).unwrap(), AccountHeader::new::<TestAccount>());
+        assert_eq!(from_account_data::<TestAccount>(data).unwrap().value, 5);
+        assert_eq!(initialize_account_data::<TestAccount>(data), Err(ProgramError::AccountAlreadyInitialized));
+    }
+
+    #[test]
+    fn test_uninitialized() {
+        let mut data = account_data();
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        assert_eq!(from_account_data::<TestAccount>(data), Err(ProgramError::InvalidAccountData));
+    }
+
+    #[test]
+    fn test_wrong_account() {
+        let mut data = account_data();
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        initialize_account_data::<OtherAccount>(data).unwrap();
+        assert_eq!(from_account_data::<TestAccount>(data), Err(ProgramError::InvalidAccountData));
+    }
+
+    #[test]
+    fn test_wrong_version() {
+        let mut data = account_data();
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        initialize_account_data::<TestAccount>(data).unwrap();
+        data[8] = TestAccount::VERSION + 1;
+        assert_eq!(from_account_data::<TestAccount>(data), Err(ProgramError::InvalidAccountData));
+    }
+
+    #[test]
+    fn test_migrate() {
+        let mut data = [0u64; account_size::<TestAccountV2>() / 8];
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        initialize_account_data::<TestAccount>(data).unwrap().value = 5;
+        assert_eq!(account_version::<TestAccountV2>(data), Ok(1));
+        assert_eq!(from_account_data::<TestAccountV2>(data), Err(ProgramError::InvalidAccountData));
+
+        migrate_account_data::<TestAccountV2>(data, 1).unwrap();
+        assert_eq!(account_version::<TestAccountV2>(data), Ok(2));
+        assert_eq!(*from_account_data::<TestAccountV2>(data).unwrap(), TestAccountV2 { value: 5, doubled: 10 });
+        // the account is already migrated
+        assert_eq!(migrate_account_data::<TestAccountV2>(data, 1), Err(ProgramError::InvalidAccountData));
+        // and an older program can't read it
+        assert_eq!(account_version::<TestAccount>(data), Err(ProgramError::InvalidAccountData));
+    }
+
+    #[test]
+    fn test_migrate_legacy() {
+        let mut data = [0u64; account_size::<TestAccountV2>() / 8];
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        // a legacy account only held the doubled value
+        data[..8].copy_from_slice(&10u64.to_le_bytes());
+
+        migrate_legacy_account_data::<TestAccountV2>(data, 8, 8).unwrap();
+        assert_eq!(account_version::<TestAccountV2>(data), Ok(2));
+        assert_eq!(*from_account_data::<TestAccountV2>(data).unwrap(), TestAccountV2 { value: 0, doubled: 10 });
+        // the migrated layout has to fit
+        assert_eq!(migrate_legacy_account_data::<TestAccountV2>(data, 8, 9), Err(ProgramError::AccountDataTooSmall));
+    }
+
+    #[test]
+    fn test_too_small() {
+        let mut data = account_data();
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        initialize_account_data::<TestAccount>(data).unwrap();
+        let data = &data[..account_size::<TestAccount>() - 1];
+        assert_eq!(from_account_data::<TestAccount>(data), Err(ProgramError::AccountDataTooSmall));
+    }
+}
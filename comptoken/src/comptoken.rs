@@ -1,50 +1,69 @@
 mod comptoken_proof;
-mod constants;
-mod global_data;
+pub mod constants;
+pub mod global_data;
+mod token_metadata;
 mod verify_accounts;
 
 extern crate bs58;
 
+use spl_tlv_account_resolution::{account::ExtraAccountMeta, seeds::Seed, state::ExtraAccountMetaList};
 use spl_token_2022::{
-    extension::StateWithExtensions,
-    instruction::mint_to,
+    extension::{metadata_pointer, transfer_hook, BaseStateWithExtensions, ExtensionType, StateWithExtensions},
+    instruction::{initialize_mint2, mint_to},
     onchain,
     solana_program::{
-        account_info::AccountInfo, entrypoint, entrypoint::MAX_PERMITTED_DATA_INCREASE, hash::HASH_BYTES,
-        instruction::AccountMeta, msg, program::set_return_data, program_error::ProgramError, pubkey::Pubkey,
+        account_info::{next_account_info, AccountInfo},
+        entrypoint,
+        entrypoint::MAX_PERMITTED_DATA_INCREASE,
+        hash::HASH_BYTES,
+        instruction::AccountMeta,
+        msg,
+        program::set_return_data,
+        program_error::ProgramError,
+        pubkey::Pubkey,
         system_instruction,
+        system_instruction::MAX_PERMITTED_DATA_LENGTH,
+        system_program,
+        sysvar::{rent::Rent, Sysvar},
     },
     state::{Account, Mint},
 };
+use spl_token_metadata_interface::state::{Field, TokenMetadata};
+use spl_transfer_hook_interface::instruction::ExecuteInstruction;
+use spl_type_length_value::state::TlvStateBorrowed;
 
 use comptoken_utils::{
-    create_pda, get_current_time, invoke_signed_verified, normalize_time,
-    user_data::{UserData, USER_DATA_MIN_SIZE},
+    create_pda,
+    distribution_record::{DistributionRecord, DISTRIBUTION_RECORD_SEED, DISTRIBUTION_RECORD_SIZE},
+    events::ComptokenEvent,
+    exempt_accounts::{exempt_accounts_size, ExemptAccounts, EXEMPT_ACCOUNTS_SEED},
+    get_current_time, invoke_signed_verified, normalize_time,
+    user_data::{user_data_size, UserData},
+    versioned_account::{
+        account_size, account_version, initialize_account_data, migrate_account_data, MigratableAccount,
+        VersionedAccount, ACCOUNT_HEADER_SIZE,
+    },
     SEC_PER_DAY,
 };
 
-use comptoken_proof::ComptokenProof;
+use crate::global_data::valid_blockhashes::ValidBlockhashes;
+use comptoken_proof::{ComptokenProof, VERIFY_DATA_SIZE};
 use constants::*;
-use global_data::{daily_distribution_data::DailyDistributionValues, GlobalData};
+use global_data::{
+    bank_liabilities::surplus_or_deficit, daily_distribution_data::DailyDistributionValues, Bumps, GlobalData,
+};
+use token_metadata::{read_field_update, read_removed_key, refreshed_metadata};
 use verify_accounts::*;
-use crate::global_data::valid_blockhashes::ValidBlockhashes;
 
 // declare and export the program's entrypoint
 entrypoint!(process_instruction);
 
 type ProgramResult = Result<(), ProgramError>;
 
-const GLOBAL_DATA_ACCOUNT_SPACE: u64 = std::mem::size_of::<GlobalData>() as u64;
-
-mod generated;
-use generated::{
-    COMPTOKEN_MINT_ADDRESS, COMPTO_FUTURE_UBI_BANK_ACCOUNT_SEEDS, COMPTO_GLOBAL_DATA_ACCOUNT_SEEDS,
-    COMPTO_INTEREST_BANK_ACCOUNT_SEEDS, COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_SEEDS, TRANSFER_HOOK_ID,
-};
+const GLOBAL_DATA_ACCOUNT_SPACE: u64 = account_size::<GlobalData>() as u64;
 
 // program entrypoint's implementation
 pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
-    msg!("instruction_data: {:?}", instruction_data);
     match instruction_data[0] {
         1 => {
             msg!("Mint New Comptokens");
@@ -78,6 +97,58 @@ pub fn process_instruction(program_id: &Pubkey, accounts: &[AccountInfo], instru
             msg!("Verify Human");
             verify_human(program_id, accounts, &instruction_data[1..])
         }
+        9 => {
+            msg!("Get Bank Reconciliation");
+            get_bank_reconciliation(program_id, accounts, &instruction_data[1..])
+        }
+        10 => {
+            msg!("Migrate Global Data");
+            migrate_global_data(program_id, accounts, &instruction_data[1..])
+        }
+        11 => {
+            msg!("Migrate User Data");
+            migrate_user_data(program_id, accounts, &instruction_data[1..])
+        }
+        12 => {
+            msg!("Close User Data Account");
+            close_user_data_account(program_id, accounts, &instruction_data[1..])
+        }
+        13 => {
+            msg!("Shrink User Data Account");
+            shrink_user_data(program_id, accounts, &instruction_data[1..])
+        }
+        14 => {
+            msg!("Update Metadata Field");
+            update_metadata_field(program_id, accounts, &instruction_data[1..])
+        }
+        15 => {
+            msg!("Remove Metadata Key");
+            remove_metadata_key(program_id, accounts, &instruction_data[1..])
+        }
+        16 => {
+            msg!("Update Extra Account Metas");
+            update_extra_account_metas(program_id, accounts, &instruction_data[1..])
+        }
+        17 => {
+            msg!("Add Exempt Account");
+            add_exempt_account(program_id, accounts, &instruction_data[1..])
+        }
+        18 => {
+            msg!("Remove Exempt Account");
+            remove_exempt_account(program_id, accounts, &instruction_data[1..])
+        }
+        19 => {
+            msg!("Migrate Legacy Global Data");
+            migrate_legacy_global_data(program_id, accounts, &instruction_data[1..])
+        }
+        20 => {
+            msg!("Set Admin");
+            set_admin(program_id, accounts, &instruction_data[1..])
+        }
+        21 => {
+            msg!("Migrate Extra Account Metas");
+            migrate_extra_account_metas(program_id, accounts, &instruction_data[1..])
+        }
         255 => {
             msg!("Test Mint");
             test_mint(program_id, accounts, &instruction_data[1..])
@@ -98,14 +169,12 @@ pub fn test_mint(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data
     //      [] User Comptoken Token Account
     //      [] Solana Token 2022
 
-    msg!("instruction_data: {:?}", instruction_data);
-
     let verified_accounts = verify_accounts(
         accounts,
         program_id,
         AccountsToVerify {
             comptoken_mint: Some((false, true)),
-            global_data: Some((false, false)),
+            global_data: Some((true, (false, false))),
             user_wallet: Some((true, false)),
             user_comptoken_token_account: Some((false, false)),
             solana_token_2022_program: Some((false, false)),
@@ -115,16 +184,15 @@ pub fn test_mint(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data
 
     let comptoken_mint_account = verified_accounts.comptoken_mint.unwrap();
     let global_data_account = verified_accounts.global_data.unwrap();
+    let global_data_bump = verified_accounts.global_data_bump.unwrap();
     let user_comptoken_token_account = verified_accounts.user_comptoken_token_account.unwrap();
 
     let amount = u64::from_le_bytes(instruction_data[0..8].try_into().expect("correct size"));
 
-    mint(
-        &global_data_account,
-        &user_comptoken_token_account,
-        amount,
-        &[&comptoken_mint_account, &user_comptoken_token_account, &global_data_account],
-    )
+    mint(&comptoken_mint_account, &global_data_account, global_data_bump, &user_comptoken_token_account, amount)?;
+    ComptokenEvent::TokensMinted { destination: *user_comptoken_token_account.key, amount }.emit();
+
+    Ok(())
 }
 
 #[cfg(not(feature = "testmode"))]
@@ -141,13 +209,18 @@ pub fn mint_comptokens(program_id: &Pubkey, accounts: &[AccountInfo], instructio
     //      [w] User's Comptoken Token Account
     //      [w] User's Data Account
     //      [] Solana Token 2022 Program
+    //      optional, to grow the user's data account if it is full:
+    //      [s, w] Payer Account
+    //      [] Solana Program
+    //  instruction data:
+    //      the proof, optionally followed by how many proofs to grow the user's data account by (u64)
 
     let verified_accounts = verify_accounts(
         accounts,
         program_id,
         AccountsToVerify {
             comptoken_mint: Some((false, true)),
-            global_data: Some((false, false)),
+            global_data: Some((true, (false, false))),
             user_wallet: Some((true, false)),
             user_comptoken_token_account: Some((false, true)),
             user_data: Some((true, (false, true))),
@@ -157,27 +230,74 @@ pub fn mint_comptokens(program_id: &Pubkey, accounts: &[AccountInfo], instructio
     )?;
     let comptoken_mint_account = verified_accounts.comptoken_mint.unwrap();
     let global_data_account = verified_accounts.global_data.unwrap();
+    let global_data_bump = verified_accounts.global_data_bump.unwrap();
     let user_comptoken_token_account = verified_accounts.user_comptoken_token_account.unwrap();
     let user_data_account = verified_accounts.user_data.unwrap();
 
+    let growth_accounts = &mut accounts.iter().skip(6);
+    let growth_accounts = match next_account_info(growth_accounts) {
+        Ok(payer) => Some((verify_payer_account(payer), verify_solana_program(next_account_info(growth_accounts)?))),
+        Err(_) => None,
+    };
+
+    let (proof_data, growth_step) = instruction_data.split_at(instruction_data.len().min(VERIFY_DATA_SIZE));
+    let growth_step = match growth_step {
+        [] => USER_DATA_GROWTH_STEP,
+        step => u64::from_le_bytes(step.try_into().map_err(|_| ProgramError::InvalidInstructionData)?),
+    };
+    // a step of 0 wouldn't make room for the proof
+    if !(1..=MAX_USER_DATA_GROWTH_STEP).contains(&growth_step) {
+        msg!("the growth step must be between 1 and {}", MAX_USER_DATA_GROWTH_STEP);
+        return Err(ProgramError::InvalidInstructionData);
+    }
+
     let global_data: &mut GlobalData = (&global_data_account).into();
     let proof = ComptokenProof::verify_submitted_proof(
         &user_comptoken_token_account,
-        instruction_data,
+        proof_data,
         &global_data.valid_blockhashes,
     );
 
     msg!("data/accounts verified");
 
+    if let Some((payer_account, system_program)) = growth_accounts {
+        let proof_capacity = {
+            let mut user_data: UserData = (&user_data_account).into();
+            user_data.update(&global_data.valid_blockhashes.valid_blockhash);
+            user_data.is_full().then_some(user_data.proofs.len())
+        };
+        if let Some(proof_capacity) = proof_capacity {
+            let target_size = usize::try_from(growth_step)
+                .ok()
+                .and_then(|step| proof_capacity.checked_add(step))
+                .and_then(user_data_size)
+                .ok_or(ProgramError::InvalidInstructionData)?;
+            msg!("user data is full, growing it");
+            grow_user_data(&payer_account, &user_data_account, &system_program, target_size)?;
+        }
+    }
+
     // now save the hash to the account, returning an error if the hash already exists
+    let proof_hash = proof.hash;
     store_hash(proof, &user_data_account, &global_data.valid_blockhashes);
     msg!("stored the proof");
+    ComptokenEvent::ProofAccepted {
+        user_comptoken_wallet: *user_comptoken_token_account.key,
+        proof: proof_hash,
+    }
+    .emit();
     mint(
+        &comptoken_mint_account,
         &global_data_account,
+        global_data_bump,
         &user_comptoken_token_account,
         MINING_AMOUNT,
-        &[&comptoken_mint_account, &user_comptoken_token_account, &global_data_account],
     )?;
+    ComptokenEvent::TokensMinted {
+        destination: *user_comptoken_token_account.key,
+        amount: MINING_AMOUNT,
+    }
+    .emit();
 
     Ok(())
 }
@@ -187,26 +307,30 @@ pub fn initialize_comptoken_program(
 ) -> ProgramResult {
     //  accounts order:
     //      [s, w] Payer (probably COMPTO's account)
-    //      [] Comptoken Mint
+    //      [s] Upgrade Authority (of the comptoken program, and becomes the admin)
+    //      [] Comptoken Program (recorded by the transfer hook)
+    //      [] Comptoken Program Data (knows the upgrade authority)
+    //      [w] Comptoken Mint (created here, with the global data as mint authority)
     //      [w] Global Data Account (also mint authority)
     //      [w] Comptoken Interest Bank
     //      [w] Comptoken Verified Human UBI Bank
     //      [w] Comptoken Future UBI Bank
-    //      [] Transfer Hook Program
+    //      [] Transfer Hook Program (the mint's transfer hook)
     //      [w] Extra Account Metas Account
     //      [] Solana Program
     //      [] Solana Token 2022 Program
     //      [] Solana SlotHashes Sysvar
 
-    msg!("instruction_data: {:?}", instruction_data);
-
     let verified_accounts = verify_accounts(
         accounts,
         program_id,
         AccountsToVerify {
             payer: Some((true, true)),
-            comptoken_mint: Some((false, false)),
-            global_data: Some((false, true)),
+            upgrade_authority: Some((true, false)),
+            comptoken_program: Some((false, false)),
+            comptoken_program_data: Some((false, false)),
+            comptoken_mint: Some((false, true)),
+            global_data: Some((false, (false, true))),
             interest_bank: Some((false, true)),
             verified_human_ubi_bank: Some((false, true)),
             future_ubi_bank: Some((false, true)),
@@ -220,82 +344,115 @@ pub fn initialize_comptoken_program(
     )?;
 
     let payer_account = verified_accounts.payer.unwrap();
+    let upgrade_authority = verified_accounts.upgrade_authority.unwrap();
+    let comptoken_program = verified_accounts.comptoken_program.unwrap();
     let comptoken_mint = verified_accounts.comptoken_mint.unwrap();
+    let comptoken_mint_bump = verified_accounts.comptoken_mint_bump.unwrap();
     let global_data_account = verified_accounts.global_data.unwrap();
+    let global_data_bump = verified_accounts.global_data_bump.unwrap();
     let unpaid_interest_bank = verified_accounts.interest_bank.unwrap();
+    let interest_bank_bump = verified_accounts.interest_bank_bump.unwrap();
     let unpaid_verified_human_ubi_bank = verified_accounts.verified_human_ubi_bank.unwrap();
+    let verified_human_ubi_bank_bump = verified_accounts.verified_human_ubi_bank_bump.unwrap();
     let unpaid_future_ubi_bank = verified_accounts.future_ubi_bank.unwrap();
+    let future_ubi_bank_bump = verified_accounts.future_ubi_bank_bump.unwrap();
+    let transfer_hook_program = verified_accounts.transfer_hook_program.unwrap();
     let extra_account_metas_account = verified_accounts.extra_account_metas.unwrap();
+    let extra_account_metas_bump = verified_accounts.extra_account_metas_bump.unwrap();
     let solana_program = verified_accounts.solana_program.unwrap();
     let slothashes_account = verified_accounts.slothashes.unwrap();
 
-    let first_8_bytes: [u8; 8] = instruction_data[0..8].try_into().unwrap();
-    let lamports_global_data = u64::from_le_bytes(first_8_bytes);
-    let lamports_interest_bank = u64::from_le_bytes(instruction_data[8..16].try_into().unwrap());
-    let lamports_verified_human_ubi_bank = u64::from_le_bytes(instruction_data[16..24].try_into().unwrap());
-    let lamports_future_ubi_bank = u64::from_le_bytes(instruction_data[24..32].try_into().unwrap());
-    msg!("Lamports global data: {:?}", lamports_global_data);
-    msg!("Lamports interest bank: {:?}", lamports_interest_bank);
-    msg!("Lamports verified human ubi bank: {:?}", lamports_verified_human_ubi_bank);
-    msg!("Lamports future ubi bank: {:?}", lamports_future_ubi_bank);
+    // seconds after midnight UTC+0 at which each day starts. days start at midnight UTC+0 if it isn't given
+    let day_boundary_offset = match instruction_data {
+        [] => 0,
+        offset => i64::from_le_bytes(offset.try_into().map_err(|_| ProgramError::InvalidInstructionData)?),
+    };
+    if !(0..SEC_PER_DAY).contains(&day_boundary_offset) {
+        msg!("the day boundary offset must be less than a day");
+        return Err(ProgramError::InvalidArgument);
+    }
+
+    let global_data_seeds: &[&[u8]] = &[COMPTO_GLOBAL_DATA_ACCOUNT_SEED, &[global_data_bump]];
 
     create_pda(
         &payer_account,
         &global_data_account,
-        lamports_global_data,
+        Rent::get()?.minimum_balance(GLOBAL_DATA_ACCOUNT_SPACE as usize),
         GLOBAL_DATA_ACCOUNT_SPACE,
         program_id,
-        &[COMPTO_GLOBAL_DATA_ACCOUNT_SEEDS],
+        &[global_data_seeds],
     )?;
     msg!("created global data account");
-    create_pda(
+    create_comptoken_mint(
+        &payer_account,
+        &comptoken_mint,
+        comptoken_mint_bump,
+        &global_data_account,
+        global_data_seeds,
+        &transfer_hook_program,
+    )?;
+    msg!("created comptoken mint");
+    create_bank_account(
         &payer_account,
         &unpaid_interest_bank,
-        lamports_interest_bank,
-        COMPTOKEN_ACCOUNT_SPACE,
-        &spl_token_2022::ID,
-        &[COMPTO_INTEREST_BANK_ACCOUNT_SEEDS],
+        &[COMPTO_INTEREST_BANK_ACCOUNT_SEED, &[interest_bank_bump]],
+        &global_data_account,
+        &comptoken_mint,
     )?;
     msg!("created interest bank account");
-    init_comptoken_account(&unpaid_interest_bank, &global_data_account, &[], &comptoken_mint)?;
-    msg!("initialized interest bank account");
-
-    create_pda(
+    create_bank_account(
         &payer_account,
         &unpaid_verified_human_ubi_bank,
-        lamports_interest_bank,
-        COMPTOKEN_ACCOUNT_SPACE,
-        &spl_token_2022::ID,
-        &[COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_SEEDS],
+        &[COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_SEED, &[verified_human_ubi_bank_bump]],
+        &global_data_account,
+        &comptoken_mint,
     )?;
     msg!("created verified human ubi bank account");
-    init_comptoken_account(&unpaid_verified_human_ubi_bank, &global_data_account, &[], &comptoken_mint)?;
-    msg!("initialized verified human ubi bank account");
-
-    create_pda(
+    create_bank_account(
         &payer_account,
         &unpaid_future_ubi_bank,
-        lamports_future_ubi_bank,
-        COMPTOKEN_ACCOUNT_SPACE,
-        &spl_token_2022::ID,
-        &[COMPTO_FUTURE_UBI_BANK_ACCOUNT_SEEDS],
+        &[COMPTO_FUTURE_UBI_BANK_ACCOUNT_SEED, &[future_ubi_bank_bump]],
+        &global_data_account,
+        &comptoken_mint,
     )?;
     msg!("created future ubi bank account");
-    init_comptoken_account(&unpaid_future_ubi_bank, &global_data_account, &[], &comptoken_mint)?;
-    msg!("initialized future ubi bank account");
 
-    let global_data: &mut GlobalData = (&global_data_account).into();
-    global_data.initialize(&slothashes_account);
+    // the bank data accounts are never created, so their bumps are only found here
+    let bank_data_bump = |bank: &VerifiedAccountInfo| Pubkey::find_program_address(&[bank.key.as_ref()], program_id).1;
+    let bumps = Bumps {
+        global_data: global_data_bump,
+        interest_bank: interest_bank_bump,
+        verified_human_ubi_bank: verified_human_ubi_bank_bump,
+        future_ubi_bank: future_ubi_bank_bump,
+        interest_bank_data: bank_data_bump(&unpaid_interest_bank),
+        verified_human_ubi_bank_data: bank_data_bump(&unpaid_verified_human_ubi_bank),
+        future_ubi_bank_data: bank_data_bump(&unpaid_future_ubi_bank),
+        extra_account_metas: extra_account_metas_bump,
+    };
+    let global_data: &mut GlobalData = initialize_account_data(global_data_account.data_unchecked())?;
+    // only the upgrade authority may initialize the program, so that it can't be front-run, and it administers it until
+    // it sets another admin
+    global_data.initialize(
+        comptoken_mint.key,
+        transfer_hook_program.key,
+        bumps,
+        upgrade_authority.key,
+        day_boundary_offset,
+        &slothashes_account,
+    );
 
     let mut init_transfer_hook_instruction =
         spl_transfer_hook_interface::instruction::initialize_extra_account_meta_list(
-            &TRANSFER_HOOK_ID,
+            transfer_hook_program.key,
             extra_account_metas_account.key,
             comptoken_mint.key,
             global_data_account.key,
             &[],
         );
     init_transfer_hook_instruction.accounts.push(AccountMeta::new(*payer_account.key, true));
+    init_transfer_hook_instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*comptoken_program.key, false));
 
     invoke_signed_verified(
         &init_transfer_hook_instruction,
@@ -305,8 +462,9 @@ pub fn initialize_comptoken_program(
             &global_data_account,
             &solana_program,
             &payer_account,
+            &comptoken_program,
         ],
-        &[COMPTO_GLOBAL_DATA_ACCOUNT_SEEDS],
+        &[global_data_seeds],
     )
 }
 
@@ -315,6 +473,7 @@ pub fn create_user_data_account(
 ) -> ProgramResult {
     //  Account Order
     //      [s, w] payer account
+    //      [] Comptoken Global Data (knows the comptoken mint, and when days start)
     //      [s] User Solana Wallet
     //      [] User's Comptoken Token Account
     //      [w] User's Data Account
@@ -325,6 +484,7 @@ pub fn create_user_data_account(
         program_id,
         AccountsToVerify {
             payer: Some((true, true)),
+            global_data: Some((true, (false, false))),
             user_wallet: Some((true, false)),
             user_comptoken_token_account: Some((false, false)),
             user_data: Some((false, (false, true))),
@@ -334,18 +494,21 @@ pub fn create_user_data_account(
     )?;
 
     let payer_account = verified_accounts.payer.unwrap();
+    let global_data_account = verified_accounts.global_data.unwrap();
     let user_comptoken_wallet_account = verified_accounts.user_comptoken_token_account.unwrap();
     let user_data_account = verified_accounts.user_data.unwrap();
     let bump = verified_accounts.user_data_bump.unwrap();
 
-    // find space and minimum rent required for account
-    let rent_lamports = u64::from_le_bytes(instruction_data[0..8].try_into().expect("correct size"));
-    let space = usize::from_le_bytes(instruction_data[8..16].try_into().expect("correct size"));
+    let space = user_data_size(read_proof_capacity(instruction_data)?).ok_or(ProgramError::InvalidInstructionData)?;
     msg!("space: {}", space);
-    assert!(space >= USER_DATA_MIN_SIZE);
-    assert!((space - USER_DATA_MIN_SIZE) % HASH_BYTES == 0);
+    // an account created through a CPI can be at most `MAX_PERMITTED_DATA_INCREASE` bytes, and can be grown from there
+    if space > MAX_PERMITTED_DATA_INCREASE {
+        msg!("user data is too large to create, grow it after creating it");
+        return Err(ProgramError::InvalidArgument);
+    }
 
-    create_pda(
+    let rent_lamports = Rent::get()?.minimum_balance(space);
+    create_pda_account(
         &payer_account,
         &user_data_account,
         rent_lamports,
@@ -353,10 +516,11 @@ pub fn create_user_data_account(
         program_id,
         &[&[user_comptoken_wallet_account.key.as_ref(), &[bump]]],
     )?;
+    refund_excess_rent(&user_data_account, &payer_account)?;
 
     // initialize data account
-    let user_data: &mut UserData = (&user_data_account).into();
-    user_data.initialize();
+    let global_data: &mut GlobalData = (&global_data_account).into();
+    UserData::initialize(user_data_account.data_unchecked(), bump, global_data.today())?;
 
     Ok(())
 }
@@ -365,44 +529,88 @@ pub fn daily_distribution_event(
     program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8],
 ) -> ProgramResult {
     //  accounts order:
-    //      [] Comptoken Mint
+    //      [s, w] Payer (pays the rent for the distribution record, and for any growth of the metadata)
+    //      [w] Comptoken Mint
     //      [w] Comptoken Global Data (also mint authority)
     //      [w] Comptoken Interest Bank
     //      [w] Comptoken Verified Human UBI Bank
     //      [w] Comptoken Future UBI Bank
+    //      [w] Comptoken Distribution Record (for today)
+    //      [] Solana Program
     //      [] Solana Token 2022 Program
     //      [] Solana SlotHashes Sysvar
-    //      [w] Comptoken Future UBI Bank
+    //      optional, to receive the crank reward:
+    //      [s] Caller's Wallet
+    //      [w] Caller's Comptoken Token Account
 
     let verified_accounts = verify_accounts(
         accounts,
         program_id,
         AccountsToVerify {
-            comptoken_mint: Some((false, false)),
-            global_data: Some((false, true)),
+            payer: Some((true, true)),
+            comptoken_mint: Some((false, true)),
+            global_data: Some((true, (false, true))),
             interest_bank: Some((false, true)),
             verified_human_ubi_bank: Some((false, true)),
             future_ubi_bank: Some((false, true)),
+            distribution_record: Some((false, true)),
+            solana_program: Some((false, false)),
             solana_token_2022_program: Some((false, false)),
             slothashes: Some((false, false)),
             ..Default::default()
         },
     )?;
 
+    let payer_account = verified_accounts.payer.unwrap();
     let comptoken_mint_account = verified_accounts.comptoken_mint.unwrap();
     let global_data_account = verified_accounts.global_data.unwrap();
+    let global_data_bump = verified_accounts.global_data_bump.unwrap();
     let unpaid_interest_bank_account = verified_accounts.interest_bank.unwrap();
     let unpaid_verified_human_ubi_bank_account = verified_accounts.verified_human_ubi_bank.unwrap();
     let unpaid_future_ubi_bank_account = verified_accounts.future_ubi_bank.unwrap();
+    let distribution_record_account = verified_accounts.distribution_record.unwrap();
+    let distribution_record_bump = verified_accounts.distribution_record_bump.unwrap();
+    let solana_program = verified_accounts.solana_program.unwrap();
     let slothashes_account = verified_accounts.slothashes.unwrap();
 
+    let caller_accounts = &mut verified_accounts.remaining_accounts.iter();
+    let caller_comptoken_token_account = match next_account_info(caller_accounts) {
+        Ok(caller_wallet) => {
+            let caller_wallet = verify_wallet_account(caller_wallet, false);
+            Some(verify_user_comptoken_token_account(
+                next_account_info(caller_accounts)?,
+                Some(&caller_wallet),
+                comptoken_mint_account.key,
+                true,
+            ))
+        }
+        Err(_) => None,
+    };
+
+    let rent_lamports = Rent::get()?.minimum_balance(DISTRIBUTION_RECORD_SIZE);
+    let distribution_time = <&mut GlobalData>::from(&global_data_account).today();
+    create_pda_account(
+        &payer_account,
+        &distribution_record_account,
+        rent_lamports,
+        DISTRIBUTION_RECORD_SIZE as u64,
+        program_id,
+        &[&[DISTRIBUTION_RECORD_SEED, &distribution_time.to_le_bytes(), &[distribution_record_bump]]],
+    )?;
+
     let daily_distribution: DailyDistributionValues;
+    let (supply, high_water_mark);
     // scope to prevent reborrowing issues
     {
         let mut global_data_account_data = global_data_account.try_borrow_mut_data().unwrap();
         let global_data: &mut GlobalData = global_data_account_data.as_mut().into();
         let mint_data = comptoken_mint_account.try_borrow_data().unwrap();
         let comptoken_mint = StateWithExtensions::<Mint>::unpack(&mint_data).unwrap().base;
+        let unpaid_interest_bank_data = unpaid_interest_bank_account.try_borrow_data().unwrap();
+        let unpaid_interest_bank = StateWithExtensions::<Account>::unpack(&unpaid_interest_bank_data).unwrap().base;
+        let unpaid_verified_human_ubi_bank_data = unpaid_verified_human_ubi_bank_account.try_borrow_data().unwrap();
+        let unpaid_verified_human_ubi_bank =
+            StateWithExtensions::<Account>::unpack(&unpaid_verified_human_ubi_bank_data).unwrap().base;
         let unpaid_future_ubi_bank_data = unpaid_future_ubi_bank_account.try_borrow_data().unwrap();
         let unpaid_future_ubi_bank = StateWithExtensions::<Account>::unpack(&unpaid_future_ubi_bank_data).unwrap().base;
 
@@ -412,31 +620,96 @@ pub fn daily_distribution_event(
             "daily distribution already called today"
         );
 
-        daily_distribution =
-            global_data.daily_distribution_event(&comptoken_mint, &unpaid_future_ubi_bank, &slothashes_account);
+        daily_distribution = global_data.daily_distribution_event(
+            &comptoken_mint,
+            &unpaid_interest_bank,
+            &unpaid_verified_human_ubi_bank,
+            &unpaid_future_ubi_bank,
+            &slothashes_account,
+            caller_comptoken_token_account.is_some() && CRANK_REWARD > 0,
+        );
+
+        let distribution_record: &mut DistributionRecord =
+            initialize_account_data(distribution_record_account.data_unchecked())?;
+        *distribution_record = global_data
+            .daily_distribution_data
+            .distribution_record(comptoken_mint.supply, &daily_distribution);
+        // the supply once everything below is minted
+        supply = global_data.daily_distribution_data.yesterday_supply;
+        high_water_mark = global_data.daily_distribution_data.high_water_mark;
+    }
+    if let (Some(caller_comptoken_token_account), 1..) =
+        (&caller_comptoken_token_account, daily_distribution.crank_reward)
+    {
+        msg!("Crank Reward: {}", daily_distribution.crank_reward);
+        mint(
+            &comptoken_mint_account,
+            &global_data_account,
+            global_data_bump,
+            caller_comptoken_token_account,
+            daily_distribution.crank_reward,
+        )?;
+    }
+    ComptokenEvent::DailyDistribution {
+        distribution_time,
+        interest_distribution: daily_distribution.interest_distribution,
+        ubi_for_verified_humans: daily_distribution.ubi_for_verified_humans,
+        future_ubi_distribution: daily_distribution.future_ubi_distribution,
+        crank_reward: daily_distribution.crank_reward,
+        supply,
+        high_water_mark,
     }
+    .emit();
     // mint to banks
     msg!("Interest Distribution: {}", daily_distribution.interest_distribution);
     mint(
+        &comptoken_mint_account,
         &global_data_account,
+        global_data_bump,
         &unpaid_interest_bank_account,
         daily_distribution.interest_distribution,
-        &[&comptoken_mint_account, &global_data_account, &unpaid_interest_bank_account],
     )?;
     msg!("Ubi for verified humans: {}", daily_distribution.ubi_for_verified_humans);
     mint(
+        &comptoken_mint_account,
         &global_data_account,
+        global_data_bump,
         &unpaid_verified_human_ubi_bank_account,
         daily_distribution.ubi_for_verified_humans,
-        &[&comptoken_mint_account, &global_data_account, &unpaid_verified_human_ubi_bank_account],
     )?;
     msg!("Future UBI Distribution: {}", daily_distribution.future_ubi_distribution);
     mint(
+        &comptoken_mint_account,
         &global_data_account,
+        global_data_bump,
         &unpaid_future_ubi_bank_account,
         daily_distribution.future_ubi_distribution,
-        &[&comptoken_mint_account, &global_data_account, &unpaid_future_ubi_bank_account],
-    )
+    )?;
+
+    // only the metadata whose keys the admin has added is refreshed
+    let global_data: &mut GlobalData = (&global_data_account).into();
+    let refreshed = refreshed_metadata(&global_data.daily_distribution_data);
+    let stale: Vec<_> = {
+        let mint_data = comptoken_mint_account.try_borrow_data()?;
+        let metadata =
+            StateWithExtensions::<Mint>::unpack(&mint_data)?.get_variable_len_extension::<TokenMetadata>()?;
+        refreshed
+            .into_iter()
+            .filter(|(key, value)| metadata.additional_metadata.iter().any(|(k, v)| k == key && v != value))
+            .collect()
+    };
+    for (key, value) in stale {
+        update_metadata(
+            &payer_account,
+            &comptoken_mint_account,
+            &global_data_account,
+            global_data_bump,
+            &solana_program,
+            Field::Key(key.to_string()),
+            value,
+        )?;
+    }
+    Ok(())
 }
 
 pub fn get_valid_blockhashes(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
@@ -448,7 +721,7 @@ pub fn get_valid_blockhashes(program_id: &Pubkey, accounts: &[AccountInfo], _ins
         accounts,
         program_id,
         AccountsToVerify {
-            global_data: Some((false, true)),
+            global_data: Some((true, (false, true))),
             slothashes: Some((false, false)),
             ..Default::default()
         },
@@ -458,9 +731,10 @@ pub fn get_valid_blockhashes(program_id: &Pubkey, accounts: &[AccountInfo], _ins
     let slothashes_account = verified_accounts.slothashes.unwrap();
 
     let global_data: &mut GlobalData = (&global_data_account).into();
+    let day_boundary_offset = global_data.day_boundary_offset;
     let valid_blockhashes = &mut global_data.valid_blockhashes;
 
-    valid_blockhashes.update(&slothashes_account);
+    valid_blockhashes.update(&slothashes_account, day_boundary_offset);
 
     let mut data = Vec::from(global_data.valid_blockhashes.valid_blockhash.to_bytes());
     data.extend(global_data.valid_blockhashes.announced_blockhash.to_bytes());
@@ -472,7 +746,7 @@ pub fn get_owed_comptokens(program_id: &Pubkey, accounts: &[AccountInfo], _instr
     //  accounts order:
     //      [] Comptoken Program
     //      [] Comptoken Mint
-    //      [] Comptoken Global Data (also mint authority)
+    //      [w] Comptoken Global Data (also mint authority)
     //      [w] Comptoken Interest Bank
     //      [w] Comptoken Verified Human UBI Bank
     //      [] Interest Bank Data PDA (doesn't actually exist)
@@ -482,6 +756,7 @@ pub fn get_owed_comptokens(program_id: &Pubkey, accounts: &[AccountInfo], _instr
     //      [w] User's Data
     //      [] Transfer Hook Program
     //      [] Extra Account Metas Account
+    //      [] Exempt Accounts Account (passed to the transfer hook)
     //      [] Solana Token 2022 Program
 
     let verified_accounts = verify_accounts(
@@ -490,7 +765,7 @@ pub fn get_owed_comptokens(program_id: &Pubkey, accounts: &[AccountInfo], _instr
         AccountsToVerify {
             comptoken_program: Some((false, false)),
             comptoken_mint: Some((false, false)),
-            global_data: Some((false, false)),
+            global_data: Some((true, (false, true))),
             interest_bank: Some((false, true)),
             verified_human_ubi_bank: Some((false, true)),
             interest_bank_data: Some((false, false)),
@@ -500,20 +775,30 @@ pub fn get_owed_comptokens(program_id: &Pubkey, accounts: &[AccountInfo], _instr
             user_data: Some((true, (false, true))),
             transfer_hook_program: Some((false, false)),
             extra_account_metas: Some((false, false)),
+            exempt_accounts: Some((false, false)),
             solana_token_2022_program: Some((false, false)),
             ..Default::default()
         },
     )?;
 
-    let comptoken_program = verified_accounts.comptoken_program.unwrap();
-    let comptoken_mint_account = verified_accounts.comptoken_mint.unwrap();
-    let global_data_account = verified_accounts.global_data.unwrap();
-    let unpaid_interest_bank = verified_accounts.interest_bank.unwrap();
-    let interest_data_pda = verified_accounts.interest_bank_data.unwrap();
-    let user_comptoken_token_account = verified_accounts.user_comptoken_token_account.unwrap();
-    let user_data_account = verified_accounts.user_data.unwrap();
-    let transfer_hook_program = verified_accounts.transfer_hook_program.unwrap();
-    let extra_account_metas_account = verified_accounts.extra_account_metas.unwrap();
+    pay_owed_comptokens(&verified_accounts)
+}
+
+// pays the interest, and the UBI if the user is a verified human, owed since the user was last paid
+fn pay_owed_comptokens(verified_accounts: &VerifiedAccounts) -> ProgramResult {
+    let comptoken_program = verified_accounts.comptoken_program.as_ref().unwrap();
+    let comptoken_mint_account = verified_accounts.comptoken_mint.as_ref().unwrap();
+    let global_data_account = verified_accounts.global_data.as_ref().unwrap();
+    let global_data_bump = verified_accounts.global_data_bump.unwrap();
+    let unpaid_interest_bank = verified_accounts.interest_bank.as_ref().unwrap();
+    let unpaid_verified_human_ubi_bank = verified_accounts.verified_human_ubi_bank.as_ref().unwrap();
+    let interest_data_pda = verified_accounts.interest_bank_data.as_ref().unwrap();
+    let verified_human_ubi_data_pda = verified_accounts.verified_human_ubi_bank_data.as_ref().unwrap();
+    let user_comptoken_token_account = verified_accounts.user_comptoken_token_account.as_ref().unwrap();
+    let user_data_account = verified_accounts.user_data.as_ref().unwrap();
+    let transfer_hook_program = verified_accounts.transfer_hook_program.as_ref().unwrap();
+    let extra_account_metas_account = verified_accounts.extra_account_metas.as_ref().unwrap();
+    let exempt_accounts_account = verified_accounts.exempt_accounts.as_ref().unwrap();
 
     let interest;
     let is_verified_human;
@@ -521,12 +806,12 @@ pub fn get_owed_comptokens(program_id: &Pubkey, accounts: &[AccountInfo], _instr
     {
         let user_wallet_data = user_comptoken_token_account.try_borrow_data().unwrap();
         let user_comptoken_wallet = StateWithExtensions::<Account>::unpack(user_wallet_data.as_ref()).unwrap();
-        let global_data: &mut GlobalData = (&global_data_account).into();
-        let user_data: &mut UserData = (&user_data_account).into();
-        is_verified_human = user_data.is_verified_human;
+        let global_data: &mut GlobalData = (global_data_account).into();
+        let mut user_data: UserData = (user_data_account).into();
+        is_verified_human = user_data.is_verified_human();
 
         // get days since last update
-        let current_day = normalize_time(get_current_time());
+        let current_day = global_data.today();
         let days_since_last_update = (current_day - user_data.last_interest_payout_date) / SEC_PER_DAY;
 
         msg!("total before interest: {}", user_comptoken_wallet.base.amount);
@@ -545,19 +830,25 @@ pub fn get_owed_comptokens(program_id: &Pubkey, accounts: &[AccountInfo], _instr
         msg!("Interest: {}", interest);
         msg!("ubi: {}", ubi);
         user_data.last_interest_payout_date = current_day;
+        global_data.bank_liabilities.pay_interest(interest);
+        global_data.bank_liabilities.pay_verified_human_ubi(ubi);
     }
+    assert!(get_bank_balance(unpaid_interest_bank) >= interest, "interest bank cannot cover owed interest");
+    assert!(get_bank_balance(unpaid_verified_human_ubi_bank) >= ubi, "verified human ubi bank cannot cover owed ubi");
     if interest > 0 {
         transfer(
-            &unpaid_interest_bank,
-            &user_comptoken_token_account,
-            &comptoken_mint_account,
-            &global_data_account,
+            unpaid_interest_bank,
+            user_comptoken_token_account,
+            comptoken_mint_account,
+            global_data_account,
+            global_data_bump,
             &[
-                &extra_account_metas_account,
-                &transfer_hook_program,
-                &comptoken_program,
-                &user_data_account,
-                &interest_data_pda,
+                extra_account_metas_account,
+                transfer_hook_program,
+                comptoken_program,
+                user_data_account,
+                interest_data_pda,
+                exempt_accounts_account,
             ],
             interest,
         )?;
@@ -565,20 +856,109 @@ pub fn get_owed_comptokens(program_id: &Pubkey, accounts: &[AccountInfo], _instr
     // get ubi if verified
     if is_verified_human && ubi > 0 {
         transfer(
-            &unpaid_interest_bank,
-            &user_comptoken_token_account,
-            &comptoken_mint_account,
-            &global_data_account,
+            unpaid_verified_human_ubi_bank,
+            user_comptoken_token_account,
+            comptoken_mint_account,
+            global_data_account,
+            global_data_bump,
             &[
-                &extra_account_metas_account,
-                &transfer_hook_program,
-                &comptoken_program,
-                &user_data_account,
-                &interest_data_pda,
+                extra_account_metas_account,
+                transfer_hook_program,
+                comptoken_program,
+                user_data_account,
+                verified_human_ubi_data_pda,
+                exempt_accounts_account,
             ],
             ubi,
         )?;
     }
+    ComptokenEvent::InterestClaimed {
+        user_comptoken_wallet: *user_comptoken_token_account.key,
+        interest,
+        ubi,
+    }
+    .emit();
+
+    Ok(())
+}
+
+pub fn close_user_data_account(
+    program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8],
+) -> ProgramResult {
+    //  accounts order:
+    //      [] Comptoken Program
+    //      [] Comptoken Mint
+    //      [w] Comptoken Global Data (also mint authority)
+    //      [w] Comptoken Interest Bank
+    //      [w] Comptoken Verified Human UBI Bank
+    //      [] Interest Bank Data PDA (doesn't actually exist)
+    //      [] Verified Human UBI Bank Data PDA (doesn't actually exist)
+    //      [s] User Solana Wallet
+    //      [w] User's Comptoken Token Account
+    //      [w] User's Data
+    //      [] Transfer Hook Program
+    //      [] Extra Account Metas Account
+    //      [] Exempt Accounts Account (passed to the transfer hook)
+    //      [] Solana Token 2022 Program
+    //      [w] Recipient of the User's Data's Lamports
+
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            comptoken_program: Some((false, false)),
+            comptoken_mint: Some((false, false)),
+            global_data: Some((true, (false, true))),
+            interest_bank: Some((false, true)),
+            verified_human_ubi_bank: Some((false, true)),
+            interest_bank_data: Some((false, false)),
+            verified_human_ubi_bank_data: Some((false, false)),
+            user_wallet: Some((true, false)),
+            user_comptoken_token_account: Some((false, true)),
+            user_data: Some((true, (false, true))),
+            transfer_hook_program: Some((false, false)),
+            extra_account_metas: Some((false, false)),
+            exempt_accounts: Some((false, false)),
+            solana_token_2022_program: Some((false, false)),
+            ..Default::default()
+        },
+    )?;
+    let recipient_account = VerifiedAccountInfo::verify_account_signer_or_writable(
+        next_account_info(&mut verified_accounts.remaining_accounts.iter())?,
+        false,
+        true,
+    );
+
+    // a verified human must opt in to giving up their status (and the UBI that comes with it)
+    let forfeit_verified_human = match instruction_data {
+        [] | [0] => false,
+        [1] => true,
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+
+    // the user is paid everything they are owed before the record of when they were last paid goes away
+    pay_owed_comptokens(&verified_accounts)?;
+
+    let global_data_account = verified_accounts.global_data.unwrap();
+    let user_comptoken_token_account = verified_accounts.user_comptoken_token_account.unwrap();
+    let user_data_account = verified_accounts.user_data.unwrap();
+    assert_ne!(recipient_account.key, user_data_account.key, "the user data can't receive its own lamports");
+
+    let was_verified_human = UserData::from(&user_data_account).is_verified_human();
+    if was_verified_human {
+        assert!(forfeit_verified_human, "closing the user data of a verified human forfeits their status");
+        let global_data: &mut GlobalData = (&global_data_account).into();
+        global_data.daily_distribution_data.verified_humans -= 1;
+    }
+
+    let lamports = close_account(&user_data_account, &recipient_account)?;
+    ComptokenEvent::UserDataClosed {
+        user_comptoken_wallet: *user_comptoken_token_account.key,
+        user_data: *user_data_account.key,
+        lamports,
+        forfeited_verified_human: was_verified_human,
+    }
+    .emit();
 
     Ok(())
 }
@@ -586,6 +966,7 @@ pub fn get_owed_comptokens(program_id: &Pubkey, accounts: &[AccountInfo], _instr
 pub fn realloc_user_data(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
     //  Account Order
     //      [s, w] Payer Account
+    //      [] Comptoken Global Data (knows the comptoken mint)
     //      [s] User Solana Wallet
     //      [] User's Comptoken Token Account
     //      [w] User's Data
@@ -596,6 +977,7 @@ pub fn realloc_user_data(program_id: &Pubkey, accounts: &[AccountInfo], instruct
         program_id,
         AccountsToVerify {
             payer: Some((true, true)),
+            global_data: Some((true, (false, false))),
             user_wallet: Some((true, false)),
             user_comptoken_token_account: Some((false, false)),
             user_data: Some((true, (false, true))),
@@ -608,22 +990,64 @@ pub fn realloc_user_data(program_id: &Pubkey, accounts: &[AccountInfo], instruct
     let user_data_account = verified_accounts.user_data.unwrap();
     let system_program = verified_accounts.solana_program.unwrap();
 
-    // find space and minimum rent required for account
-    let rent_lamports = u64::from_le_bytes(instruction_data[0..8].try_into().expect("correct size"));
-    let new_size = usize::from_le_bytes(instruction_data[8..16].try_into().expect("correct size"));
+    let target_size =
+        user_data_size(read_proof_capacity(instruction_data)?).ok_or(ProgramError::InvalidInstructionData)?;
+    // an account can only grow by `MAX_PERMITTED_DATA_INCREASE` bytes per instruction, so larger capacities are reached
+    // by sending this instruction until the size stops changing
+    grow_user_data(&payer_account, &user_data_account, &system_program, target_size)
+}
 
-    // SAFETY: user_data_account is passed in from the runtime and is guaranteed to uphold the invariants original_data_len() and realloc assumes
-    assert!(new_size <= unsafe { user_data_account.original_data_len() } + MAX_PERMITTED_DATA_INCREASE);
-    assert!(user_data_account.data_len() < new_size);
-    assert!((new_size - USER_DATA_MIN_SIZE) % HASH_BYTES == 0);
-    let lamports = rent_lamports.saturating_sub(user_data_account.lamports());
+pub fn shrink_user_data(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+    //  Account Order
+    //      [] Comptoken Global Data (knows the comptoken mint and the valid blockhash)
+    //      [s, w] User Solana Wallet (receives the freed rent)
+    //      [] User's Comptoken Token Account
+    //      [w] User's Data
 
-    invoke_signed_verified(
-        &system_instruction::transfer(payer_account.key, user_data_account.key, lamports),
-        &[&user_data_account, &payer_account, &system_program],
-        &[],
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            global_data: Some((true, (false, false))),
+            user_wallet: Some((true, true)),
+            user_comptoken_token_account: Some((false, false)),
+            user_data: Some((true, (false, true))),
+            ..Default::default()
+        },
     )?;
-    user_data_account.realloc(new_size, false)
+
+    let global_data_account = verified_accounts.global_data.unwrap();
+    let user_wallet_account = verified_accounts.user_wallet.unwrap();
+    let user_data_account = verified_accounts.user_data.unwrap();
+
+    let proof_capacity = read_proof_capacity(instruction_data)?;
+    let new_size = user_data_size(proof_capacity).ok_or(ProgramError::InvalidInstructionData)?;
+    let current_size = user_data_account.data_len();
+    assert!(new_size <= current_size, "user data can only shrink");
+    {
+        let global_data: &mut GlobalData = (&global_data_account).into();
+        let mut user_data: UserData = (&user_data_account).into();
+        // proofs of an old blockhash would be discarded by the next proof anyway, so they don't need to be kept
+        user_data.update(&global_data.valid_blockhashes.valid_blockhash);
+        assert!(user_data.length <= proof_capacity, "user data can't shrink below the proofs it holds");
+    }
+    if new_size == current_size {
+        msg!("user data is already {} bytes", current_size);
+        return Ok(());
+    }
+
+    user_data_account.realloc(new_size, false)?;
+    refund_excess_rent(&user_data_account, &user_wallet_account)?;
+    ComptokenEvent::UserDataResized { user_data: *user_data_account.key, new_size: new_size as u64 }.emit();
+
+    Ok(())
+}
+
+// TODO: cpi to worldcoin to verify human
+// until then no one can be verified, so `verify_human` fails here instead of panicking
+fn verify_with_worldcoin(_user_wallet: &VerifiedAccountInfo) -> ProgramResult {
+    msg!("verifying humans with worldcoin is not implemented yet");
+    Err(ProgramError::InvalidInstructionData)
 }
 
 pub fn verify_human(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
@@ -638,6 +1062,7 @@ pub fn verify_human(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_
     //      [w] User's Data
     //      [] transfer hook program
     //      [] extra account metas account
+    //      [] exempt accounts account (passed to the transfer hook)
     //      [] Solana Token 2022 Program
 
     let verified_accounts = verify_accounts(
@@ -646,7 +1071,7 @@ pub fn verify_human(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_
         AccountsToVerify {
             comptoken_program: Some((false, false)),
             comptoken_mint: Some((false, false)),
-            global_data: Some((false, true)),
+            global_data: Some((true, (false, true))),
             future_ubi_bank: Some((false, true)),
             future_ubi_bank_data: Some((false, false)),
             user_wallet: Some((true, false)),
@@ -654,6 +1079,7 @@ pub fn verify_human(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_
             user_data: Some((true, (false, true))),
             transfer_hook_program: Some((false, false)),
             extra_account_metas: Some((false, false)),
+            exempt_accounts: Some((false, false)),
             solana_token_2022_program: Some((false, false)),
             ..Default::default()
         },
@@ -662,14 +1088,16 @@ pub fn verify_human(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_
     let comptoken_program = verified_accounts.comptoken_program.unwrap();
     let comptoken_mint = verified_accounts.comptoken_mint.unwrap();
     let global_data_account = verified_accounts.global_data.unwrap();
+    let global_data_bump = verified_accounts.global_data_bump.unwrap();
     let unpaid_future_ubi_bank_account = verified_accounts.future_ubi_bank.unwrap();
     let future_ubi_bank_data = verified_accounts.future_ubi_bank_data.unwrap();
     let user_comptoken_token_account = verified_accounts.user_comptoken_token_account.unwrap();
     let user_data_account = verified_accounts.user_data.unwrap();
     let transfer_hook_program = verified_accounts.transfer_hook_program.unwrap();
     let extra_account_metas_account = verified_accounts.extra_account_metas.unwrap();
+    let exempt_accounts_account = verified_accounts.exempt_accounts.unwrap();
 
-    todo!("cpi to worldcoin to verify human");
+    verify_with_worldcoin(&verified_accounts.user_wallet.unwrap())?;
     // TODO
     // also... what about when people die?
     // also... what happens about double attempts to verify?
@@ -678,9 +1106,9 @@ pub fn verify_human(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_
     let verified_humans;
     let future_ubi_amount;
     {
-        let user_data: &mut UserData = (&user_data_account).into();
+        let mut user_data: UserData = (&user_data_account).into();
         assert!(user_data.is_current(), "user data account is not current");
-        user_data.is_verified_human = true;
+        user_data.is_verified_human = true.into();
 
         let global_data: &mut GlobalData = (&global_data_account).into();
         verified_humans = global_data.daily_distribution_data.verified_humans;
@@ -692,74 +1120,855 @@ pub fn verify_human(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_
         future_ubi_amount = unpaid_future_ubi_bank.amount;
     }
 
+    let mut future_ubi = 0;
     if verified_humans <= FUTURE_UBI_VERIFIED_HUMANS {
         let amount = future_ubi_amount / (FUTURE_UBI_VERIFIED_HUMANS - verified_humans);
+        future_ubi = amount;
+        let global_data: &mut GlobalData = (&global_data_account).into();
+        global_data.bank_liabilities.pay_future_ubi(amount);
         transfer(
             &unpaid_future_ubi_bank_account,
             &user_comptoken_token_account,
             &comptoken_mint,
             &global_data_account,
+            global_data_bump,
             &[
                 &extra_account_metas_account,
                 &transfer_hook_program,
                 &comptoken_program,
                 &user_data_account,
                 &future_ubi_bank_data,
+                &exempt_accounts_account,
             ],
             amount,
         )?;
     }
+    ComptokenEvent::HumanVerified {
+        user_comptoken_wallet: *user_comptoken_token_account.key,
+        future_ubi,
+    }
+    .emit();
 
     Ok(())
 }
 
-fn mint(
-    mint_authority: &VerifiedAccountInfo, destination_wallet: &VerifiedAccountInfo, amount: u64,
-    accounts: &[&VerifiedAccountInfo],
+pub fn get_bank_reconciliation(
+    program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8],
 ) -> ProgramResult {
-    let instruction = mint_to(
-        &spl_token_2022::id(),
-        &COMPTOKEN_MINT_ADDRESS,
-        destination_wallet.key,
-        mint_authority.key,
-        &[mint_authority.key],
-        amount,
+    //  accounts order:
+    //      [] Comptoken Global Data (also mint authority)
+    //      [] Comptoken Interest Bank
+    //      [] Comptoken Verified Human UBI Bank
+    //      [] Comptoken Future UBI Bank
+
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            global_data: Some((true, (false, false))),
+            interest_bank: Some((false, false)),
+            verified_human_ubi_bank: Some((false, false)),
+            future_ubi_bank: Some((false, false)),
+            ..Default::default()
+        },
     )?;
-    invoke_signed_verified(&instruction, accounts, &[COMPTO_GLOBAL_DATA_ACCOUNT_SEEDS])
-}
 
-fn transfer<'a>(
-    source: &VerifiedAccountInfo<'a>, destination: &VerifiedAccountInfo<'a>, mint: &VerifiedAccountInfo<'a>,
-    global_data: &VerifiedAccountInfo<'a>, additional_accounts: &[&VerifiedAccountInfo<'a>], amount: u64,
-) -> ProgramResult {
-    let additional_accounts: Vec<_> = additional_accounts.iter().map(|account| account.0.clone()).collect();
-    onchain::invoke_transfer_checked(
-        &spl_token_2022::ID,
-        source.0.clone(),
-        mint.0.clone(),
-        destination.0.clone(),
-        global_data.0.clone(),
-        &additional_accounts,
-        amount,
-        MINT_DECIMALS,
-        &[COMPTO_GLOBAL_DATA_ACCOUNT_SEEDS],
-    )
+    let global_data_account = verified_accounts.global_data.unwrap();
+    let unpaid_interest_bank = verified_accounts.interest_bank.unwrap();
+    let unpaid_verified_human_ubi_bank = verified_accounts.verified_human_ubi_bank.unwrap();
+    let unpaid_future_ubi_bank = verified_accounts.future_ubi_bank.unwrap();
+
+    let global_data: &mut GlobalData = (&global_data_account).into();
+    let liabilities = &global_data.bank_liabilities;
+
+    // for each bank: balance, liabilities, surplus (negative if the bank is in deficit)
+    let mut data = Vec::new();
+    for (bank, owed) in [
+        (&unpaid_interest_bank, liabilities.interest_bank),
+        (&unpaid_verified_human_ubi_bank, liabilities.verified_human_ubi_bank),
+        (&unpaid_future_ubi_bank, liabilities.future_ubi_bank),
+    ] {
+        let balance = get_bank_balance(bank);
+        msg!("balance: {}, liabilities: {}, surplus: {}", balance, owed, surplus_or_deficit(balance, owed));
+        data.extend(balance.to_le_bytes());
+        data.extend(owed.to_le_bytes());
+        data.extend(surplus_or_deficit(balance, owed).to_le_bytes());
+    }
+    set_return_data(&data);
+    Ok(())
 }
 
-fn init_comptoken_account<'a>(
+// anyone may migrate, since migrating only changes the layout of the data and not what it means
+pub fn migrate_global_data(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+    //  accounts order:
+    //      [s, w] Payer (pays for any extra rent)
+    //      [w] Comptoken Global Data
+    //      [] Solana Program
+
+    // the stored bump can't be read until the global data is migrated, so it is searched for
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            payer: Some((true, true)),
+            global_data: Some((false, (false, true))),
+            solana_program: Some((false, false)),
+            ..Default::default()
+        },
+    )?;
+
+    let payer_account = verified_accounts.payer.unwrap();
+    let global_data_account = verified_accounts.global_data.unwrap();
+    let system_program = verified_accounts.solana_program.unwrap();
+
+    migrate_account::<GlobalData>(program_id, &payer_account, &global_data_account, &system_program)
+}
+
+// the global data must already be migrated, since it is needed to verify the user's comptoken token account
+pub fn migrate_user_data(program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8]) -> ProgramResult {
+    //  accounts order:
+    //      [s, w] Payer (pays for any extra rent)
+    //      [] Comptoken Global Data (knows the comptoken mint)
+    //      [] User's Comptoken Token Account
+    //      [w] User's Data
+    //      [] Solana Program
+
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            payer: Some((true, true)),
+            global_data: Some((true, (false, false))),
+            user_comptoken_token_account: Some((false, false)),
+            user_data: Some((false, (false, true))),
+            solana_program: Some((false, false)),
+            ..Default::default()
+        },
+    )?;
+
+    let payer_account = verified_accounts.payer.unwrap();
+    let user_data_account = verified_accounts.user_data.unwrap();
+    let user_data_bump = verified_accounts.user_data_bump.unwrap();
+    let system_program = verified_accounts.solana_program.unwrap();
+
+    // user data from before the account header is moved behind one, which is the current layout
+    if UserData::is_legacy(&user_data_account.try_borrow_data()?) {
+        let new_size = user_data_account.data_len() + ACCOUNT_HEADER_SIZE;
+        grow_legacy_account::<UserData>(program_id, &payer_account, &user_data_account, &system_program, new_size)?;
+        UserData::migrate_legacy(user_data_account.data_unchecked(), user_data_bump)?;
+        emit_account_migrated::<UserData>(&user_data_account, new_size);
+        return Ok(());
+    }
+    migrate_account::<UserData>(program_id, &payer_account, &user_data_account, &system_program)
+}
+
+// global data from before the account header lacks the addresses and bumps the program now records at initialization.
+// they are read from the mint, whose mint authority is the global data, and from the banks, so anyone may migrate it
+pub fn migrate_legacy_global_data(
+    program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8],
+) -> ProgramResult {
+    //  accounts order:
+    //      [s, w] Payer (pays for the extra rent)
+    //      [w] Comptoken Global Data
+    //      [] Comptoken Interest Bank
+    //      [] Comptoken Verified Human UBI Bank
+    //      [] Comptoken Future UBI Bank
+    //      [] Solana Program
+    //      [] Comptoken Mint (comes last, since it can't be checked against the legacy global data)
+
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            payer: Some((true, true)),
+            global_data: Some((false, (false, true))),
+            interest_bank: Some((false, false)),
+            verified_human_ubi_bank: Some((false, false)),
+            future_ubi_bank: Some((false, false)),
+            solana_program: Some((false, false)),
+            ..Default::default()
+        },
+    )?;
+
+    let payer_account = verified_accounts.payer.unwrap();
+    let global_data_account = verified_accounts.global_data.unwrap();
+    let unpaid_interest_bank = verified_accounts.interest_bank.unwrap();
+    let unpaid_verified_human_ubi_bank = verified_accounts.verified_human_ubi_bank.unwrap();
+    let unpaid_future_ubi_bank = verified_accounts.future_ubi_bank.unwrap();
+    let system_program = verified_accounts.solana_program.unwrap();
+    let comptoken_mint =
+        verify_legacy_comptoken_mint(accounts.get(6).ok_or(ProgramError::NotEnoughAccountKeys)?, &global_data_account);
+
+    assert!(
+        GlobalData::is_legacy(&global_data_account.try_borrow_data()?),
+        "only legacy global data is migrated this way"
+    );
+    let transfer_hook_program = {
+        let mint_data = comptoken_mint.try_borrow_data()?;
+        let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+        Option::<Pubkey>::from(mint.get_extension::<transfer_hook::TransferHook>()?.program_id)
+            .ok_or(ProgramError::InvalidAccountData)?
+    };
+    let bank_data_bump = |bank: &VerifiedAccountInfo| Pubkey::find_program_address(&[bank.key.as_ref()], program_id).1;
+    let bumps = Bumps {
+        global_data: verified_accounts.global_data_bump.unwrap(),
+        interest_bank: verified_accounts.interest_bank_bump.unwrap(),
+        verified_human_ubi_bank: verified_accounts.verified_human_ubi_bank_bump.unwrap(),
+        future_ubi_bank: verified_accounts.future_ubi_bank_bump.unwrap(),
+        interest_bank_data: bank_data_bump(&unpaid_interest_bank),
+        verified_human_ubi_bank_data: bank_data_bump(&unpaid_verified_human_ubi_bank),
+        future_ubi_bank_data: bank_data_bump(&unpaid_future_ubi_bank),
+        extra_account_metas: Pubkey::find_program_address(
+            &[b"extra-account-metas", comptoken_mint.key.as_ref()],
+            &transfer_hook_program,
+        )
+        .1,
+    };
+    let read_bank = |bank: &VerifiedAccountInfo| -> Result<Account, ProgramError> {
+        let bank_data = bank.try_borrow_data()?;
+        let bank = StateWithExtensions::<Account>::unpack(&bank_data)?.base;
+        assert_eq!(bank.mint, *comptoken_mint.key, "the banks hold comptokens");
+        Ok(bank)
+    };
+    let unpaid_interest_bank = read_bank(&unpaid_interest_bank)?;
+    let unpaid_verified_human_ubi_bank = read_bank(&unpaid_verified_human_ubi_bank)?;
+    let unpaid_future_ubi_bank = read_bank(&unpaid_future_ubi_bank)?;
+
+    let new_size = account_size::<GlobalData>();
+    grow_legacy_account::<GlobalData>(program_id, &payer_account, &global_data_account, &system_program, new_size)?;
+    GlobalData::migrate_legacy(
+        global_data_account.data_unchecked(),
+        comptoken_mint.key,
+        &transfer_hook_program,
+        bumps,
+        &unpaid_interest_bank,
+        &unpaid_verified_human_ubi_bank,
+        &unpaid_future_ubi_bank,
+    )?;
+    emit_account_migrated::<GlobalData>(&global_data_account, new_size);
+
+    Ok(())
+}
+
+pub fn update_metadata_field(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+    //  accounts order:
+    //      [s, w] Admin (pays for any extra rent)
+    //      [w] Comptoken Mint (holds the metadata)
+    //      [] Comptoken Global Data (metadata update authority)
+    //      [] Solana Program
+    //      [] Solana Token 2022 Program
+
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            admin: Some((true, true)),
+            comptoken_mint: Some((false, true)),
+            global_data: Some((true, (false, false))),
+            solana_program: Some((false, false)),
+            solana_token_2022_program: Some((false, false)),
+            ..Default::default()
+        },
+    )?;
+
+    let admin_account = verified_accounts.admin.unwrap();
+    let comptoken_mint = verified_accounts.comptoken_mint.unwrap();
+    let global_data_account = verified_accounts.global_data.unwrap();
+    let global_data_bump = verified_accounts.global_data_bump.unwrap();
+    let system_program = verified_accounts.solana_program.unwrap();
+
+    let (field, value) = read_field_update(instruction_data)?;
+    update_metadata(
+        &admin_account,
+        &comptoken_mint,
+        &global_data_account,
+        global_data_bump,
+        &system_program,
+        field,
+        value,
+    )
+}
+
+// removes a custom field, e.g. to stop the daily distribution event refreshing it. the freed rent stays in the mint
+pub fn remove_metadata_key(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+    //  accounts order:
+    //      [s] Admin
+    //      [w] Comptoken Mint (holds the metadata)
+    //      [] Comptoken Global Data (metadata update authority)
+    //      [] Solana Token 2022 Program
+
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            admin: Some((true, false)),
+            comptoken_mint: Some((false, true)),
+            global_data: Some((true, (false, false))),
+            solana_token_2022_program: Some((false, false)),
+            ..Default::default()
+        },
+    )?;
+
+    let comptoken_mint = verified_accounts.comptoken_mint.unwrap();
+    let global_data_account = verified_accounts.global_data.unwrap();
+    let global_data_bump = verified_accounts.global_data_bump.unwrap();
+
+    let key = read_removed_key(instruction_data)?;
+    let remove_key = spl_token_metadata_interface::instruction::remove_key(
+        &spl_token_2022::ID,
+        comptoken_mint.key,
+        global_data_account.key,
+        key,
+        false,
+    );
+    invoke_signed_verified(
+        &remove_key,
+        &[&comptoken_mint, &global_data_account],
+        &[&[COMPTO_GLOBAL_DATA_ACCOUNT_SEED, &[global_data_bump]]],
+    )
+}
+
+// sets the accounts the transfer hook is passed after the comptoken accounts, e.g. to add an account to every transfer
+pub fn update_extra_account_metas(
+    program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8],
+) -> ProgramResult {
+    //  accounts order:
+    //      [s, w] Admin (pays for any extra rent and receives any freed rent)
+    //      [] Comptoken Mint
+    //      [] Comptoken Global Data (mint authority)
+    //      [] Transfer Hook Program
+    //      [w] Extra Account Metas Account
+    //      [] Solana Program
+
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            admin: Some((true, true)),
+            comptoken_mint: Some((false, false)),
+            global_data: Some((true, (false, false))),
+            transfer_hook_program: Some((false, false)),
+            extra_account_metas: Some((false, true)),
+            solana_program: Some((false, false)),
+            ..Default::default()
+        },
+    )?;
+
+    let admin_account = verified_accounts.admin.as_ref().unwrap();
+    let extra_account_metas = read_extra_account_metas(instruction_data)?;
+    set_extra_account_metas(admin_account, &verified_accounts, extra_account_metas)
+}
+
+// extra account metas written before the exempt accounts were added list only the first `LEGACY_COMPTOKEN_ACCOUNT_METAS`
+// comptoken accounts, so every transfer fails once the transfer hook expects the exempt accounts. anyone may migrate
+// them, since the accounts the admin set after the comptoken accounts are kept
+pub fn migrate_extra_account_metas(
+    program_id: &Pubkey, accounts: &[AccountInfo], _instruction_data: &[u8],
+) -> ProgramResult {
+    //  accounts order:
+    //      [s, w] Payer (pays for the extra rent)
+    //      [] Comptoken Mint
+    //      [] Comptoken Global Data (mint authority)
+    //      [] Transfer Hook Program
+    //      [w] Extra Account Metas Account
+    //      [] Solana Program
+
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            payer: Some((true, true)),
+            comptoken_mint: Some((false, false)),
+            global_data: Some((true, (false, false))),
+            transfer_hook_program: Some((false, false)),
+            extra_account_metas: Some((false, true)),
+            solana_program: Some((false, false)),
+            ..Default::default()
+        },
+    )?;
+
+    let payer_account = verified_accounts.payer.as_ref().unwrap();
+    let extra_account_metas_account = verified_accounts.extra_account_metas.as_ref().unwrap();
+
+    let admin_account_metas = {
+        let data = extra_account_metas_account.try_borrow_data()?;
+        let tlv_state = TlvStateBorrowed::unpack(&data)?;
+        let account_metas = ExtraAccountMetaList::unpack_with_tlv_state::<ExecuteInstruction>(&tlv_state)?;
+        let account_metas = account_metas.data();
+        if account_metas.get(LEGACY_COMPTOKEN_ACCOUNT_METAS) == Some(&exempt_accounts_account_meta()?) {
+            msg!("the extra account metas are already current");
+            return Ok(());
+        }
+        account_metas
+            .get(LEGACY_COMPTOKEN_ACCOUNT_METAS..)
+            .ok_or(ProgramError::InvalidAccountData)?
+            .to_vec()
+    };
+    // the transfer hook puts the current comptoken accounts in front of these
+    set_extra_account_metas(payer_account, &verified_accounts, &admin_account_metas)
+}
+
+// lets a comptoken token account (e.g. an escrow or a pool) send and receive comptokens without its owed comptokens
+// being claimed first
+pub fn add_exempt_account(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+    //  accounts order:
+    //      [s, w] Admin (pays for the extra rent)
+    //      [] Comptoken Global Data (knows the admin)
+    //      [w] Exempt Accounts Account (created with the first exempt account)
+    //      [] Solana Program
+
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            admin: Some((true, true)),
+            global_data: Some((true, (false, false))),
+            exempt_accounts: Some((false, true)),
+            solana_program: Some((false, false)),
+            ..Default::default()
+        },
+    )?;
+
+    let admin_account = verified_accounts.admin.unwrap();
+    let exempt_accounts_account = verified_accounts.exempt_accounts.unwrap();
+    let exempt_accounts_bump = verified_accounts.exempt_accounts_bump.unwrap();
+    let system_program = verified_accounts.solana_program.unwrap();
+
+    let account = read_pubkey(instruction_data)?;
+    if exempt_accounts_account.data_is_empty() {
+        let size = exempt_accounts_size(1);
+        create_pda_account(
+            &admin_account,
+            &exempt_accounts_account,
+            Rent::get()?.minimum_balance(size),
+            size as u64,
+            program_id,
+            &[&[EXEMPT_ACCOUNTS_SEED, &[exempt_accounts_bump]]],
+        )?;
+        ExemptAccounts::initialize(exempt_accounts_account.data_unchecked(), exempt_accounts_bump)?;
+    } else {
+        let new_size = exempt_accounts_size(ExemptAccounts::from(&exempt_accounts_account).length + 1);
+        fund_rent(&admin_account, &exempt_accounts_account, &system_program, new_size)?;
+        exempt_accounts_account.realloc(new_size, false)?;
+    }
+    ExemptAccounts::from(&exempt_accounts_account).insert(&account);
+    Ok(())
+}
+
+pub fn remove_exempt_account(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+    //  accounts order:
+    //      [s, w] Admin (receives the freed rent)
+    //      [] Comptoken Global Data (knows the admin)
+    //      [w] Exempt Accounts Account
+
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            admin: Some((true, true)),
+            global_data: Some((true, (false, false))),
+            exempt_accounts: Some((false, true)),
+            ..Default::default()
+        },
+    )?;
+
+    let admin_account = verified_accounts.admin.unwrap();
+    let exempt_accounts_account = verified_accounts.exempt_accounts.unwrap();
+
+    let account = read_pubkey(instruction_data)?;
+    let new_size = {
+        let mut exempt_accounts = ExemptAccounts::from(&exempt_accounts_account);
+        exempt_accounts.remove(&account);
+        exempt_accounts_size(exempt_accounts.length)
+    };
+    exempt_accounts_account.realloc(new_size, false)?;
+    refund_excess_rent(&exempt_accounts_account, &admin_account)
+}
+
+// also gives an admin to a program initialized before the admin was recorded
+pub fn set_admin(program_id: &Pubkey, accounts: &[AccountInfo], instruction_data: &[u8]) -> ProgramResult {
+    //  accounts order:
+    //      [s] Upgrade Authority (of the comptoken program)
+    //      [] Comptoken Program Data (knows the upgrade authority)
+    //      [w] Comptoken Global Data (records the admin)
+
+    let verified_accounts = verify_accounts(
+        accounts,
+        program_id,
+        AccountsToVerify {
+            upgrade_authority: Some((true, false)),
+            comptoken_program_data: Some((false, false)),
+            global_data: Some((true, (false, true))),
+            ..Default::default()
+        },
+    )?;
+
+    let global_data_account = verified_accounts.global_data.unwrap();
+
+    let admin = read_pubkey(instruction_data)?;
+    let global_data: &mut GlobalData = (&global_data_account).into();
+    global_data.admin = admin;
+    Ok(())
+}
+
+fn get_bank_balance(bank: &VerifiedAccountInfo) -> u64 {
+    let bank_data = bank.try_borrow_data().unwrap();
+    StateWithExtensions::<Account>::unpack(&bank_data).unwrap().base.amount
+}
+
+fn mint<'a>(
+    mint: &VerifiedAccountInfo<'a>, mint_authority: &VerifiedAccountInfo<'a>, mint_authority_bump: u8,
+    destination_wallet: &VerifiedAccountInfo<'a>, amount: u64,
+) -> ProgramResult {
+    let instruction = mint_to(
+        &spl_token_2022::id(),
+        mint.key,
+        destination_wallet.key,
+        mint_authority.key,
+        &[mint_authority.key],
+        amount,
+    )?;
+    invoke_signed_verified(
+        &instruction,
+        &[mint, destination_wallet, mint_authority],
+        &[&[COMPTO_GLOBAL_DATA_ACCOUNT_SEED, &[mint_authority_bump]]],
+    )
+}
+
+// anyone can send lamports to a PDA's address before it is created, which would make `create_account` fail and block
+// whatever creates it, so an already funded account is topped up and allocated instead
+fn create_pda_account<'a>(
+    payer: &VerifiedAccountInfo<'a>, account: &VerifiedAccountInfo<'a>, rent_lamports: u64, space: u64,
+    program_id: &Pubkey, signers_seeds: &[&[&[u8]]],
+) -> ProgramResult {
+    if account.lamports() == 0 {
+        return create_pda(payer, account, rent_lamports, space, program_id, signers_seeds);
+    }
+    let top_up = rent_lamports.saturating_sub(account.lamports());
+    if top_up > 0 {
+        let transfer_instr = system_instruction::transfer(payer.key, account.key, top_up);
+        invoke_signed_verified(&transfer_instr, &[payer, account], &[])?;
+    }
+    let allocate_instr = system_instruction::allocate(account.key, space);
+    invoke_signed_verified(&allocate_instr, &[account], signers_seeds)?;
+    let assign_instr = system_instruction::assign(account.key, program_id);
+    invoke_signed_verified(&assign_instr, &[account], signers_seeds)
+}
+
+// tops `account` up to the rent exempt minimum for `size` bytes
+fn fund_rent<'a>(
+    payer: &VerifiedAccountInfo<'a>, account: &VerifiedAccountInfo<'a>, system_program: &VerifiedAccountInfo<'a>,
+    size: usize,
+) -> ProgramResult {
+    let lamports = Rent::get()?.minimum_balance(size).saturating_sub(account.lamports());
+    if lamports > 0 {
+        invoke_signed_verified(
+            &system_instruction::transfer(payer.key, account.key, lamports),
+            &[account, payer, system_program],
+            &[],
+        )?;
+    }
+    Ok(())
+}
+
+// grows `user_data_account` toward `target_size`, by at most `MAX_PERMITTED_DATA_INCREASE` bytes in this instruction
+fn grow_user_data<'a>(
+    payer: &VerifiedAccountInfo<'a>, user_data_account: &VerifiedAccountInfo<'a>,
+    system_program: &VerifiedAccountInfo<'a>, target_size: usize,
+) -> ProgramResult {
+    assert!(target_size <= MAX_PERMITTED_DATA_LENGTH as usize, "user data can't be that large");
+    let current_size = user_data_account.data_len();
+    assert!(current_size <= target_size, "user data can only grow");
+
+    // SAFETY: user_data_account is passed in from the runtime and is guaranteed to uphold the invariants original_data_len() and realloc assumes
+    let max_size = unsafe { user_data_account.original_data_len() } + MAX_PERMITTED_DATA_INCREASE;
+    let new_size = if target_size <= max_size {
+        target_size
+    } else {
+        current_size + (max_size - current_size) / HASH_BYTES * HASH_BYTES
+    };
+    if new_size == current_size {
+        msg!("user data is already {} bytes", current_size);
+        return Ok(());
+    }
+
+    fund_rent(payer, user_data_account, system_program, new_size)?;
+    user_data_account.realloc(new_size, false)?;
+    refund_excess_rent(user_data_account, payer)?;
+    ComptokenEvent::UserDataResized { user_data: *user_data_account.key, new_size: new_size as u64 }.emit();
+    Ok(())
+}
+
+// returns whatever `account`, which the program must own, holds above the rent exempt minimum for its size
+fn refund_excess_rent(account: &VerifiedAccountInfo, recipient: &VerifiedAccountInfo) -> ProgramResult {
+    let excess = account.lamports().saturating_sub(Rent::get()?.minimum_balance(account.data_len()));
+    if excess > 0 {
+        msg!("refunding {} lamports", excess);
+        **account.try_borrow_mut_lamports()? -= excess;
+        **recipient.try_borrow_mut_lamports()? += excess;
+    }
+    Ok(())
+}
+
+// the capacity a user data account should have, in proofs
+fn read_proof_capacity(instruction_data: &[u8]) -> Result<usize, ProgramError> {
+    let capacity = instruction_data.get(0..8).ok_or(ProgramError::InvalidInstructionData)?;
+    usize::try_from(u64::from_le_bytes(capacity.try_into().unwrap())).map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+// e.g. the comptoken token account to exempt (or to no longer exempt), or the new admin
+fn read_pubkey(instruction_data: &[u8]) -> Result<Pubkey, ProgramError> {
+    Pubkey::try_from(instruction_data).map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+// the extra account metas are sent packed, as the transfer hook stores them
+fn read_extra_account_metas(instruction_data: &[u8]) -> Result<&[ExtraAccountMeta], ProgramError> {
+    bytemuck::try_cast_slice(instruction_data).map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+// the comptoken accounts the transfer hook listed before the exempt accounts: the comptoken program and the sender's
+// and receiver's user data
+const LEGACY_COMPTOKEN_ACCOUNT_METAS: usize = 3;
+
+// the exempt accounts, as the transfer hook lists them after the legacy comptoken accounts
+fn exempt_accounts_account_meta() -> Result<ExtraAccountMeta, ProgramError> {
+    // the index of the comptoken program in the transfer hook's execute instruction
+    const COMPTOKEN_PROGRAM_INDEX: u8 = 5;
+    ExtraAccountMeta::new_external_pda_with_seeds(
+        COMPTOKEN_PROGRAM_INDEX,
+        &[Seed::Literal { bytes: EXEMPT_ACCOUNTS_SEED.to_vec() }],
+        false,
+        false,
+    )
+}
+
+// the transfer hook keeps its comptoken accounts first, and replaces the accounts that follow them with
+// `extra_account_metas`. `payer` pays for any extra rent and receives any freed rent
+fn set_extra_account_metas<'a>(
+    payer: &VerifiedAccountInfo<'a>, verified_accounts: &VerifiedAccounts<'a, '_>,
+    extra_account_metas: &[ExtraAccountMeta],
+) -> ProgramResult {
+    let comptoken_mint = verified_accounts.comptoken_mint.as_ref().unwrap();
+    let global_data_account = verified_accounts.global_data.as_ref().unwrap();
+    let global_data_bump = verified_accounts.global_data_bump.unwrap();
+    let transfer_hook_program = verified_accounts.transfer_hook_program.as_ref().unwrap();
+    let extra_account_metas_account = verified_accounts.extra_account_metas.as_ref().unwrap();
+    let solana_program = verified_accounts.solana_program.as_ref().unwrap();
+
+    let mut update_transfer_hook_instruction = spl_transfer_hook_interface::instruction::update_extra_account_meta_list(
+        transfer_hook_program.key,
+        extra_account_metas_account.key,
+        comptoken_mint.key,
+        global_data_account.key,
+        extra_account_metas,
+    );
+    update_transfer_hook_instruction.accounts.push(AccountMeta::new(*payer.key, true));
+    update_transfer_hook_instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*solana_program.key, false));
+
+    invoke_signed_verified(
+        &update_transfer_hook_instruction,
+        &[extra_account_metas_account, comptoken_mint, global_data_account, payer, solana_program],
+        &[&[COMPTO_GLOBAL_DATA_ACCOUNT_SEED, &[global_data_bump]]],
+    )
+}
+
+fn transfer<'a>(
+    source: &VerifiedAccountInfo<'a>, destination: &VerifiedAccountInfo<'a>, mint: &VerifiedAccountInfo<'a>,
+    global_data: &VerifiedAccountInfo<'a>, global_data_bump: u8, additional_accounts: &[&VerifiedAccountInfo<'a>],
+    amount: u64,
+) -> ProgramResult {
+    let additional_accounts: Vec<_> = additional_accounts.iter().map(|account| account.0.clone()).collect();
+    onchain::invoke_transfer_checked(
+        &spl_token_2022::ID,
+        source.0.clone(),
+        mint.0.clone(),
+        destination.0.clone(),
+        global_data.0.clone(),
+        &additional_accounts,
+        amount,
+        MINT_DECIMALS,
+        &[&[COMPTO_GLOBAL_DATA_ACCOUNT_SEED, &[global_data_bump]]],
+    )
+}
+
+fn init_comptoken_account<'a>(
     account: &VerifiedAccountInfo<'a>, owner: &VerifiedAccountInfo, signer_seeds: &[&[&[u8]]],
     mint: &VerifiedAccountInfo<'a>,
 ) -> ProgramResult {
-    let init_comptoken_account_instr = spl_token_2022::instruction::initialize_account3(
+    let init_comptoken_account_instr =
+        spl_token_2022::instruction::initialize_account3(&spl_token_2022::ID, account.key, mint.key, owner.key)?;
+    invoke_signed_verified(&init_comptoken_account_instr, &[account, mint], signer_seeds)
+}
+
+// creates a bank as a comptoken account owned by the global data, sized for the account extensions the mint requires
+fn create_bank_account<'a>(
+    payer: &VerifiedAccountInfo<'a>, bank: &VerifiedAccountInfo<'a>, bank_seeds: &[&[u8]],
+    global_data: &VerifiedAccountInfo<'a>, mint: &VerifiedAccountInfo<'a>,
+) -> ProgramResult {
+    let mint_extensions = StateWithExtensions::<Mint>::unpack(&mint.try_borrow_data()?)?.get_extension_types()?;
+    let space = ExtensionType::try_calculate_account_len::<Account>(
+        &ExtensionType::get_required_init_account_extensions(&mint_extensions),
+    )?;
+    let rent_lamports = Rent::get()?.minimum_balance(space);
+    create_pda(payer, bank, rent_lamports, space as u64, &spl_token_2022::ID, &[bank_seeds])?;
+    init_comptoken_account(bank, global_data, &[], mint)
+}
+
+// creates the mint with the global data as the authority of the mint and of its extensions: the transfer hook, and the
+// metadata, which is stored in the mint itself
+fn create_comptoken_mint<'a>(
+    payer: &VerifiedAccountInfo<'a>, mint: &VerifiedAccountInfo<'a>, mint_bump: u8,
+    global_data: &VerifiedAccountInfo<'a>, global_data_seeds: &[&[u8]],
+    transfer_hook_program: &VerifiedAccountInfo<'a>,
+) -> ProgramResult {
+    let space = ExtensionType::try_calculate_account_len::<Mint>(&[
+        ExtensionType::TransferHook,
+        ExtensionType::MetadataPointer,
+    ])?;
+    let metadata = TokenMetadata {
+        update_authority: Some(*global_data.key).try_into()?,
+        mint: *mint.key,
+        name: COMPTOKEN_NAME.to_string(),
+        symbol: COMPTOKEN_SYMBOL.to_string(),
+        uri: COMPTOKEN_URI.to_string(),
+        additional_metadata: Vec::new(),
+    };
+    // token 2022 grows the mint to fit the metadata, but the rent for it has to be there already
+    let rent_lamports = Rent::get()?.minimum_balance(space + metadata.tlv_size_of()?);
+    create_pda_account(
+        payer,
+        mint,
+        rent_lamports,
+        space as u64,
         &spl_token_2022::ID,
-        account.key,
-        &COMPTOKEN_MINT_ADDRESS,
-        owner.key,
+        &[&[COMPTO_MINT_SEED, &[mint_bump]]],
     )?;
-    invoke_signed_verified(&init_comptoken_account_instr, &[account, mint], signer_seeds)
+
+    // the extensions have to be initialized before the mint
+    let authority = Some(*global_data.key);
+    let initialize_instructions = [
+        transfer_hook::instruction::initialize(
+            &spl_token_2022::ID,
+            mint.key,
+            authority,
+            Some(*transfer_hook_program.key),
+        )?,
+        metadata_pointer::instruction::initialize(&spl_token_2022::ID, mint.key, authority, Some(*mint.key))?,
+        initialize_mint2(&spl_token_2022::ID, mint.key, global_data.key, None, MINT_DECIMALS)?,
+    ];
+    for instruction in &initialize_instructions {
+        invoke_signed_verified(instruction, &[mint], &[])?;
+    }
+
+    let initialize_metadata = spl_token_metadata_interface::instruction::initialize(
+        &spl_token_2022::ID,
+        mint.key,
+        global_data.key,
+        mint.key,
+        global_data.key,
+        metadata.name,
+        metadata.symbol,
+        metadata.uri,
+    );
+    invoke_signed_verified(&initialize_metadata, &[mint, global_data], &[global_data_seeds])
+}
+
+// sets `field` of the mint's metadata to `value`, first funding the rent for any growth from `payer`
+fn update_metadata<'a>(
+    payer: &VerifiedAccountInfo<'a>, mint: &VerifiedAccountInfo<'a>, global_data: &VerifiedAccountInfo<'a>,
+    global_data_bump: u8, system_program: &VerifiedAccountInfo<'a>, field: Field, value: String,
+) -> ProgramResult {
+    // token 2022 resizes the mint to fit the metadata, but the rent for it has to be there already
+    let new_size = {
+        let mint_data = mint.try_borrow_data()?;
+        let mut metadata =
+            StateWithExtensions::<Mint>::unpack(&mint_data)?.get_variable_len_extension::<TokenMetadata>()?;
+        let old_metadata_size = metadata.tlv_size_of()?;
+        metadata.update(field.clone(), value.clone());
+        mint_data.len() - old_metadata_size + metadata.tlv_size_of()?
+    };
+    fund_rent(payer, mint, system_program, new_size)?;
+
+    let update_field = spl_token_metadata_interface::instruction::update_field(
+        &spl_token_2022::ID,
+        mint.key,
+        global_data.key,
+        field,
+        value,
+    );
+    invoke_signed_verified(
+        &update_field,
+        &[mint, global_data],
+        &[&[COMPTO_GLOBAL_DATA_ACCOUNT_SEED, &[global_data_bump]]],
+    )
+}
+
+// upgrades `account` to the current layout of `T`, topping up its rent if it grows. migrating an account that is
+// already current does nothing, so a migration can safely be sent more than once
+fn migrate_account<'a, T: MigratableAccount>(
+    program_id: &Pubkey, payer: &VerifiedAccountInfo<'a>, account: &VerifiedAccountInfo<'a>,
+    system_program: &VerifiedAccountInfo<'a>,
+) -> ProgramResult {
+    assert_eq!(account.owner, program_id);
+    let (version, old_size, new_size) = {
+        let data = account.try_borrow_data()?;
+        let version = account_version::<T>(&data)?;
+        (version, data.len(), T::migrated_size(version, &data))
+    };
+    if version == T::VERSION {
+        msg!("account is already at version {}", version);
+        return Ok(());
+    }
+    msg!("migrating from version {} to {}", version, T::VERSION);
+
+    fund_rent(payer, account, system_program, new_size)?;
+    // the body is rewritten at the larger of the two sizes
+    if new_size > old_size {
+        account.realloc(new_size, true)?;
+    }
+    migrate_account_data::<T>(account.data_unchecked(), version)?;
+    if new_size < old_size {
+        account.realloc(new_size, false)?;
+    }
+    emit_account_migrated::<T>(account, new_size);
+
+    Ok(())
+}
+
+// accounts from before the account header are only ever grown, to be moved behind a header
+fn grow_legacy_account<'a, T: VersionedAccount>(
+    program_id: &Pubkey, payer: &VerifiedAccountInfo<'a>, account: &VerifiedAccountInfo<'a>,
+    system_program: &VerifiedAccountInfo<'a>, new_size: usize,
+) -> ProgramResult {
+    assert_eq!(account.owner, program_id);
+    msg!("migrating from before the account header to version {}", T::VERSION);
+    fund_rent(payer, account, system_program, new_size)?;
+    account.realloc(new_size, true)
+}
+
+fn emit_account_migrated<T: VersionedAccount>(account: &VerifiedAccountInfo, new_size: usize) {
+    ComptokenEvent::AccountMigrated {
+        account: *account.key,
+        version: T::VERSION,
+        new_size: new_size as u64,
+    }
+    .emit();
+}
+
+// moves all of `account`'s lamports to `recipient` and hands the emptied account back to the system program
+fn close_account(account: &VerifiedAccountInfo, recipient: &VerifiedAccountInfo) -> Result<u64, ProgramError> {
+    let lamports = account.lamports();
+    let mut recipient_lamports = recipient.try_borrow_mut_lamports()?;
+    **recipient_lamports = recipient_lamports.checked_add(lamports).ok_or(ProgramError::ArithmeticOverflow)?;
+    **account.try_borrow_mut_lamports()? = 0;
+    account.realloc(0, false)?;
+    account.assign(&system_program::ID);
+    Ok(lamports)
 }
 
 fn store_hash(proof: ComptokenProof, data_account: &VerifiedAccountInfo, validhash: &ValidBlockhashes) {
-    let user_data: &mut UserData = data_account.into();
+    let mut user_data: UserData = data_account.into();
     user_data.insert(&proof.hash, &validhash.valid_blockhash);
 }
@@ -1,22 +1,15 @@
-use std::mem;
-use hex;
-use solana_program::msg;
+use sha2::{Digest, Sha256};
 use spl_token_2022::solana_program::{
-    hash::{Hash, Hasher, HASH_BYTES},
+    hash::{Hash, Hasher},
     pubkey::Pubkey,
 };
-use byteorder::{LittleEndian, WriteBytesExt};
-use sha2::{Sha256, Digest};
-use std::io::Cursor;
 
 use comptoken_utils::verify_accounts::VerifiedAccountInfo;
 
 use crate::global_data::valid_blockhashes::ValidBlockhashes;
 
-// ensure this remains consistent with comptoken_proof.js
-const MIN_NUM_ZEROED_BITS: u32 = 3; // TODO: replace with permanent value
-
-pub const VERIFY_DATA_SIZE: usize = HASH_BYTES + mem::size_of::<u64>() + HASH_BYTES;
+// pubkey (32) + extra data (32) + nonce (4) + version (4) + timestamp (4)
+pub const VERIFY_DATA_SIZE: usize = 76;
 
 // Ensure changes to this struct remain consistent with comptoken_proof.js
 #[derive(Debug)]
@@ -25,7 +18,6 @@ pub struct ComptokenProof {
     pub hash: Hash,
 }
 
-
 // 4 bytes: <version>
 // 32 bytes: <previous block hash according to the compto program>
 // 32 bytes: <merkle root>
@@ -37,7 +29,7 @@ pub struct ComptokenProof {
 
 impl ComptokenProof {
     pub fn from_bytes(data: &[u8], valid_blockhashes: &ValidBlockhashes) -> Result<Self, &'static str> {
-        if data.len() != 76 {
+        if data.len() != VERIFY_DATA_SIZE {
             return Err("Invalid byte slice length");
         }
 
@@ -45,16 +37,14 @@ impl ComptokenProof {
         let extra_data: [u8; 32] = data[32..64].try_into().map_err(|_| "Failed to parse extra_data")?;
         let nonce: [u8; 4] = data[64..68].try_into().map_err(|_| "Failed to parse nonce")?;
         let version: [u8; 4] = data[68..72].try_into().map_err(|_| "Failed to parse version")?;
-        let timestamp: [u8; 4] = data[72..76].try_into().map_err(|_| "Failed to parse timestamp")?; 
+        let timestamp: [u8; 4] = data[72..76].try_into().map_err(|_| "Failed to parse timestamp")?;
 
         let mut valid_blockhash_bytes = valid_blockhashes.valid_blockhash.to_bytes();
         valid_blockhash_bytes.reverse();
 
         let mut merkleroot_hasher = Hasher::default();
         merkleroot_hasher.hash(&extra_data);
-        msg!("extra_data: {:?}", hex::encode(extra_data));
         merkleroot_hasher.hash(&pubkey_bytes);
-        msg!("pubkey_bytes: {:?}", hex::encode(pubkey_bytes));
         let merkleroot_hash1 = merkleroot_hasher.result();
         merkleroot_hasher = Hasher::default();
         merkleroot_hasher.hash(&merkleroot_hash1.to_bytes());
@@ -62,25 +52,22 @@ impl ComptokenProof {
         let binding = hex::decode("d8ad0e18").unwrap();
         let nbits = binding.as_slice();
 
-
         let mut block_header = [0u8; 80];
         block_header[0..4].copy_from_slice(&version);
         block_header[4..36].copy_from_slice(&valid_blockhash_bytes);
         block_header[36..68].copy_from_slice(&merkleroot_hash2.to_bytes());
         block_header[68..72].copy_from_slice(&timestamp);
-        block_header[72..76].copy_from_slice(&nbits);
+        block_header[72..76].copy_from_slice(nbits);
         block_header[76..80].copy_from_slice(&nonce);
-        
-        let hash1 = Sha256::digest(&block_header);
-        let hash2 = Sha256::digest(&hash1);
+
+        let hash1 = Sha256::digest(block_header);
+        let hash2 = Sha256::digest(hash1);
         let mut final_hash = hash2.to_vec();
         final_hash.reverse();
 
-        msg!("Final Hash: {:?}", hex::encode(&final_hash));
         let pubkey = Pubkey::new_from_array(pubkey_bytes);
-        // msg!("hash2: {:?}", hex::encode(hash2.to_bytes()));
         Ok(Self {
-            pubkey: pubkey,
+            pubkey,
             hash: Hash::new_from_array(final_hash.try_into().unwrap()),
         })
     }
@@ -90,12 +77,12 @@ impl ComptokenProof {
         // Represent it as a byte array for comparison
         // easy mode (dev mode)
         let target_bytes: [u8; 32] = [
-            0x0e, 0xad, 0xd8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
-            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
+            0x0e, 0xad, 0xd8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
         ];
         // the real target
         // let target_bytes: [u8; 32] = [
-        //     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0e, 0xad, 0xd8, 0x00, 0x00, 0x00, 0x00, 0x00, 
+        //     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0e, 0xad, 0xd8, 0x00, 0x00, 0x00, 0x00, 0x00,
         //     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00
         // ];
 
@@ -127,55 +114,79 @@ impl ComptokenProof {
 mod test {
 
     use super::*;
+    use proptest::prelude::*;
     use spl_token_2022::solana_program::pubkey::PUBKEY_BYTES;
 
-    const ZERO_PUBKEY: Pubkey = Pubkey::new_from_array([0; PUBKEY_BYTES]);
-
-    fn create_arbitrary_block(pubkey: &Pubkey, recent_block_hash: Hash, nonce: u64, hash: Hash) -> ComptokenProof {
-        ComptokenProof { pubkey, recent_block_hash, nonce, hash }
+    fn create_valid_blockhashes(valid_blockhash: Hash) -> ValidBlockhashes {
+        ValidBlockhashes {
+            announced_blockhash: Hash::default(),
+            announced_blockhash_time: 0,
+            valid_blockhash,
+            valid_blockhash_time: 0,
+        }
     }
 
     #[test]
-    fn test_leading_zeroes() {
+    fn test_is_hash_lower_than_target() {
         let mut hash_array = [0; 32];
-        let mut hash = Hash::new_from_array(hash_array);
-        assert_eq!(256, ComptokenProof::leading_zeroes(&hash));
+        assert!(ComptokenProof::is_hash_lower_than_target(&Hash::new_from_array(hash_array)));
 
-        hash_array[0] = 0b1000_0000;
-        hash = Hash::new_from_array(hash_array);
-        assert_eq!(0, ComptokenProof::leading_zeroes(&hash));
+        hash_array[0] = 0x0e;
+        hash_array[1] = 0xad;
+        hash_array[2] = 0xd7;
+        assert!(ComptokenProof::is_hash_lower_than_target(&Hash::new_from_array(hash_array)));
 
-        hash_array[0] = 0b0000_1000;
-        hash = Hash::new_from_array(hash_array);
-        assert_eq!(4, ComptokenProof::leading_zeroes(&hash));
+        hash_array[2] = 0xd8;
+        assert!(!ComptokenProof::is_hash_lower_than_target(&Hash::new_from_array(hash_array)));
+
+        hash_array[0] = 0xff;
+        assert!(!ComptokenProof::is_hash_lower_than_target(&Hash::new_from_array(hash_array)));
     }
 
     #[test]
     fn test_from_bytes() {
-        assert_eq!(ComptokenProof::from_bytes(&ZERO_PUBKEY, &[0; VERIFY_DATA_SIZE]).hash, [0; 32].into());
+        let valid_blockhashes = create_valid_blockhashes(Hash::new_from_array([1; 32]));
+        assert!(ComptokenProof::from_bytes(&[0; VERIFY_DATA_SIZE - 1], &valid_blockhashes).is_err());
+        assert!(ComptokenProof::from_bytes(&[0; VERIFY_DATA_SIZE + 1], &valid_blockhashes).is_err());
 
-        let recent_hash = Hash::new_from_array([1; 32]);
         let pubkey = Pubkey::new_from_array([2; PUBKEY_BYTES]);
-        let nonce: u64 = 0x03030303_03030303;
-        let mut v = Vec::<u8>::with_capacity(VERIFY_DATA_SIZE);
-        let mut hasher = Hasher::default();
-
-        hasher.hash(&pubkey.to_bytes());
-        v.extend(recent_hash.to_bytes());
-        v.extend(nonce.to_be_bytes());
-        hasher.hash(&v);
-        let hash = hasher.result();
-        v.extend(hash.to_bytes());
-
-        let bytes = v.try_into().unwrap();
-        let block_from_bytes = ComptokenProof::from_bytes(&pubkey, &bytes);
-        let block_from_data = create_arbitrary_block(&pubkey, recent_hash, nonce, hash);
-        assert_eq!(
-            block_from_bytes.recent_block_hash, block_from_data.recent_block_hash,
-            "recent_block_hashes are different"
-        );
-        assert_eq!(block_from_bytes.pubkey, block_from_data.pubkey, "pubkeys are different");
-        assert_eq!(block_from_bytes.nonce, block_from_data.nonce, "nonces are different");
-        assert_eq!(block_from_bytes.hash, block_from_data.hash, "hashes are different");
+        let mut data = [3_u8; VERIFY_DATA_SIZE];
+        data[0..32].copy_from_slice(&pubkey.to_bytes());
+
+        let proof = ComptokenProof::from_bytes(&data, &valid_blockhashes).expect("valid length");
+        assert_eq!(proof.pubkey, pubkey, "pubkeys are different");
+        // the hash is deterministic for the same data and valid blockhash
+        let same_proof = ComptokenProof::from_bytes(&data, &valid_blockhashes).expect("valid length");
+        assert_eq!(proof.hash, same_proof.hash, "hashes are different");
+        // but depends on the valid blockhash
+        let other_valid_blockhashes = create_valid_blockhashes(Hash::new_from_array([4; 32]));
+        let other_proof = ComptokenProof::from_bytes(&data, &other_valid_blockhashes).expect("valid length");
+        assert_ne!(proof.hash, other_proof.hash, "hashes are the same");
+    }
+
+    proptest! {
+        #[test]
+        fn prop_from_bytes_invalid_length(data in prop::collection::vec(any::<u8>(), 0..2 * VERIFY_DATA_SIZE)) {
+            prop_assume!(data.len() != VERIFY_DATA_SIZE);
+            let valid_blockhashes = create_valid_blockhashes(Hash::new_from_array([1; 32]));
+            prop_assert!(ComptokenProof::from_bytes(&data, &valid_blockhashes).is_err());
+        }
+
+        #[test]
+        fn prop_from_bytes(
+            data in prop::collection::vec(any::<u8>(), VERIFY_DATA_SIZE),
+            valid_blockhash in any::<[u8; 32]>(),
+            changed_byte in 0..VERIFY_DATA_SIZE,
+        ) {
+            let valid_blockhashes = create_valid_blockhashes(Hash::new_from_array(valid_blockhash));
+            let proof = ComptokenProof::from_bytes(&data, &valid_blockhashes).expect("valid length");
+            prop_assert_eq!(proof.pubkey.as_ref(), &data[0..PUBKEY_BYTES]);
+
+            // every byte of the submitted data is part of the hash
+            let mut changed_data = data.clone();
+            changed_data[changed_byte] ^= 1;
+            let changed_proof = ComptokenProof::from_bytes(&changed_data, &valid_blockhashes).expect("valid length");
+            prop_assert_ne!(proof.hash, changed_proof.hash);
+        }
     }
 }
@@ -1,3 +1,5 @@
+use spl_token_2022::solana_program::{entrypoint::MAX_PERMITTED_DATA_INCREASE, hash::HASH_BYTES};
+
 // TODO: This number deserves scrutiny and justification.
 pub const COMPTOKEN_DISTRIBUTION_MULTIPLIER: u64 = 146_000;
 
@@ -15,13 +17,39 @@ pub const END_GOAL_PERCENT_INCREASE: f64 = 0.00061;
 // seconds between earliest possible announcement and switchover point, currently 5 mins
 pub const ANNOUNCEMENT_INTERVAL: i64 = 60 * 5;
 
+// seeds of the program's PDAs. the bumps are found at runtime so that one build works for any program id
+pub const COMPTO_MINT_SEED: &[u8] = b"Comptoken Mint";
+pub const COMPTO_GLOBAL_DATA_ACCOUNT_SEED: &[u8] = b"Global Data";
+pub const COMPTO_INTEREST_BANK_ACCOUNT_SEED: &[u8] = b"Interest Bank";
+pub const COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_SEED: &[u8] = b"Verified Human UBI Bank";
+pub const COMPTO_FUTURE_UBI_BANK_ACCOUNT_SEED: &[u8] = b"Future UBI Bank";
+
 // MAGIC NUMBER: ensure this remains consistent with common.py
 pub const MINT_DECIMALS: u8 = 2;
 
-// the bytes a comptoken account takes up
-pub const COMPTOKEN_ACCOUNT_SPACE: u64 = 256; // TODO: get actual size
+// the token metadata the mint is created with
+pub const COMPTOKEN_NAME: &str = "Comptoken";
+pub const COMPTOKEN_SYMBOL: &str = "CPT";
+// TODO: point this at the off-chain metadata once it is hosted
+pub const COMPTOKEN_URI: &str = "";
+// the metadata the daily distribution event keeps current, once the admin has added these keys to the metadata
+pub const APR_METADATA_KEY: &str = "apr";
+pub const VERIFIED_HUMANS_METADATA_KEY: &str = "verified_humans";
 
 // the number of verified humans that will receive money from the future UBI account
 pub const FUTURE_UBI_VERIFIED_HUMANS: u64 = 1_000_000_000;
 
 pub const MINING_AMOUNT: u64 = 10000;
+
+// how often comptokens in the interest and verified human UBI banks that are owed to nobody are reabsorbed
+pub const DUST_SWEEP_INTERVAL_DAYS: i64 = 30;
+
+// the comptokens minted to whoever calls the daily distribution event, so that it gets called without us running it.
+// set to 0 to disable the reward
+pub const CRANK_REWARD: u64 = 100;
+
+// how many proofs a full user data account grows by when a proof is minted with a payer, unless the miner asks for a
+// different step
+pub const USER_DATA_GROWTH_STEP: u64 = 8;
+// the most proofs a user data account can grow by in one instruction
+pub const MAX_USER_DATA_GROWTH_STEP: u64 = (MAX_PERMITTED_DATA_INCREASE / HASH_BYTES) as u64;
@@ -0,0 +1,240 @@
+use bytemuck::{Pod, Zeroable};
+use spl_token_2022::{solana_program::msg, state::Account};
+
+use comptoken_utils::SEC_PER_DAY;
+
+use crate::{constants::*, global_data::daily_distribution_data::DailyDistributionValues};
+
+// the comptokens each bank owes to users that have not been paid out yet
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+pub struct BankLiabilities {
+    pub interest_bank: u64,
+    pub verified_human_ubi_bank: u64,
+    pub future_ubi_bank: u64,
+    pub last_dust_sweep_time: i64,
+}
+
+impl BankLiabilities {
+    // `today` is the start of the current day
+    pub(super) fn initialize(&mut self, today: i64) {
+        self.last_dust_sweep_time = today;
+    }
+
+    // the interest holders can claim for a day with `interest_rate` (e.g. 0.01 for 1%): the rate applied to everything
+    // they hold or are owed. this is less than the interest minted, which is calculated on the whole supply, because
+    // comptokens held by the banks belong to nobody, and the future UBI bank's interest is minted into it directly.
+    // rounded up so that what is recorded as owed is never less than what holders can claim
+    pub fn claimable_interest(&self, interest_rate: f64, supply: u64, bank_balances: u64) -> u64 {
+        let held_or_owed = supply.saturating_sub(bank_balances) + self.interest_bank + self.verified_human_ubi_bank;
+        (held_or_owed as f64 * interest_rate).ceil() as u64
+    }
+
+    // records the day's distribution as owed, and periodically reabsorbs dust (comptokens held by a bank that are
+    // owed to nobody) by minting that much less into the bank. returns the amount of dust swept.
+    pub(super) fn daily_distribution(
+        &mut self, today: i64, distribution: &mut DailyDistributionValues, interest_owed: u64,
+        verified_human_ubi_owed: u64, unpaid_interest_bank: &Account, unpaid_verified_human_ubi_bank: &Account,
+    ) -> u64 {
+        let future_ubi_owed = distribution.future_ubi_distribution;
+
+        let mut dust = 0;
+        if self.is_dust_sweep_due(today) {
+            let interest_dust = std::cmp::min(
+                surplus(unpaid_interest_bank.amount, self.interest_bank),
+                distribution.interest_distribution,
+            );
+            let ubi_dust = std::cmp::min(
+                surplus(unpaid_verified_human_ubi_bank.amount, self.verified_human_ubi_bank),
+                distribution.ubi_for_verified_humans,
+            );
+            msg!("Swept interest dust: {}", interest_dust);
+            msg!("Swept verified human UBI dust: {}", ubi_dust);
+            distribution.interest_distribution -= interest_dust;
+            distribution.ubi_for_verified_humans -= ubi_dust;
+            self.last_dust_sweep_time = today;
+            dust = interest_dust + ubi_dust;
+        }
+
+        // interest on comptokens owed to nobody is minted, but not owed, so it becomes dust
+        self.interest_bank += interest_owed;
+        // ubi that does not divide evenly between verified humans is owed to nobody, and becomes dust
+        self.verified_human_ubi_bank += verified_human_ubi_owed;
+        self.future_ubi_bank += future_ubi_owed;
+        dust
+    }
+
+    // interest is calculated per user with rounding, so payouts can slightly exceed what was recorded as owed
+    pub fn pay_interest(&mut self, amount: u64) {
+        self.interest_bank = self.interest_bank.saturating_sub(amount);
+    }
+
+    pub fn pay_verified_human_ubi(&mut self, amount: u64) {
+        self.verified_human_ubi_bank = self.verified_human_ubi_bank.saturating_sub(amount);
+    }
+
+    pub fn pay_future_ubi(&mut self, amount: u64) {
+        self.future_ubi_bank = self.future_ubi_bank.saturating_sub(amount);
+    }
+
+    pub fn is_dust_sweep_due(&self, today: i64) -> bool {
+        today >= self.last_dust_sweep_time + DUST_SWEEP_INTERVAL_DAYS * SEC_PER_DAY
+    }
+}
+
+// a positive surplus means the bank holds more than it owes, a negative one (a deficit) means it is insolvent
+pub fn surplus_or_deficit(balance: u64, liabilities: u64) -> i64 {
+    (balance as i128 - liabilities as i128).clamp(i64::MIN as i128, i64::MAX as i128) as i64
+}
+
+fn surplus(balance: u64, liabilities: u64) -> u64 {
+    balance.saturating_sub(liabilities)
+}
+
+#[cfg(test)]
+mod test {
+    use spl_token_2022::solana_program::pubkey::Pubkey;
+
+    use super::*;
+    use crate::{get_current_time, normalize_time};
+
+    // the start of the current day, with days starting at midnight UTC+0
+    fn today() -> i64 {
+        normalize_time(get_current_time(), 0)
+    }
+
+    fn bank(amount: u64) -> Account {
+        Account { amount, owner: Pubkey::new_unique(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_daily_distribution_records_liabilities() {
+        let mut liabilities = BankLiabilities {
+            interest_bank: 0,
+            verified_human_ubi_bank: 0,
+            future_ubi_bank: 0,
+            last_dust_sweep_time: 0,
+        };
+        liabilities.initialize(today());
+
+        let mut distribution = DailyDistributionValues {
+            high_water_mark_increase: 0,
+            interest_distribution: 100,
+            ubi_for_verified_humans: 10,
+            future_ubi_distribution: 90,
+            crank_reward: 0,
+        };
+        let dust = liabilities.daily_distribution(today(), &mut distribution, 100, 9, &bank(0), &bank(0));
+
+        assert_eq!(dust, 0);
+        assert_eq!(distribution.interest_distribution, 100);
+        assert_eq!(distribution.ubi_for_verified_humans, 10);
+        assert_eq!(liabilities.interest_bank, 100);
+        assert_eq!(liabilities.verified_human_ubi_bank, 9);
+        assert_eq!(liabilities.future_ubi_bank, 90);
+
+        liabilities.pay_interest(101);
+        liabilities.pay_verified_human_ubi(4);
+        liabilities.pay_future_ubi(90);
+        assert_eq!(liabilities.interest_bank, 0);
+        assert_eq!(liabilities.verified_human_ubi_bank, 5);
+        assert_eq!(liabilities.future_ubi_bank, 0);
+    }
+
+    #[test]
+    fn test_daily_distribution_sweeps_dust() {
+        let mut liabilities = BankLiabilities {
+            interest_bank: 50,
+            verified_human_ubi_bank: 9,
+            future_ubi_bank: 0,
+            last_dust_sweep_time: today() - DUST_SWEEP_INTERVAL_DAYS * SEC_PER_DAY,
+        };
+        assert!(liabilities.is_dust_sweep_due(today()));
+
+        let mut distribution = DailyDistributionValues {
+            high_water_mark_increase: 0,
+            interest_distribution: 100,
+            ubi_for_verified_humans: 3,
+            future_ubi_distribution: 0,
+            crank_reward: 0,
+        };
+        let dust = liabilities.daily_distribution(today(), &mut distribution, 100, 3, &bank(55), &bank(14));
+
+        assert_eq!(dust, 5 + 3);
+        assert_eq!(distribution.interest_distribution, 95);
+        assert_eq!(distribution.ubi_for_verified_humans, 0);
+        assert_eq!(liabilities.interest_bank, 150);
+        assert_eq!(liabilities.verified_human_ubi_bank, 12);
+        assert!(!liabilities.is_dust_sweep_due(today()));
+        // the banks still cover what they owe after minting
+        assert!(surplus_or_deficit(55 + 95, liabilities.interest_bank) >= 0);
+        assert!(surplus_or_deficit(14, liabilities.verified_human_ubi_bank) >= 0);
+    }
+
+    #[test]
+    fn test_claimable_interest() {
+        let liabilities = BankLiabilities {
+            interest_bank: 100,
+            verified_human_ubi_bank: 50,
+            future_ubi_bank: 1_000,
+            last_dust_sweep_time: 0,
+        };
+        // holders hold 10_000 - 2_000 and are owed 150 by the banks
+        assert_eq!(liabilities.claimable_interest(0.01, 10_000, 2_000), 82);
+        assert_eq!(liabilities.claimable_interest(0.0, 10_000, 2_000), 0);
+        // rounded up
+        assert_eq!(liabilities.claimable_interest(0.0001, 10_000, 2_000), 1);
+    }
+
+    #[test]
+    fn test_daily_distribution_sweeps_interest_on_unowed_comptokens() {
+        // day 1: holders hold 9_000 of a 10_000 supply, and the future UBI bank holds the other 1_000
+        let mut liabilities = BankLiabilities {
+            interest_bank: 0,
+            verified_human_ubi_bank: 0,
+            future_ubi_bank: 1_000,
+            last_dust_sweep_time: today(),
+        };
+        let mut distribution = DailyDistributionValues {
+            high_water_mark_increase: 0,
+            interest_distribution: 100,
+            ubi_for_verified_humans: 0,
+            future_ubi_distribution: 0,
+            crank_reward: 0,
+        };
+        let interest_owed = liabilities.claimable_interest(0.01, 10_000, 1_000);
+        assert!(!liabilities.is_dust_sweep_due(today()));
+        assert_eq!(liabilities.daily_distribution(today(), &mut distribution, interest_owed, 0, &bank(0), &bank(0)), 0);
+        // all 100 is minted into the interest bank, but holders can only claim the interest on their 9_000
+        assert_eq!(liabilities.interest_bank, 90);
+        let interest_bank_balance = distribution.interest_distribution;
+        assert_eq!(interest_bank_balance, 100);
+        assert_eq!(surplus_or_deficit(interest_bank_balance, liabilities.interest_bank), 10);
+
+        // day 2, once the sweep is due: the interest bank's 10 surplus is minted that much less
+        liabilities.last_dust_sweep_time -= DUST_SWEEP_INTERVAL_DAYS * SEC_PER_DAY;
+        let mut distribution = DailyDistributionValues {
+            high_water_mark_increase: 0,
+            interest_distribution: 100,
+            ubi_for_verified_humans: 0,
+            future_ubi_distribution: 0,
+            crank_reward: 0,
+        };
+        let interest_owed = liabilities.claimable_interest(0.01, 10_100, 1_000 + interest_bank_balance);
+        assert_eq!(interest_owed, 91);
+        let dust =
+            liabilities.daily_distribution(today(), &mut distribution, interest_owed, 0, &bank(interest_bank_balance), &bank(0));
+        assert_eq!(dust, 10);
+        assert_eq!(distribution.interest_distribution, 90);
+        assert_eq!(liabilities.interest_bank, 90 + 91);
+        // the bank still covers what holders can claim
+        assert!(surplus_or_deficit(interest_bank_balance + 90, liabilities.interest_bank) >= 0);
+    }
+
+    #[test]
+    fn test_surplus_or_deficit() {
+        assert_eq!(surplus_or_deficit(10, 4), 6);
+        assert_eq!(surplus_or_deficit(4, 10), -6);
+        assert_eq!(surplus_or_deficit(u64::MAX, 0), i64::MAX);
+    }
+}
@@ -1,52 +1,252 @@
+pub mod bank_liabilities;
 pub mod daily_distribution_data;
 pub mod valid_blockhashes;
 
-use spl_token_2022::state::{Account, Mint};
+use bytemuck::{Pod, Zeroable};
+use spl_token_2022::{
+    solana_program::{msg, program_error::ProgramError, pubkey::Pubkey},
+    state::{Account, Mint},
+};
 
-use crate::VerifiedAccountInfo;
+use comptoken_utils::versioned_account::{
+    account_size, from_account_data_mut, from_body_mut, migrate_legacy_account_data, MigratableAccount,
+    VersionedAccount,
+};
+
+use crate::{constants::CRANK_REWARD, get_current_time, normalize_time, VerifiedAccountInfo};
+use bank_liabilities::BankLiabilities;
 use daily_distribution_data::{DailyDistributionData, DailyDistributionValues};
 use valid_blockhashes::ValidBlockhashes;
 
 #[repr(C)]
-#[derive(Debug)]
-// MAGIC NUMBER: Changes to the size of this struct need to be reflected in test_client.js
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+// CHANGES TO THIS STRUCT NEED A NEW `VERSION` AND TO BE REFLECTED IN accounts.js
 pub struct GlobalData {
+    // chosen at initialization so that the program isn't built for a specific mint
+    pub comptoken_mint: Pubkey,
+    pub transfer_hook_program: Pubkey,
+    pub bumps: Bumps,
     pub valid_blockhashes: ValidBlockhashes,
     pub daily_distribution_data: DailyDistributionData,
+    pub bank_liabilities: BankLiabilities,
+    // may update the mint's metadata. `Pubkey::default()` until the upgrade authority sets one
+    pub admin: Pubkey,
+    // seconds after midnight UTC+0 at which each day (and so the daily distribution) starts. chosen at initialization
+    pub day_boundary_offset: i64,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
+// the canonical bumps of the PDAs, found once at initialization so that later instructions don't search for them
+pub struct Bumps {
+    pub global_data: u8,
+    pub interest_bank: u8,
+    pub verified_human_ubi_bank: u8,
+    pub future_ubi_bank: u8,
+    pub interest_bank_data: u8,
+    pub verified_human_ubi_bank_data: u8,
+    pub future_ubi_bank_data: u8,
+    pub extra_account_metas: u8,
+}
+
+// the global data from before the account header only held the valid blockhashes and the daily distribution data, with
+// the same layouts. no later layout has the same size, so it is recognised by its size
+pub const LEGACY_GLOBAL_DATA_SIZE: usize =
+    std::mem::size_of::<ValidBlockhashes>() + std::mem::size_of::<DailyDistributionData>();
+// where the legacy data goes in the body: after the mint, the transfer hook program and the bumps
+const LEGACY_GLOBAL_DATA_OFFSET: usize = 2 * std::mem::size_of::<Pubkey>() + std::mem::size_of::<Bumps>();
+
+impl VersionedAccount for GlobalData {
+    const DISCRIMINATOR: [u8; 8] = *b"GlblData";
+    // 2: added the admin
+    // 3: added the day boundary offset
+    const VERSION: u8 = 3;
+}
+
+// a new version adds the step from the version before it here
+impl MigratableAccount for GlobalData {
+    fn migrated_size(_version: u8, _data: &[u8]) -> usize {
+        account_size::<Self>()
+    }
+
+    fn migrate_body(version: u8, _body: &mut [u8]) -> Result<(), ProgramError> {
+        match version {
+            // the admin is appended and left zeroed, since anyone may migrate. the upgrade authority then sets it
+            // the day boundary offset is appended after it, and left zeroed, which is the midnight UTC+0 older
+            // versions used
+            1 | 2 => Ok(()),
+            _ => {
+                msg!("no migration from global data version {}", version);
+                Err(ProgramError::InvalidAccountData)
+            }
+        }
+    }
 }
 
 impl GlobalData {
-    pub fn initialize(&mut self, slot_hash_account: &VerifiedAccountInfo) {
-        self.valid_blockhashes.initialize(slot_hash_account);
-        self.daily_distribution_data.initialize();
+    pub fn initialize(
+        &mut self, comptoken_mint: &Pubkey, transfer_hook_program: &Pubkey, bumps: Bumps, admin: &Pubkey,
+        day_boundary_offset: i64, slot_hash_account: &VerifiedAccountInfo,
+    ) {
+        self.comptoken_mint = *comptoken_mint;
+        self.transfer_hook_program = *transfer_hook_program;
+        self.bumps = bumps;
+        self.admin = *admin;
+        self.day_boundary_offset = day_boundary_offset;
+        self.valid_blockhashes.initialize(slot_hash_account, day_boundary_offset);
+        self.daily_distribution_data.initialize(self.today());
+        self.bank_liabilities.initialize(self.today());
+    }
+
+    // for legacy global data, once it has grown to the current size. the legacy program didn't track what its banks owed,
+    // so everything in them is treated as owed, and it has no admin until the upgrade authority sets one. its days
+    // started at midnight UTC+0, which is the zeroed day boundary offset
+    pub fn migrate_legacy<'a>(
+        data: &'a mut [u8], comptoken_mint: &Pubkey, transfer_hook_program: &Pubkey, bumps: Bumps,
+        unpaid_interest_bank: &Account, unpaid_verified_human_ubi_bank: &Account, unpaid_future_ubi_bank: &Account,
+    ) -> Result<&'a mut Self, ProgramError> {
+        let body = migrate_legacy_account_data::<Self>(data, LEGACY_GLOBAL_DATA_SIZE, LEGACY_GLOBAL_DATA_OFFSET)?;
+        let global_data: &mut Self = from_body_mut(body)?;
+        global_data.comptoken_mint = *comptoken_mint;
+        global_data.transfer_hook_program = *transfer_hook_program;
+        global_data.bumps = bumps;
+        global_data.bank_liabilities.initialize(global_data.today());
+        global_data.bank_liabilities.interest_bank = unpaid_interest_bank.amount;
+        global_data.bank_liabilities.verified_human_ubi_bank = unpaid_verified_human_ubi_bank.amount;
+        global_data.bank_liabilities.future_ubi_bank = unpaid_future_ubi_bank.amount;
+        Ok(global_data)
+    }
+
+    pub fn is_legacy(data: &[u8]) -> bool {
+        data.len() == LEGACY_GLOBAL_DATA_SIZE
+    }
+
+    // the start of the current day
+    pub fn today(&self) -> i64 {
+        normalize_time(get_current_time(), self.day_boundary_offset)
     }
 
     pub fn daily_distribution_event(
-        &mut self, mint: &Mint, unpaid_future_ubi_bank: &Account, slothashes_account: &VerifiedAccountInfo,
+        &mut self, mint: &Mint, unpaid_interest_bank: &Account, unpaid_verified_human_ubi_bank: &Account,
+        unpaid_future_ubi_bank: &Account, slothashes_account: &VerifiedAccountInfo, reward_caller: bool,
     ) -> DailyDistributionValues {
-        self.valid_blockhashes.update(slothashes_account);
-        self.daily_distribution_data.daily_distribution(mint, unpaid_future_ubi_bank)
+        let today = self.today();
+        self.valid_blockhashes.update(slothashes_account, self.day_boundary_offset);
+        let mut distribution = self.daily_distribution_data.daily_distribution(today, mint, unpaid_future_ubi_bank);
+        let bank_balances =
+            unpaid_interest_bank.amount + unpaid_verified_human_ubi_bank.amount + unpaid_future_ubi_bank.amount;
+        let interest_owed = self.bank_liabilities.claimable_interest(
+            self.daily_distribution_data.todays_interest_rate() - 1.,
+            mint.supply,
+            bank_balances,
+        );
+        let verified_human_ubi_owed =
+            self.daily_distribution_data.todays_ubi() * self.daily_distribution_data.verified_humans;
+        let dust = self.bank_liabilities.daily_distribution(
+            today,
+            &mut distribution,
+            interest_owed,
+            verified_human_ubi_owed,
+            unpaid_interest_bank,
+            unpaid_verified_human_ubi_bank,
+        );
+        // swept dust is not minted, so it is not part of the supply
+        self.daily_distribution_data.yesterday_supply -= dust;
+        if reward_caller {
+            distribution.crank_reward = CRANK_REWARD;
+            self.daily_distribution_data.yesterday_supply += CRANK_REWARD;
+        }
+        distribution
     }
 }
 
 impl<'a> From<&VerifiedAccountInfo<'a>> for &'a mut GlobalData {
-    fn from(account: &VerifiedAccountInfo) -> Self {
-        let mut data = account.try_borrow_mut_data().unwrap();
-        let data = data.as_mut();
+    fn from(account: &VerifiedAccountInfo<'a>) -> Self {
+        account.data_unchecked().into()
+    }
+}
 
-        data.into()
+impl<'a> From<&'a mut [u8]> for &'a mut GlobalData {
+    fn from(data: &'a mut [u8]) -> Self {
+        from_account_data_mut(data).unwrap()
     }
 }
 
-impl From<&mut [u8]> for &mut GlobalData {
-    fn from(value: &mut [u8]) -> Self {
-        assert!(
-            value.len() >= std::mem::size_of::<GlobalData>(),
-            "\n    note: left = `{}`\n    note: right = `{}`",
-            value.len(),
-            std::mem::size_of::<GlobalData>()
-        );
+#[cfg(test)]
+mod test {
+    use spl_token_2022::solana_program::hash::Hash;
+
+    use comptoken_utils::versioned_account::{migrate_account_data, AccountHeader, ACCOUNT_HEADER_SIZE};
+
+    use super::*;
+    use daily_distribution_data::{HistoricDistribution, HISTORY_SIZE};
+
+    fn bank(amount: u64) -> Account {
+        Account { amount, owner: Pubkey::new_unique(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_migrate_legacy() {
+        // the size of the deployed legacy global data
+        assert_eq!(LEGACY_GLOBAL_DATA_SIZE, 5960);
+
+        let mut expected = GlobalData::zeroed();
+        expected.valid_blockhashes.valid_blockhash = Hash::new_from_array([1; 32]);
+        expected.valid_blockhashes.valid_blockhash_time = 2;
+        expected.daily_distribution_data.yesterday_supply = 3;
+        expected.daily_distribution_data.historic_distributions[HISTORY_SIZE - 1] =
+            HistoricDistribution { interest_rate: 1.5, ubi: 4 };
+        let mut data = vec![0_u64; account_size::<GlobalData>() / 8];
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        data[..std::mem::size_of::<ValidBlockhashes>()]
+            .copy_from_slice(bytemuck::bytes_of(&expected.valid_blockhashes));
+        data[std::mem::size_of::<ValidBlockhashes>()..LEGACY_GLOBAL_DATA_SIZE]
+            .copy_from_slice(bytemuck::bytes_of(&expected.daily_distribution_data));
+
+        expected.comptoken_mint = Pubkey::new_unique();
+        expected.transfer_hook_program = Pubkey::new_unique();
+        expected.bumps.global_data = 255;
+        expected.bumps.extra_account_metas = 254;
+        expected.bank_liabilities.initialize(normalize_time(get_current_time(), 0));
+        expected.bank_liabilities.interest_bank = 5;
+        expected.bank_liabilities.verified_human_ubi_bank = 6;
+        expected.bank_liabilities.future_ubi_bank = 7;
+
+        let global_data = GlobalData::migrate_legacy(
+            data,
+            &expected.comptoken_mint,
+            &expected.transfer_hook_program,
+            expected.bumps,
+            &bank(5),
+            &bank(6),
+            &bank(7),
+        )
+        .unwrap();
+        assert_eq!(bytemuck::bytes_of(global_data), bytemuck::bytes_of(&expected));
+        assert_eq!(global_data.admin, Pubkey::default());
+        assert_eq!(global_data.day_boundary_offset, 0);
+    }
+
+    #[test]
+    fn test_migrate_version_2() {
+        let mut expected = GlobalData::zeroed();
+        expected.comptoken_mint = Pubkey::new_unique();
+        expected.daily_distribution_data.yesterday_supply = 3;
+        expected.admin = Pubkey::new_unique();
+        let mut data = vec![0_u64; account_size::<GlobalData>() / 8];
+        let data: &mut [u8] = bytemuck::cast_slice_mut(&mut data);
+        // version 2 is the current layout without the day boundary offset at the end
+        let version_2_size = account_size::<GlobalData>() - std::mem::size_of::<i64>();
+        let mut header = AccountHeader::new::<GlobalData>();
+        header.version = 2;
+        data[..ACCOUNT_HEADER_SIZE].copy_from_slice(bytemuck::bytes_of(&header));
+        data[ACCOUNT_HEADER_SIZE..version_2_size]
+            .copy_from_slice(&bytemuck::bytes_of(&expected)[..version_2_size - ACCOUNT_HEADER_SIZE]);
 
-        unsafe { &mut *(value as *mut _ as *mut GlobalData) }
+        assert_eq!(GlobalData::migrated_size(2, &data[..version_2_size]), data.len());
+        migrate_account_data::<GlobalData>(data, 2).unwrap();
+        let global_data: &mut GlobalData = from_account_data_mut(data).unwrap();
+        assert_eq!(bytemuck::bytes_of(global_data), bytemuck::bytes_of(&expected));
+        assert_eq!(global_data.day_boundary_offset, 0, "older versions' days started at midnight UTC+0");
     }
 }
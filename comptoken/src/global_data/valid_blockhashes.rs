@@ -1,3 +1,4 @@
+use bytemuck::{Pod, Zeroable};
 use spl_token_2022::solana_program::{hash::Hash, slot_hashes::SlotHash};
 
 use comptoken_utils::{get_current_time, normalize_time, SEC_PER_DAY};
@@ -5,7 +6,7 @@ use comptoken_utils::{get_current_time, normalize_time, SEC_PER_DAY};
 use crate::{constants::*, VerifiedAccountInfo};
 
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Pod, Zeroable)]
 pub struct ValidBlockhashes {
     pub announced_blockhash: Hash,
     pub announced_blockhash_time: i64,
@@ -14,20 +15,20 @@ pub struct ValidBlockhashes {
 }
 
 impl ValidBlockhashes {
-    pub(super) fn initialize(&mut self, slothashes_account: &VerifiedAccountInfo) {
-        self.update(slothashes_account);
+    pub(super) fn initialize(&mut self, slothashes_account: &VerifiedAccountInfo, day_boundary_offset: i64) {
+        self.update(slothashes_account, day_boundary_offset);
     }
 
-    pub fn update(&mut self, slot_hash_account: &VerifiedAccountInfo) {
+    pub fn update(&mut self, slot_hash_account: &VerifiedAccountInfo, day_boundary_offset: i64) {
         if self.is_announced_blockhash_stale() {
             self.announced_blockhash = get_most_recent_blockhash(slot_hash_account);
             // This is necessary for the case where a day's update has been "skipped"
             self.announced_blockhash_time =
-                normalize_time(get_current_time() + ANNOUNCEMENT_INTERVAL) - ANNOUNCEMENT_INTERVAL;
+                normalize_time(get_current_time() + ANNOUNCEMENT_INTERVAL, day_boundary_offset) - ANNOUNCEMENT_INTERVAL;
         }
         if self.is_valid_blockhash_stale() {
             self.valid_blockhash = self.announced_blockhash;
-            self.valid_blockhash_time = normalize_time(get_current_time());
+            self.valid_blockhash_time = normalize_time(get_current_time(), day_boundary_offset);
         }
     }
 
@@ -0,0 +1,81 @@
+use spl_token_2022::solana_program::program_error::ProgramError;
+use spl_token_metadata_interface::state::Field;
+
+use crate::{
+    constants::{APR_METADATA_KEY, VERIFIED_HUMANS_METADATA_KEY},
+    global_data::daily_distribution_data::DailyDistributionData,
+};
+
+// instruction data: [field: u8] ([key length: u8] [key]) [value], where the key is only present for a custom field
+// (field 3). 0, 1 and 2 are the name, symbol and uri
+pub fn read_field_update(instruction_data: &[u8]) -> Result<(Field, String), ProgramError> {
+    let (&field, rest) = instruction_data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    let (field, value) = match field {
+        0 => (Field::Name, rest),
+        1 => (Field::Symbol, rest),
+        2 => (Field::Uri, rest),
+        3 => {
+            let (key, value) = read_key(rest)?;
+            (Field::Key(key), value)
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    };
+    Ok((field, read_string(value)?))
+}
+
+// instruction data: [key]
+pub fn read_removed_key(instruction_data: &[u8]) -> Result<String, ProgramError> {
+    read_string(instruction_data)
+}
+
+// the key, and the data after it
+fn read_key(data: &[u8]) -> Result<(String, &[u8]), ProgramError> {
+    let (&length, rest) = data.split_first().ok_or(ProgramError::InvalidInstructionData)?;
+    if rest.len() < length as usize {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (key, rest) = rest.split_at(length as usize);
+    Ok((read_string(key)?, rest))
+}
+
+fn read_string(data: &[u8]) -> Result<String, ProgramError> {
+    String::from_utf8(data.to_vec()).map_err(|_| ProgramError::InvalidInstructionData)
+}
+
+// the values of the automatically refreshed metadata after the most recent distribution
+pub fn refreshed_metadata(daily_distribution_data: &DailyDistributionData) -> [(&'static str, String); 2] {
+    [
+        (APR_METADATA_KEY, format_apr(daily_distribution_data.todays_interest_rate())),
+        (VERIFIED_HUMANS_METADATA_KEY, daily_distribution_data.verified_humans.to_string()),
+    ]
+}
+
+// the simple (not compounded) yearly rate of a daily `interest_rate`, as a percentage. a day without a distribution
+// has no interest
+fn format_apr(interest_rate: f64) -> String {
+    format!("{:.2}%", (interest_rate - 1.).max(0.) * 365. * 100.)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read_field_update() {
+        assert_eq!(read_field_update(b"\x00Comptoken"), Ok((Field::Name, "Comptoken".to_string())));
+        assert_eq!(read_field_update(b"\x02"), Ok((Field::Uri, String::new())));
+        assert_eq!(read_field_update(b"\x03\x03apr1.00%"), Ok((Field::Key("apr".to_string()), "1.00%".to_string())));
+        assert_eq!(read_field_update(b""), Err(ProgramError::InvalidInstructionData));
+        assert_eq!(read_field_update(b"\x04value"), Err(ProgramError::InvalidInstructionData));
+        assert_eq!(read_field_update(b"\x03\x04apr"), Err(ProgramError::InvalidInstructionData));
+        assert_eq!(read_field_update(b"\x01\xff"), Err(ProgramError::InvalidInstructionData));
+    }
+
+    #[test]
+    fn test_format_apr() {
+        assert_eq!(format_apr(1.), "0.00%");
+        assert_eq!(format_apr(1.001), "36.50%");
+        // the history starts zeroed
+        assert_eq!(format_apr(0.), "0.00%");
+    }
+}
@@ -1,89 +1,178 @@
-use solana_program::msg;
 use spl_token_2022::{
     extension::StateWithExtensions,
     solana_program::{
         account_info::{next_account_info, AccountInfo},
+        bpf_loader_upgradeable::{self, UpgradeableLoaderState},
         program_error::ProgramError,
+        program_option::COption,
         pubkey::Pubkey,
     },
-    state::Account,
+    state::{Account, Mint},
 };
 
-use crate::generated::{
-    COMPTOKEN_MINT_ADDRESS, COMPTO_FUTURE_UBI_BANK_ACCOUNT_SEEDS, COMPTO_GLOBAL_DATA_ACCOUNT_SEEDS,
-    COMPTO_INTEREST_BANK_ACCOUNT_SEEDS, COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_SEEDS, TRANSFER_HOOK_ID,
+use crate::{
+    constants::{
+        COMPTO_FUTURE_UBI_BANK_ACCOUNT_SEED, COMPTO_GLOBAL_DATA_ACCOUNT_SEED, COMPTO_INTEREST_BANK_ACCOUNT_SEED,
+        COMPTO_MINT_SEED, COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_SEED,
+    },
+    global_data::{Bumps, GlobalData},
 };
 
 pub use comptoken_utils::verify_accounts::VerifiedAccountInfo;
 
+use comptoken_utils::{
+    distribution_record::DISTRIBUTION_RECORD_SEED,
+    exempt_accounts::{ExemptAccountsFields, EXEMPT_ACCOUNTS_SEED},
+    user_data::UserDataFields,
+};
+
 pub fn verify_payer_account<'a>(account: &AccountInfo<'a>) -> VerifiedAccountInfo<'a> {
     VerifiedAccountInfo::verify_account_signer_or_writable(account, true, true)
 }
 
-pub fn verify_comptoken_mint<'a>(account: &AccountInfo<'a>, needs_writable: bool) -> VerifiedAccountInfo<'a> {
-    VerifiedAccountInfo::verify_specific_address(account, &COMPTOKEN_MINT_ADDRESS, false, needs_writable)
+// a program initialized before the admin was recorded has none
+pub fn verify_admin_account<'a>(
+    account: &AccountInfo<'a>, admin: &Pubkey, needs_writable: bool,
+) -> VerifiedAccountInfo<'a> {
+    assert_ne!(*admin, Pubkey::default(), "the comptoken program has no admin");
+    assert_eq!(account.key, admin, "only the admin can do this");
+    VerifiedAccountInfo::verify_account_signer_or_writable(account, true, needs_writable)
 }
 
-pub fn verify_global_data_account<'a>(
-    account: &AccountInfo<'a>, program_id: &Pubkey, needs_writable: bool,
+// whoever may upgrade the program may also choose its admin. an immutable program has no upgrade authority
+pub fn verify_upgrade_authority_account<'a>(
+    account: &AccountInfo<'a>, upgrade_authority: Option<Pubkey>, needs_writable: bool,
 ) -> VerifiedAccountInfo<'a> {
-    VerifiedAccountInfo::verify_pda_with_bump(
-        account,
-        program_id,
-        COMPTO_GLOBAL_DATA_ACCOUNT_SEEDS,
-        false,
-        needs_writable,
-    )
+    let upgrade_authority = upgrade_authority.expect("the comptoken program is immutable");
+    assert_eq!(*account.key, upgrade_authority, "only the upgrade authority can do this");
+    VerifiedAccountInfo::verify_account_signer_or_writable(account, true, needs_writable)
 }
 
-pub fn verify_interest_bank_account<'a>(
-    account: &AccountInfo<'a>, program_id: &Pubkey, needs_writable: bool,
+// the program data account the upgradeable loader keeps for the comptoken program, which knows its upgrade authority
+pub fn verify_comptoken_program_data_account<'a>(
+    account: &AccountInfo<'a>, program_id: &Pubkey,
+) -> (VerifiedAccountInfo<'a>, Option<Pubkey>) {
+    let program_data_address = bpf_loader_upgradeable::get_program_data_address(program_id);
+    let program_data = VerifiedAccountInfo::verify_specific_address(account, &program_data_address, false, false);
+    assert_eq!(*account.owner, bpf_loader_upgradeable::ID);
+    let data = account.try_borrow_data().unwrap();
+    (program_data, read_upgrade_authority(&data))
+}
+
+// MAGIC NUMBERS: the serialized layout of `UpgradeableLoaderState::ProgramData`, i.e.
+// a u32 tag (3), the u64 slot of the last deployment and an `Option<Pubkey>` (a u8 tag and the key)
+fn read_upgrade_authority(data: &[u8]) -> Option<Pubkey> {
+    assert!(data.len() >= UpgradeableLoaderState::size_of_programdata_metadata(), "program data too small");
+    assert_eq!(data[0..4], 3u32.to_le_bytes(), "not a program data account");
+    (data[12] == 1).then(|| Pubkey::new_from_array(data[13..45].try_into().unwrap()))
+}
+
+pub fn verify_comptoken_mint<'a>(
+    account: &AccountInfo<'a>, comptoken_mint: &Pubkey, needs_writable: bool,
 ) -> VerifiedAccountInfo<'a> {
-    VerifiedAccountInfo::verify_pda_with_bump(
-        account,
-        program_id,
-        COMPTO_INTEREST_BANK_ACCOUNT_SEEDS,
-        false,
-        needs_writable,
-    )
+    VerifiedAccountInfo::verify_specific_address(account, comptoken_mint, false, needs_writable)
 }
 
-pub fn verify_verified_human_ubi_bank_account<'a>(
-    account: &AccountInfo<'a>, program_id: &Pubkey, needs_writable: bool,
+// legacy global data doesn't know the mint, which is recognised by the global data being its mint authority instead
+pub fn verify_legacy_comptoken_mint<'a>(
+    account: &AccountInfo<'a>, global_data: &VerifiedAccountInfo<'a>,
 ) -> VerifiedAccountInfo<'a> {
-    VerifiedAccountInfo::verify_pda_with_bump(
+    assert_eq!(*account.owner, spl_token_2022::ID);
+    {
+        let data = account.try_borrow_data().unwrap();
+        let mint = StateWithExtensions::<Mint>::unpack(&data).unwrap().base;
+        assert_eq!(mint.mint_authority, COption::Some(*global_data.key), "the global data must mint the comptokens");
+    }
+    VerifiedAccountInfo::verify_account_signer_or_writable(account, false, false)
+}
+
+// the global data doesn't know the mint until it is initialized, which creates the mint as a PDA of the program
+pub fn verify_uninitialized_comptoken_mint<'a>(
+    account: &AccountInfo<'a>, program_id: &Pubkey, needs_writable: bool,
+) -> (VerifiedAccountInfo<'a>, u8) {
+    assert!(account.data_is_empty(), "the comptoken mint is already created");
+    VerifiedAccountInfo::verify_pda(account, program_id, &[COMPTO_MINT_SEED], false, needs_writable)
+}
+
+// the bump is only searched for when it isn't known yet (i.e. when the account is being created)
+fn verify_pda_with_known_bump<'a>(
+    account: &AccountInfo<'a>, program_id: &Pubkey, seeds: &[&[u8]], bump: Option<u8>, needs_signer: bool,
+    needs_writable: bool,
+) -> (VerifiedAccountInfo<'a>, u8) {
+    match bump {
+        Some(bump) => {
+            let bump_seed = [bump];
+            let mut seeds = seeds.to_vec();
+            seeds.push(&bump_seed);
+            (VerifiedAccountInfo::verify_pda_with_bump(account, program_id, &seeds, needs_signer, needs_writable), bump)
+        }
+        None => VerifiedAccountInfo::verify_pda(account, program_id, seeds, needs_signer, needs_writable),
+    }
+}
+
+pub fn verify_global_data_account<'a>(
+    account: &AccountInfo<'a>, program_id: &Pubkey, is_created: bool, needs_writable: bool,
+) -> (VerifiedAccountInfo<'a>, u8) {
+    // once created, the global data knows its own bump
+    let bump = is_created.then(|| {
+        assert_eq!(account.owner, program_id);
+        let mut data = account.try_borrow_mut_data().unwrap();
+        let global_data: &mut GlobalData = data.as_mut().into();
+        global_data.bumps.global_data
+    });
+    verify_pda_with_known_bump(account, program_id, &[COMPTO_GLOBAL_DATA_ACCOUNT_SEED], bump, false, needs_writable)
+}
+
+pub fn verify_interest_bank_account<'a>(
+    account: &AccountInfo<'a>, program_id: &Pubkey, bump: Option<u8>, needs_writable: bool,
+) -> (VerifiedAccountInfo<'a>, u8) {
+    verify_pda_with_known_bump(account, program_id, &[COMPTO_INTEREST_BANK_ACCOUNT_SEED], bump, false, needs_writable)
+}
+
+pub fn verify_verified_human_ubi_bank_account<'a>(
+    account: &AccountInfo<'a>, program_id: &Pubkey, bump: Option<u8>, needs_writable: bool,
+) -> (VerifiedAccountInfo<'a>, u8) {
+    verify_pda_with_known_bump(
         account,
         program_id,
-        COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_SEEDS,
+        &[COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_SEED],
+        bump,
         false,
         needs_writable,
     )
 }
 
 pub fn verify_future_ubi_bank_account<'a>(
-    account: &AccountInfo<'a>, program_id: &Pubkey, needs_writable: bool,
+    account: &AccountInfo<'a>, program_id: &Pubkey, bump: Option<u8>, needs_writable: bool,
+) -> (VerifiedAccountInfo<'a>, u8) {
+    verify_pda_with_known_bump(account, program_id, &[COMPTO_FUTURE_UBI_BANK_ACCOUNT_SEED], bump, false, needs_writable)
+}
+
+// the data account a bank would have if it were a user, passed to the transfer hook
+pub fn verify_bank_data_account<'a>(
+    account: &AccountInfo<'a>, bank: &VerifiedAccountInfo<'a>, program_id: &Pubkey, bump: u8, needs_signer: bool,
+    needs_writable: bool,
 ) -> VerifiedAccountInfo<'a> {
     VerifiedAccountInfo::verify_pda_with_bump(
         account,
         program_id,
-        COMPTO_FUTURE_UBI_BANK_ACCOUNT_SEEDS,
-        false,
+        &[bank.key.as_ref(), &[bump]],
+        needs_signer,
         needs_writable,
     )
 }
 
+// the owner is only checked when the instruction acts on the owner's behalf
 pub fn verify_user_comptoken_token_account<'a>(
-    account: &AccountInfo<'a>, wallet_owner: &VerifiedAccountInfo<'a>, needs_writable: bool,
+    account: &AccountInfo<'a>, wallet_owner: Option<&VerifiedAccountInfo<'a>>, comptoken_mint: &Pubkey,
+    needs_writable: bool,
 ) -> VerifiedAccountInfo<'a> {
-    // msg!("account.owner: {:?}", account.owner);
-    // msg!("account.key: {:?}", account.key);
     let account_data = &account.data.borrow();
-    // msg!("account_data: {:?}", account_data);
     let wallet = StateWithExtensions::<Account>::unpack(account_data).expect("valid account state");
-    // msg!("wallet.base.owner: {:?}", wallet.base.owner);
-    // msg!("wallet_owner.key: {:?}", wallet_owner.key);
-    assert!(*wallet_owner.key == wallet.base.owner);
-    assert_eq!(wallet.base.mint, COMPTOKEN_MINT_ADDRESS);
+    if let Some(wallet_owner) = wallet_owner {
+        assert!(*wallet_owner.key == wallet.base.owner);
+    }
+    assert_eq!(wallet.base.mint, *comptoken_mint);
     VerifiedAccountInfo::verify_account_signer_or_writable(account, false, needs_writable)
 }
 
@@ -91,13 +180,30 @@ pub fn verify_user_data_account<'a>(
     user_data_account: &AccountInfo<'a>, user_comptoken_wallet_account: &VerifiedAccountInfo, program_id: &Pubkey,
     is_created: bool, needs_writable: bool,
 ) -> (VerifiedAccountInfo<'a>, u8) {
-    if is_created {
+    // once created, the user data knows its own bump
+    let bump = is_created.then(|| {
         assert_eq!(user_data_account.owner, program_id);
-    }
-    VerifiedAccountInfo::verify_pda(
+        let data = user_data_account.try_borrow_data().unwrap();
+        UserDataFields::from_account_data(&data).unwrap().bump
+    });
+    verify_pda_with_known_bump(
         user_data_account,
         program_id,
         &[user_comptoken_wallet_account.key.as_ref()],
+        bump,
+        false,
+        needs_writable,
+    )
+}
+
+// the record of the distribution of the day starting at `today`
+pub fn verify_distribution_record_account<'a>(
+    account: &AccountInfo<'a>, program_id: &Pubkey, today: i64, needs_writable: bool,
+) -> (VerifiedAccountInfo<'a>, u8) {
+    VerifiedAccountInfo::verify_pda(
+        account,
+        program_id,
+        &[DISTRIBUTION_RECORD_SEED, &today.to_le_bytes()],
         false,
         needs_writable,
     )
@@ -109,24 +215,44 @@ pub fn verify_slothashes_account<'a>(account: &AccountInfo<'a>) -> VerifiedAccou
 
 pub fn verify_extra_account_metas_account<'a>(
     account: &AccountInfo<'a>, mint: &VerifiedAccountInfo<'a>, transfer_hook_program: &VerifiedAccountInfo<'a>,
-    needs_writable: bool,
-) -> VerifiedAccountInfo<'a> {
-    VerifiedAccountInfo::verify_pda(
+    bump: Option<u8>, needs_writable: bool,
+) -> (VerifiedAccountInfo<'a>, u8) {
+    verify_pda_with_known_bump(
         account,
         transfer_hook_program.key,
         &[b"extra-account-metas", mint.key.as_ref()],
+        bump,
         false,
         needs_writable,
     )
-    .0
 }
 
-pub fn verify_wallet_account<'a>(account: &AccountInfo<'a>) -> VerifiedAccountInfo<'a> {
-    VerifiedAccountInfo::verify_account_signer_or_writable(account, true, false)
+// the exempt accounts aren't created until the admin exempts the first account, and then know their own bump
+pub fn verify_exempt_accounts_account<'a>(
+    account: &AccountInfo<'a>, program_id: &Pubkey, needs_writable: bool,
+) -> (VerifiedAccountInfo<'a>, u8) {
+    let bump = (!account.data_is_empty()).then(|| {
+        assert_eq!(account.owner, program_id);
+        let data = account.try_borrow_data().unwrap();
+        ExemptAccountsFields::from_account_data(&data).unwrap().bump
+    });
+    verify_pda_with_known_bump(account, program_id, &[EXEMPT_ACCOUNTS_SEED], bump, false, needs_writable)
+}
+
+pub fn verify_wallet_account<'a>(account: &AccountInfo<'a>, needs_writable: bool) -> VerifiedAccountInfo<'a> {
+    VerifiedAccountInfo::verify_account_signer_or_writable(account, true, needs_writable)
 }
 
-pub fn verify_transfer_hook_program<'a>(account: &AccountInfo<'a>) -> VerifiedAccountInfo<'a> {
-    VerifiedAccountInfo::verify_specific_address(account, &TRANSFER_HOOK_ID, false, false)
+pub fn verify_transfer_hook_program<'a>(
+    account: &AccountInfo<'a>, transfer_hook_program: &Pubkey,
+) -> VerifiedAccountInfo<'a> {
+    VerifiedAccountInfo::verify_specific_address(account, transfer_hook_program, false, false)
+}
+
+// the program the mint's transfer hook is created with, at initialization
+pub fn verify_uninitialized_transfer_hook_program<'a>(account: &AccountInfo<'a>) -> VerifiedAccountInfo<'a> {
+    assert!(account.executable, "the transfer hook must be a program");
+    VerifiedAccountInfo::verify_account_signer_or_writable(account, false, false)
 }
 
 pub fn verify_solana_program<'a>(account: &AccountInfo<'a>) -> VerifiedAccountInfo<'a> {
@@ -147,54 +273,85 @@ pub type SignerAndWritable = (bool, bool);
 #[derive(Default)]
 pub struct AccountsToVerify {
     pub payer: Option<SignerAndWritable>,
+    pub admin: Option<SignerAndWritable>,
+    pub upgrade_authority: Option<SignerAndWritable>,
     pub comptoken_program: Option<SignerAndWritable>,
+    pub comptoken_program_data: Option<SignerAndWritable>,
     pub comptoken_mint: Option<SignerAndWritable>,
-    pub global_data: Option<SignerAndWritable>,
+    pub global_data: Option<(bool, SignerAndWritable)>, // (isCreated, (needsSigner, needsWritable)),
     pub interest_bank: Option<SignerAndWritable>,
     pub verified_human_ubi_bank: Option<SignerAndWritable>,
     pub future_ubi_bank: Option<SignerAndWritable>,
     pub interest_bank_data: Option<SignerAndWritable>,
     pub verified_human_ubi_bank_data: Option<SignerAndWritable>,
     pub future_ubi_bank_data: Option<SignerAndWritable>,
+    pub distribution_record: Option<SignerAndWritable>,
     pub user_wallet: Option<SignerAndWritable>,
     pub user_comptoken_token_account: Option<SignerAndWritable>,
     pub user_data: Option<(bool, SignerAndWritable)>, // (isCreated, (needsSigner, needsWritable)),
     pub transfer_hook_program: Option<SignerAndWritable>,
     pub extra_account_metas: Option<SignerAndWritable>,
+    pub exempt_accounts: Option<SignerAndWritable>,
     pub solana_program: Option<SignerAndWritable>,
     pub solana_token_2022_program: Option<SignerAndWritable>,
     pub slothashes: Option<SignerAndWritable>,
 }
 
-pub struct VerifiedAccounts<'a> {
+// some accounts are only verified (e.g. programs that are CPIed into) and never read
+#[allow(dead_code)]
+pub struct VerifiedAccounts<'a, 'b> {
     pub payer: Option<VerifiedAccountInfo<'a>>,
+    pub admin: Option<VerifiedAccountInfo<'a>>,
+    pub upgrade_authority: Option<VerifiedAccountInfo<'a>>,
     pub comptoken_program: Option<VerifiedAccountInfo<'a>>,
+    pub comptoken_program_data: Option<VerifiedAccountInfo<'a>>,
     pub comptoken_mint: Option<VerifiedAccountInfo<'a>>,
+    // only known when the mint is being created
+    pub comptoken_mint_bump: Option<u8>,
     pub global_data: Option<VerifiedAccountInfo<'a>>,
+    pub global_data_bump: Option<u8>,
     pub interest_bank: Option<VerifiedAccountInfo<'a>>,
+    pub interest_bank_bump: Option<u8>,
     pub verified_human_ubi_bank: Option<VerifiedAccountInfo<'a>>,
+    pub verified_human_ubi_bank_bump: Option<u8>,
     pub future_ubi_bank: Option<VerifiedAccountInfo<'a>>,
+    pub future_ubi_bank_bump: Option<u8>,
     pub interest_bank_data: Option<VerifiedAccountInfo<'a>>,
     pub verified_human_ubi_bank_data: Option<VerifiedAccountInfo<'a>>,
     pub future_ubi_bank_data: Option<VerifiedAccountInfo<'a>>,
+    pub distribution_record: Option<VerifiedAccountInfo<'a>>,
+    pub distribution_record_bump: Option<u8>,
     pub user_wallet: Option<VerifiedAccountInfo<'a>>,
     pub user_comptoken_token_account: Option<VerifiedAccountInfo<'a>>,
     pub user_data: Option<VerifiedAccountInfo<'a>>,
     pub user_data_bump: Option<u8>,
     pub transfer_hook_program: Option<VerifiedAccountInfo<'a>>,
     pub extra_account_metas: Option<VerifiedAccountInfo<'a>>,
+    pub extra_account_metas_bump: Option<u8>,
+    pub exempt_accounts: Option<VerifiedAccountInfo<'a>>,
+    pub exempt_accounts_bump: Option<u8>,
     pub solana_program: Option<VerifiedAccountInfo<'a>>,
     pub solana_token_2022_program: Option<VerifiedAccountInfo<'a>>,
     pub slothashes: Option<VerifiedAccountInfo<'a>>,
+    // the accounts after the verified ones, for instructions that take optional accounts at the end
+    pub remaining_accounts: &'b [AccountInfo<'a>],
 }
 
-pub fn verify_accounts<'a>(
-    accounts: &[AccountInfo<'a>], program_id: &Pubkey, accounts_to_verify: AccountsToVerify,
-) -> Result<VerifiedAccounts<'a>, ProgramError> {
+pub fn verify_accounts<'a, 'b>(
+    accounts: &'b [AccountInfo<'a>], program_id: &Pubkey, accounts_to_verify: AccountsToVerify,
+) -> Result<VerifiedAccounts<'a, 'b>, ProgramError> {
     let account_info_iter = &mut accounts.iter();
     let payer = accounts_to_verify
         .payer
         .map(|_| verify_payer_account(next_account_info(account_info_iter).unwrap()));
+    // the admin is checked against the global data, which comes after it
+    let admin_account = accounts_to_verify
+        .admin
+        .map(|(_, needs_writable)| (next_account_info(account_info_iter).unwrap(), needs_writable));
+    // the upgrade authority is checked against the program data, which comes after it
+    let upgrade_authority_account = accounts_to_verify
+        .upgrade_authority
+        .map(|(_, needs_writable)| (next_account_info(account_info_iter).unwrap(), needs_writable));
 
     let comptoken_program = accounts_to_verify.comptoken_program.map(|(needs_signer, needs_writable)| {
         VerifiedAccountInfo::verify_specific_address(
@@ -204,66 +361,139 @@ pub fn verify_accounts<'a>(
             needs_writable,
         )
     });
-    let comptoken_mint = accounts_to_verify.comptoken_mint.map(|(_, needs_writable)| {
-        verify_comptoken_mint(next_account_info(account_info_iter).unwrap(), needs_writable)
+    let (comptoken_program_data, upgrade_authority_address) = accounts_to_verify
+        .comptoken_program_data
+        .map(|_| verify_comptoken_program_data_account(next_account_info(account_info_iter).unwrap(), program_id))
+        .unzip();
+    let upgrade_authority = upgrade_authority_account.map(|(account, needs_writable)| {
+        verify_upgrade_authority_account(account, upgrade_authority_address.flatten(), needs_writable)
     });
-    let global_data = accounts_to_verify.global_data.map(|(_, needs_writable)| {
-        verify_global_data_account(next_account_info(account_info_iter).unwrap(), program_id, needs_writable)
+    // the mint is checked against the global data, which comes after it
+    let comptoken_mint_account = accounts_to_verify
+        .comptoken_mint
+        .map(|(_, needs_writable)| (next_account_info(account_info_iter).unwrap(), needs_writable));
+    let (global_data, global_data_bump) = accounts_to_verify
+        .global_data
+        .map(|(is_created, (_, needs_writable))| {
+            verify_global_data_account(
+                next_account_info(account_info_iter).unwrap(),
+                program_id,
+                is_created,
+                needs_writable,
+            )
+        })
+        .unzip();
+    // the addresses and bumps the program was initialized with
+    let (comptoken_addresses, bumps): (Option<(Pubkey, Pubkey)>, Option<Bumps>) = accounts_to_verify
+        .global_data
+        .filter(|(is_created, _)| *is_created)
+        .map(|_| {
+            let global_data: &mut GlobalData = global_data.as_ref().unwrap().into();
+            ((global_data.comptoken_mint, global_data.transfer_hook_program), global_data.bumps)
+        })
+        .unzip();
+    let admin = admin_account.map(|(account, needs_writable)| {
+        let global_data: &mut GlobalData = global_data.as_ref().unwrap().into();
+        verify_admin_account(account, &global_data.admin, needs_writable)
     });
+    let (comptoken_mint, comptoken_mint_bump) = comptoken_mint_account
+        .map(|(account, needs_writable)| match comptoken_addresses {
+            Some((comptoken_mint, _)) => (verify_comptoken_mint(account, &comptoken_mint, needs_writable), None),
+            None => {
+                let (comptoken_mint, bump) = verify_uninitialized_comptoken_mint(account, program_id, needs_writable);
+                (comptoken_mint, Some(bump))
+            }
+        })
+        .unzip();
+    let comptoken_mint_bump = comptoken_mint_bump.flatten();
 
-    let interest_bank = accounts_to_verify.interest_bank.map(|(_, needs_writable)| {
-        verify_interest_bank_account(next_account_info(account_info_iter).unwrap(), program_id, needs_writable)
-    });
-    let verified_human_ubi_bank = accounts_to_verify.verified_human_ubi_bank.map(|(_, needs_writable)| {
-        verify_verified_human_ubi_bank_account(
-            next_account_info(account_info_iter).unwrap(),
-            program_id,
-            needs_writable,
-        )
-    });
-    let future_ubi_bank = accounts_to_verify.future_ubi_bank.map(|(_, needs_writable)| {
-        verify_future_ubi_bank_account(next_account_info(account_info_iter).unwrap(), program_id, needs_writable)
-    });
+    let (interest_bank, interest_bank_bump) = accounts_to_verify
+        .interest_bank
+        .map(|(_, needs_writable)| {
+            verify_interest_bank_account(
+                next_account_info(account_info_iter).unwrap(),
+                program_id,
+                bumps.map(|bumps| bumps.interest_bank),
+                needs_writable,
+            )
+        })
+        .unzip();
+    let (verified_human_ubi_bank, verified_human_ubi_bank_bump) = accounts_to_verify
+        .verified_human_ubi_bank
+        .map(|(_, needs_writable)| {
+            verify_verified_human_ubi_bank_account(
+                next_account_info(account_info_iter).unwrap(),
+                program_id,
+                bumps.map(|bumps| bumps.verified_human_ubi_bank),
+                needs_writable,
+            )
+        })
+        .unzip();
+    let (future_ubi_bank, future_ubi_bank_bump) = accounts_to_verify
+        .future_ubi_bank
+        .map(|(_, needs_writable)| {
+            verify_future_ubi_bank_account(
+                next_account_info(account_info_iter).unwrap(),
+                program_id,
+                bumps.map(|bumps| bumps.future_ubi_bank),
+                needs_writable,
+            )
+        })
+        .unzip();
 
     let interest_bank_data = accounts_to_verify.interest_bank_data.map(|(needs_signer, needs_writable)| {
-        VerifiedAccountInfo::verify_pda(
+        verify_bank_data_account(
             next_account_info(account_info_iter).unwrap(),
+            interest_bank.as_ref().unwrap(),
             program_id,
-            &[interest_bank.as_ref().unwrap().key.as_ref()],
+            bumps.unwrap().interest_bank_data,
             needs_signer,
             needs_writable,
         )
-        .0
     });
     let verified_human_ubi_bank_data =
         accounts_to_verify.verified_human_ubi_bank_data.map(|(needs_signer, needs_writable)| {
-            VerifiedAccountInfo::verify_pda(
+            verify_bank_data_account(
                 next_account_info(account_info_iter).unwrap(),
+                verified_human_ubi_bank.as_ref().unwrap(),
                 program_id,
-                &[verified_human_ubi_bank.as_ref().unwrap().key.as_ref()],
+                bumps.unwrap().verified_human_ubi_bank_data,
                 needs_signer,
                 needs_writable,
             )
-            .0
         });
     let future_ubi_bank_data = accounts_to_verify.future_ubi_bank_data.map(|(needs_signer, needs_writable)| {
-        VerifiedAccountInfo::verify_pda(
+        verify_bank_data_account(
             next_account_info(account_info_iter).unwrap(),
+            future_ubi_bank.as_ref().unwrap(),
             program_id,
-            &[future_ubi_bank.as_ref().unwrap().key.as_ref()],
+            bumps.unwrap().future_ubi_bank_data,
             needs_signer,
             needs_writable,
         )
-        .0
     });
 
-    let user_wallet = accounts_to_verify
-        .user_wallet
-        .map(|_| verify_wallet_account(next_account_info(account_info_iter).unwrap()));
+    let (distribution_record, distribution_record_bump) = accounts_to_verify
+        .distribution_record
+        .map(|(_, needs_writable)| {
+            let global_data: &mut GlobalData = global_data.as_ref().unwrap().into();
+            verify_distribution_record_account(
+                next_account_info(account_info_iter).unwrap(),
+                program_id,
+                global_data.today(),
+                needs_writable,
+            )
+        })
+        .unzip();
+
+    let user_wallet = accounts_to_verify.user_wallet.map(|(_, needs_writable)| {
+        verify_wallet_account(next_account_info(account_info_iter).unwrap(), needs_writable)
+    });
     let user_comptoken_token_account = accounts_to_verify.user_comptoken_token_account.map(|(_, needs_writable)| {
         verify_user_comptoken_token_account(
             next_account_info(account_info_iter).unwrap(),
-            user_wallet.as_ref().unwrap(),
+            user_wallet.as_ref(),
+            &comptoken_addresses.unwrap().0,
             needs_writable,
         )
     });
@@ -280,17 +510,31 @@ pub fn verify_accounts<'a>(
         })
         .unzip();
 
-    let transfer_hook_program = accounts_to_verify
-        .transfer_hook_program
-        .map(|_| verify_transfer_hook_program(next_account_info(account_info_iter).unwrap()));
-    let extra_account_metas = accounts_to_verify.extra_account_metas.map(|(_, needs_writable)| {
-        verify_extra_account_metas_account(
-            next_account_info(account_info_iter).unwrap(),
-            comptoken_mint.as_ref().unwrap(),
-            transfer_hook_program.as_ref().unwrap(),
-            needs_writable,
-        )
+    let transfer_hook_program = accounts_to_verify.transfer_hook_program.map(|_| {
+        let account = next_account_info(account_info_iter).unwrap();
+        match comptoken_addresses {
+            Some((_, transfer_hook_program)) => verify_transfer_hook_program(account, &transfer_hook_program),
+            None => verify_uninitialized_transfer_hook_program(account),
+        }
     });
+    let (extra_account_metas, extra_account_metas_bump) = accounts_to_verify
+        .extra_account_metas
+        .map(|(_, needs_writable)| {
+            verify_extra_account_metas_account(
+                next_account_info(account_info_iter).unwrap(),
+                comptoken_mint.as_ref().unwrap(),
+                transfer_hook_program.as_ref().unwrap(),
+                bumps.map(|bumps| bumps.extra_account_metas),
+                needs_writable,
+            )
+        })
+        .unzip();
+    let (exempt_accounts, exempt_accounts_bump) = accounts_to_verify
+        .exempt_accounts
+        .map(|(_, needs_writable)| {
+            verify_exempt_accounts_account(next_account_info(account_info_iter).unwrap(), program_id, needs_writable)
+        })
+        .unzip();
 
     let solana_program = accounts_to_verify
         .solana_program
@@ -304,23 +548,65 @@ pub fn verify_accounts<'a>(
 
     Ok(VerifiedAccounts {
         payer,
+        admin,
+        upgrade_authority,
         comptoken_program,
+        comptoken_program_data,
         comptoken_mint,
+        comptoken_mint_bump,
         global_data,
+        global_data_bump,
         interest_bank,
+        interest_bank_bump,
         verified_human_ubi_bank,
+        verified_human_ubi_bank_bump,
         future_ubi_bank,
+        future_ubi_bank_bump,
         interest_bank_data,
         verified_human_ubi_bank_data,
         future_ubi_bank_data,
+        distribution_record,
+        distribution_record_bump,
         user_wallet,
         user_comptoken_token_account,
         user_data,
         user_data_bump,
         transfer_hook_program,
         extra_account_metas,
+        extra_account_metas_bump,
+        exempt_accounts,
+        exempt_accounts_bump,
         solana_program,
         solana_token_2022_program,
         slothashes,
+        remaining_accounts: account_info_iter.as_slice(),
     })
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn program_data(upgrade_authority_address: Option<Pubkey>) -> Vec<u8> {
+        let state = UpgradeableLoaderState::ProgramData { slot: 42, upgrade_authority_address };
+        let mut data = bincode::serialize(&state).unwrap();
+        data.resize(UpgradeableLoaderState::size_of_programdata_metadata() + 8, 0xff);
+        data
+    }
+
+    #[test]
+    fn test_read_upgrade_authority() {
+        let upgrade_authority = Pubkey::new_unique();
+        assert_eq!(read_upgrade_authority(&program_data(Some(upgrade_authority))), Some(upgrade_authority));
+        assert_eq!(read_upgrade_authority(&program_data(None)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a program data account")]
+    fn test_read_upgrade_authority_of_program() {
+        let state = UpgradeableLoaderState::Program { programdata_address: Pubkey::new_unique() };
+        let mut data = bincode::serialize(&state).unwrap();
+        data.resize(UpgradeableLoaderState::size_of_programdata_metadata(), 0);
+        read_upgrade_authority(&data);
+    }
+}
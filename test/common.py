@@ -14,16 +14,10 @@ DEPLOY_PATH = PROJECT_PATH / "target/deploy"
 COMPTOKEN_SRC_PATH = PROJECT_PATH / "comptoken"
 TRANSFER_HOOK_SRC_PATH = PROJECT_PATH / "comptoken-transfer-hook"
 CACHE_PATH = TEST_PATH / ".cache"
-COMPTOKEN_GENERATED_PATH = COMPTOKEN_SRC_PATH / "src/generated"
-TRANSFER_HOOK_GENERATED_PATH = TRANSFER_HOOK_SRC_PATH / "src/generated"
-
-COMPTO_GENERATED_RS_FILE = COMPTOKEN_GENERATED_PATH / "comptoken_generated.rs"
-TRANSFER_HOOK_GENERATED_RS_FILE = TRANSFER_HOOK_GENERATED_PATH / "comptoken_generated.rs"
 COMPTO_SO = DEPLOY_PATH / "comptoken.so"
 COMPTO_KEYPAIR = DEPLOY_PATH / "comptoken-keypair.json"
 TRANSFER_HOOK_SO = DEPLOY_PATH / "comptoken_transfer_hook.so"
 TRANSFER_HOOK_KEYPAIR = DEPLOY_PATH / "comptoken_transfer_hook-keypair.json"
-MINT_KEYPAIR = CACHE_PATH / "comptoken_mint-keypair.json"
 
 COMPTO_PROGRAM_ID_JSON = CACHE_PATH / "compto_program_id.json"
 COMPTO_TRANSFER_HOOK_ID_JSON = CACHE_PATH / "compto_transfer_hook_id.json"
@@ -101,8 +95,6 @@ import argparse
 
 def generateDirectories(args: argparse.Namespace):
     createDirIfNotExists(CACHE_PATH)
-    createDirIfNotExists(COMPTOKEN_GENERATED_PATH)
-    createDirIfNotExists(TRANSFER_HOOK_GENERATED_PATH)
     if args.log_directory:
         createDirIfNotExists(args.log_directory)
         createDirIfNotExists(args.log_directory / "comptoken-tests")
@@ -143,67 +135,10 @@ def write(path: Path, data: str):
     with open(path, "w") as file:
         file.write(data)
 
-def generateComptokenAddressFile(
-    globalDataSeed: int, interestBankSeed: int, verifiedHumanUBIBankSeed: int, futureUBIBankSeed: int, mintAddress: str,
-    transferHookAddress: str
-):
-    print(f"Generating {COMPTO_GENERATED_RS_FILE}...")
-    file_data = f"""\
-// AUTOGENERATED DO NOT TOUCH
-// generated by test/common.py
-
-// A given seed and program id have a 50% chance of creating a valid PDA.
-// Before building/deploying, we find the canonical seed by running 
-//      `solana find-program-derived-address <program_id>`
-// This is an efficiency optimization. We are using a static seed to create the PDA with no bump.
-// We ensure when deploying that the program id is one that only needs the seed above and no bump.
-// This is because 
-//      (1) create_program_address is not safe if using a user provided bump.
-//      (2) find_program_address is expensive and we want to avoid iterations.
-
-use spl_token_2022::solana_program::{{pubkey, pubkey::Pubkey}};
-
-pub const COMPTOKEN_MINT_ADDRESS: Pubkey = pubkey!("{mintAddress}");
-
-pub const TRANSFER_HOOK_ID: Pubkey = pubkey!("{transferHookAddress}");
-
-pub const COMPTO_GLOBAL_DATA_ACCOUNT_BUMP: u8 = {globalDataSeed};
-pub const COMPTO_INTEREST_BANK_ACCOUNT_BUMP: u8 = {interestBankSeed};
-pub const COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_BUMP: u8 = {verifiedHumanUBIBankSeed};
-pub const COMPTO_FUTURE_UBI_BANK_ACCOUNT_BUMP: u8 = {futureUBIBankSeed};\
-"""
-    write(COMPTO_GENERATED_RS_FILE, file_data)
-
-def generateTransferHookAddressFile(
-    comptokenAddress: str, extraAccountMetasSeed: int, mintAddress: str, interestBankAddress: str,
-    verifiedHumanUBIBankAddress: str, futureUBIBankAddress: str
-):
-    print(f"Generating {TRANSFER_HOOK_GENERATED_RS_FILE}...")
-    file_data = f"""\
-// AUTOGENERATED DO NOT TOUCH
-// generated by test/common.py
-
-// A given seed and program id have a 50% chance of creating a valid PDA.
-// Before building/deploying, we find the canonical seed by running 
-//      `solana find-program-derived-address <program_id>`
-// This is an efficiency optimization. We are using a static seed to create the PDA with no bump.
-// We ensure when deploying that the program id is one that only needs the seed above and no bump.
-// This is because 
-//      (1) create_program_address is not safe if using a user provided bump.
-//      (2) find_program_address is expensive and we want to avoid iterations.
-
-use spl_token_2022::solana_program::{{pubkey, pubkey::Pubkey}};
-
-pub const COMPTOKEN_ID: Pubkey = pubkey!("{comptokenAddress}");
-
-pub const EXTRA_ACCOUNT_METAS_BUMP: u8 = {extraAccountMetasSeed};
-pub const MINT_ADDRESS: Pubkey = pubkey!("{mintAddress}");
-
-pub const COMPTO_INTEREST_BANK_ACCOUNT_PUBKEY: Pubkey = pubkey!("{interestBankAddress}");
-pub const COMPTO_VERIFIED_HUMAN_UBI_BANK_ACCOUNT_PUBKEY: Pubkey = pubkey!("{verifiedHumanUBIBankAddress}");
-pub const COMPTO_FUTURE_UBI_BANK_ACCOUNT_PUBKEY: Pubkey = pubkey!("{futureUBIBankAddress}");\
-"""
-    write(TRANSFER_HOOK_GENERATED_RS_FILE, file_data)
+def setComptokenMintPDA(programId: str) -> PDA:
+    pda = PDA(programId, "Comptoken Mint")
+    write(COMPTOKEN_MINT_JSON, json.dumps(pda))
+    return pda
 
 def setGlobalDataPDA(programId: str) -> PDA:
     pda = PDA(programId, "Global Data")
@@ -240,31 +175,18 @@ def createKeyPair(outfile: Path):
 def generateTestUser():
     createKeyPair(TEST_USER_ACCOUNT_JSON)
 
-def generateFiles(comptokenProgramId: str, transferHookId: str, mintAddress: str):
+# the programs find their addresses at runtime, these files are only for the test clients
+def generateFiles(comptokenProgramId: str, transferHookId: str):
     print("generating files...")
     # pdas
-    globalDataSeed = setGlobalDataPDA(comptokenProgramId)["bumpSeed"]
-
-    interestBankPDA = setInterestBankPDA(comptokenProgramId)
-    interestBankSeed = interestBankPDA["bumpSeed"]
-    interestBankAddress = interestBankPDA["address"]
-    verifiedHumanUBIBankPDA = setVerifiedHumanUBIBankPDA(comptokenProgramId)
-    verifiedHumanUBIBankSeed = verifiedHumanUBIBankPDA["bumpSeed"]
-    verifiedHumanUBIBankAddress = verifiedHumanUBIBankPDA["address"]
-    futureUBIBankPDA = setFutureUBIBankPDA(comptokenProgramId)
-    futureUBIBankSeed = futureUBIBankPDA["bumpSeed"]
-    futureUBIBankAddress = futureUBIBankPDA["address"]
-
-    extraAccountMetasSeed = setExtraAccountMetasPDA(transferHookId, Pubkey(mintAddress))["bumpSeed"]
+    mintAddress = setComptokenMintPDA(comptokenProgramId)["address"]
+    setGlobalDataPDA(comptokenProgramId)
+    setInterestBankPDA(comptokenProgramId)
+    setVerifiedHumanUBIBankPDA(comptokenProgramId)
+    setFutureUBIBankPDA(comptokenProgramId)
+    setExtraAccountMetasPDA(transferHookId, Pubkey(mintAddress))
     # test user
     generateTestUser()
-    # rust file
-    generateComptokenAddressFile(
-        globalDataSeed, interestBankSeed, verifiedHumanUBIBankSeed, futureUBIBankSeed, mintAddress, transferHookId
-    )
-    generateTransferHookAddressFile(
-        comptokenProgramId, extraAccountMetasSeed, mintAddress, interestBankAddress, verifiedHumanUBIBankAddress, futureUBIBankAddress
-    )
     print("done generating files")
 
 def parseArgs():
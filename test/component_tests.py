@@ -13,8 +13,7 @@ ANSI_RESET = "\033[0m"
 def generateMockFiles():
     comptokenProgramId = generateMockComptokenProgramIdFile()
     transferHookId = generateMockTransferHookProgramIdFile()
-    mintAddress = generateMockMint()
-    return (comptokenProgramId, transferHookId, mintAddress)
+    return (comptokenProgramId, transferHookId)
 
 
 def generateMockComptokenProgramIdFile():
@@ -27,23 +26,6 @@ def generateMockTransferHookProgramIdFile():
     write(COMPTO_TRANSFER_HOOK_ID_JSON, json.dumps({"programId": programId}))
     return programId
 
-def generateMockMint() -> str:
-    address = randAddress()
-    file_data = f'''\
-{{
-    "commandName": "CreateToken",
-    "commandOutput": {{
-        "address": "{address}",
-        "decimals": {MINT_DECIMALS},
-        "transactionData": {{
-            "signature": ""
-        }}
-    }}
-}}\
-'''
-    write(COMPTOKEN_MINT_JSON, file_data)
-    return address
-
 def runTest(args: Namespace, file: str) -> bool:
     print(f"running {file}")
     env = os.environ
@@ -82,6 +64,21 @@ def runTests(args: Namespace, tests: list[str]):
     if failed > 0:
         sys.exit(1)
 
+def runProgramTests(args: Namespace):
+    print("running program tests...")
+    env = os.environ
+    env["SBF_OUT_DIR"] = str(PROJECT_PATH / "target/deploy/")
+    command = f"cargo test --locked --manifest-path {PROJECT_PATH / 'comptoken-program-tests/Cargo.toml'}"
+    try:
+        stdout = run(command, env=env)
+        if args.verbose >= 1:
+            print(stdout)
+        print(f"✅ {ANSI_GREEN}program tests{ANSI_RESET} passed")
+    except SubprocessFailedException as e:
+        print(f"❌ {ANSI_RED}program tests{ANSI_RESET} failed")
+        print(e)
+        sys.exit(1)
+
 @contextmanager
 def file_or_stdout(outfile: Path | None):
     if outfile is not None:
@@ -97,16 +94,26 @@ if __name__ == "__main__":
         "comptoken-tests/createUserDataAccount",
         "comptoken-tests/growUserDataAccount",
         "comptoken-tests/shrinkUserDataAccount",
+        "comptoken-tests/closeUserDataAccount",
         "comptoken-tests/proofSubmission",
         "comptoken-tests/getValidBlockhashes",
         "comptoken-tests/getOwedComptokens",
+        "comptoken-tests/getBankReconciliation",
+        "comptoken-tests/migrateAccounts",
+        "comptoken-tests/updateMetadata",
+        "comptoken-tests/updateExtraAccountMetas",
+        "comptoken-tests/exemptAccounts",
+        "comptoken-tests/setAdmin",
         "comptoken-tests/earlyDailyDistributionEvent",
         "comptoken-tests/dailyDistributionEvent",
+        "comptoken-tests/missedDailyDistributionEvent",
+        "comptoken-tests/dailyDistributionCrankReward",
         "comptoken-tests/dailyDistributionTests",
         "comptoken-tests/multidayDailyDistribution",
         "comptoken-tests/randomMultidayDailyDistribution",
         "comptoken-tests/definedMultidayDailyDistribution",
         "transfer-hook-tests/initialize_extra_account_meta_list",
+        "transfer-hook-tests/update_extra_account_meta_list",
         "transfer-hook-tests/execute",
         # commented out until WorldCoin integration is implemented
         #"comptoken-tests/verifyHuman",
@@ -115,8 +122,8 @@ if __name__ == "__main__":
     args = parseArgs()
     generateDirectories(args)
     if args.generate:
-        (comptokenProgramId, transferHookId, mintAddress) = generateMockFiles()
-        generateFiles(comptokenProgramId, transferHookId, mintAddress)
+        (comptokenProgramId, transferHookId) = generateMockFiles()
+        generateFiles(comptokenProgramId, transferHookId)
     if args.build:
         buildCompto()
         buildTransferHook()
@@ -125,3 +132,4 @@ if __name__ == "__main__":
         print("skipping building")
 
     runTests(args, tests)
+    runProgramTests(args)
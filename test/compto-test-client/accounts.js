@@ -1,5 +1,5 @@
-import { blob, f64, greedy, seq, struct } from "@solana/buffer-layout";
-import { bool, u64 } from "@solana/buffer-layout-utils";
+import { blob, f64, greedy, seq, struct, u8 } from "@solana/buffer-layout";
+import { bool, publicKey, u64 } from "@solana/buffer-layout-utils";
 import {
     ACCOUNT_SIZE,
     AccountLayout,
@@ -19,19 +19,29 @@ import {
     compto_transfer_hook_id_pubkey,
     COMPTOKEN_DECIMALS,
     comptoken_mint_pubkey,
+    COMPTOKEN_NAME,
+    COMPTOKEN_SYMBOL,
+    COMPTOKEN_URI,
+    DAY_BOUNDARY_OFFSET,
     DEFAULT_ANNOUNCE_TIME,
     DEFAULT_DISTRIBUTION_TIME,
+    EXEMPT_ACCOUNTS_SEED,
+    exempt_accounts_account_pubkey,
+    EXTRA_ACCOUNT_METAS_SEED,
+    FUTURE_UBI_BANK_SEED,
     future_ubi_bank_account_pubkey,
+    GLOBAL_DATA_SEED,
     global_data_account_pubkey,
+    INTEREST_BANK_SEED,
     interest_bank_account_pubkey,
+    VERIFIED_HUMAN_UBI_BANK_SEED,
     verified_human_ubi_bank_account_pubkey,
 } from "./common.js";
-import { getOptionOr, numAsU16ToLEBytes, toOption } from "./utils.js";
+import { getOptionOr, numAsU16ToLEBytes, numAsU32ToLEBytes, toOption } from "./utils.js";
 
-export const COMPTOKEN_WALLET_SIZE = 256; // TODO: get actual value
 
 
-class ExtensionType {
+export class ExtensionType {
     // u16 discriminated type for an extension
     // https://github.com/solana-labs/solana-program-library/blob/master/token/program-2022/src/extension/mod.rs#L1042-L1115
     static Uninitialized = 0;
@@ -91,6 +101,69 @@ export class TLV {
         return new TLV(ExtensionType.TransferHook, 64, value);
     }
 
+    /**
+     * @param {PublicKey | null} authority
+     * @param {PublicKey | null} metadataAddress
+     * @returns {TLV}
+     */
+    static MetadataPointer(authority, metadataAddress) {
+        authority = getOptionOr(toOption(authority), () => PublicKey.default).val;
+        metadataAddress = getOptionOr(toOption(metadataAddress), () => PublicKey.default).val;
+        let value = Uint8Array.from([...authority.toBytes(), ...metadataAddress.toBytes()]);
+        return new TLV(ExtensionType.MetadataPointer, 64, value);
+    }
+
+    /**
+     * the metadata is borsh encoded, so each string is prefixed by its u32 length
+     * @param {{updateAuthority: PublicKey, mint: PublicKey, name: string, symbol: string, uri: string, additionalMetadata: [string, string][]}} metadata
+     * @returns {TLV}
+     */
+    static TokenMetadata(metadata) {
+        const encodeString = (string) => {
+            const bytes = Buffer.from(string);
+            return [...numAsU32ToLEBytes(bytes.length), ...bytes];
+        };
+        let value = Uint8Array.from([
+            ...metadata.updateAuthority.toBytes(),
+            ...metadata.mint.toBytes(),
+            ...encodeString(metadata.name),
+            ...encodeString(metadata.symbol),
+            ...encodeString(metadata.uri),
+            ...numAsU32ToLEBytes(metadata.additionalMetadata.length),
+            ...metadata.additionalMetadata.flatMap(([key, value]) => [...encodeString(key), ...encodeString(value)]),
+        ]);
+        return new TLV(ExtensionType.TokenMetadata, value.length, value);
+    }
+
+    /**
+     * the inverse of `TLV.TokenMetadata`
+     * @returns {{updateAuthority: PublicKey, mint: PublicKey, name: string, symbol: string, uri: string, additionalMetadata: [string, string][]}}
+     */
+    toTokenMetadata() {
+        const buffer = Buffer.from(this.value);
+        let index = 64;
+        const decodeString = () => {
+            const length = buffer.readUInt32LE(index);
+            const string = buffer.toString("utf8", index + 4, index + 4 + length);
+            index += 4 + length;
+            return string;
+        };
+        const name = decodeString();
+        const symbol = decodeString();
+        const uri = decodeString();
+        const count = buffer.readUInt32LE(index);
+        index += 4;
+        const additionalMetadata = Array.from({ length: count }, () => [decodeString(), decodeString()]);
+        return {
+            updateAuthority: new PublicKey(buffer.subarray(0, 32)),
+            mint: new PublicKey(buffer.subarray(32, 64)),
+            name,
+            symbol,
+            uri,
+            additionalMetadata,
+        };
+    }
+
     /**
      * @returns {TLV}
      */
@@ -311,10 +384,29 @@ export class ExtraAccountMetaAccount extends Account {
     static DATA_TYPE = ExtraAccountMetaAccountData;
 }
 
+// MAGIC NUMBER: CHANGES NEED TO BE REFLECTED IN versioned_account.rs
+export const AccountHeaderLayout = struct([
+    blob(8, "discriminator"),
+    u8("version"),
+    blob(7), // padding
+]);
+
+/**
+ * @param {string} discriminator the 8 character discriminator of the account type
+ * @param {number} version
+ * @returns {Object} the header the comptoken program writes at the start of its accounts
+ */
+export function get_account_header(discriminator, version = 1) {
+    return { discriminator: Buffer.from(discriminator), version: version };
+}
+
 export const UserDataLayout = struct([
+    AccountHeaderLayout.replicate("header"),
     u64("lastInterestPayoutDate"), // actually an i64 but will always be positive
     bool("isVerifiedHuman"),
-    blob(7), // padding
+    u8("bump"),
+    bool("proofsSorted"),
+    blob(5), // padding
     u64("length"),
     blob(32, "recentBlockhash"),
     seq(blob(32), greedy(32), "proofs"),
@@ -323,16 +415,28 @@ export const UserDataLayout = struct([
 export class UserData extends DataType {
     static LAYOUT = UserDataLayout;
 
-    static MIN_SIZE = 88; // MAGIC NUMBER: CHANGE NEEDS TO BE REFLECTED IN user_data.rs
+    static MIN_SIZE = 104; // MAGIC NUMBER: CHANGE NEEDS TO BE REFLECTED IN user_data.rs
+    static VERSION = 1; // MAGIC NUMBER: CHANGE NEEDS TO BE REFLECTED IN user_data.rs
 
+    header_; // AccountHeader
     lastInterestPayoutDate_; // i64
     isVerifiedHuman_; // bool
+    bump_; // u8
+    proofsSorted_; // bool
     length_; // usize
     recentBlockhash_; // Hash
     proofs_; // [Hash]
 
     getSize() {
-        return this.constructor.MIN_SIZE + 32 * (this.proofs.length - 1);
+        return this.constructor.sizeForCapacity(this.proofs.length);
+    }
+
+    /**
+     * @param {number} proof_capacity
+     * @returns {number} the size of a user data account that can hold `proof_capacity` proofs
+     */
+    static sizeForCapacity(proof_capacity) {
+        return this.MIN_SIZE + 32 * (proof_capacity - 1);
     }
 }
 
@@ -340,15 +444,65 @@ export class UserDataAccount extends Account {
     static DATA_TYPE = UserData;
 }
 
+export const ExemptAccountsLayout = struct([
+    AccountHeaderLayout.replicate("header"),
+    u8("bump"),
+    blob(7), // padding
+    u64("length"),
+    seq(publicKey(), greedy(32), "accounts"),
+]);
+
+export class ExemptAccounts extends DataType {
+    static LAYOUT = ExemptAccountsLayout;
+
+    static MIN_SIZE = 32; // MAGIC NUMBER: CHANGE NEEDS TO BE REFLECTED IN exempt_accounts.rs
+    static VERSION = 1; // MAGIC NUMBER: CHANGE NEEDS TO BE REFLECTED IN exempt_accounts.rs
+
+    header_; // AccountHeader
+    bump_; // u8
+    length_; // usize
+    accounts_; // [PublicKey]
+
+    getSize() {
+        return this.constructor.MIN_SIZE + 32 * this.accounts.length;
+    }
+}
+
+export class ExemptAccountsAccount extends Account {
+    static DATA_TYPE = ExemptAccounts;
+}
+
 export class GlobalData extends DataType {
     // LAYOUT defined later to avoid circular dependency
 
+    header_; // AccountHeader
+    comptokenMint_; // PublicKey
+    transferHookProgram_; // PublicKey
+    bumps_;
     validBlockhashes_;
     dailyDistributionData_;
+    bankLiabilities_;
+    admin_; // PublicKey
+    dayBoundaryOffset_; // i64
 
+    static VERSION = 3; // MAGIC NUMBER: CHANGE NEEDS TO BE REFLECTED IN global_data/mod.rs
     static DAILY_DISTRIBUTION_HISTORY_SIZE = 365; // MAGIC NUMBER: remain consistent with rust
+    // the global data from before the account header was only the valid blockhashes and the daily distribution data
+    static LEGACY_OFFSET = 88; // MAGIC NUMBER: CHANGE NEEDS TO BE REFLECTED IN global_data/mod.rs
+    static LEGACY_SIZE = 5960; // MAGIC NUMBER: CHANGE NEEDS TO BE REFLECTED IN global_data/mod.rs
 }
 
+export const BumpsLayout = struct([
+    u8("globalData"),
+    u8("interestBank"),
+    u8("verifiedHumanUbiBank"),
+    u8("futureUbiBank"),
+    u8("interestBankData"),
+    u8("verifiedHumanUbiBankData"),
+    u8("futureUbiBankData"),
+    u8("extraAccountMetas"),
+]);
+
 export const ValidBlockhashesLayout = struct([
     blob(32, "announcedBlockhash"),
     u64("announcedBlockhashTime"), // actually i64, but will always be positive
@@ -370,9 +524,23 @@ export const DailyDistributionDataLayout = struct([
     seq(DistributionLayout.replicate(), GlobalData.DAILY_DISTRIBUTION_HISTORY_SIZE, "historicDistributions"),
 ]);
 
+export const BankLiabilitiesLayout = struct([
+    u64("interestBank"),
+    u64("verifiedHumanUbiBank"),
+    u64("futureUbiBank"),
+    u64("lastDustSweepTime"), // actually i64, but will always be positive
+]);
+
 export const GlobalDataLayout = struct([
+    AccountHeaderLayout.replicate("header"),
+    publicKey("comptokenMint"),
+    publicKey("transferHookProgram"),
+    BumpsLayout.replicate("bumps"),
     ValidBlockhashesLayout.replicate("validBlockhashes"),
     DailyDistributionDataLayout.replicate("dailyDistributionData"),
+    BankLiabilitiesLayout.replicate("bankLiabilities"),
+    publicKey("admin"),
+    u64("dayBoundaryOffset"), // actually i64, but will always be positive
 ])
 
 GlobalData.LAYOUT = GlobalDataLayout; // GlobalDataLayout uses DailyDistributionData, which uses GlobalData's DAILY_DISTRIBUTION_HISTORY_SIZE
@@ -381,6 +549,42 @@ export class GlobalDataAccount extends Account {
     static DATA_TYPE = GlobalData;
 }
 
+export const DistributionRecordLayout = struct([
+    AccountHeaderLayout.replicate("header"),
+    u64("distributionTime"), // actually i64, but will always be positive
+    u64("supply"),
+    u64("highWaterMark"),
+    u64("highWaterMarkIncrease"),
+    u64("interestDistribution"),
+    u64("ubiForVerifiedHumans"),
+    u64("futureUbiDistribution"),
+    u64("crankReward"),
+    u64("verifiedHumans"),
+    f64("interestRate"),
+    u64("ubi"),
+]);
+
+export class DistributionRecord extends DataType {
+    static LAYOUT = DistributionRecordLayout;
+
+    header_; // AccountHeader
+    distributionTime_; // i64
+    supply_; // u64
+    highWaterMark_; // u64
+    highWaterMarkIncrease_; // u64
+    interestDistribution_; // u64
+    ubiForVerifiedHumans_; // u64
+    futureUbiDistribution_; // u64
+    crankReward_; // u64
+    verifiedHumans_; // u64
+    interestRate_; // f64
+    ubi_; // u64
+}
+
+export class DistributionRecordAccount extends Account {
+    static DATA_TYPE = DistributionRecord;
+}
+
 export class Seed {
     discriminator; // u8
     data; // [u8]
@@ -394,17 +598,20 @@ export class Seed {
     }
 
     constructor(discriminator, data) {
-        if (discriminator !== Seed.Types.ACCOUNT_KEY) {
-            throw Error("not implemented");
+        switch (discriminator) {
+            case Seed.Types.LITERAL:
+                this.data = [data.length, ...data];
+                break;
+            case Seed.Types.ACCOUNT_KEY:
+                this.data = [data];
+                break;
+            default:
+                throw Error("not implemented");
         }
         this.discriminator = discriminator;
-        this.data = [data];
     }
 
     toBytes() {
-        if (this.discriminator !== Seed.Types.ACCOUNT_KEY) {
-            throw Error("not implemented");
-        }
         return Uint8Array.from([this.discriminator, ...this.data])
     }
 }
@@ -438,7 +645,35 @@ export function get_default_comptoken_mint() {
         isInitialized: true,
         freezeAuthorityOption: 0,
         freezeAuthority: PublicKey.default,
-    }).addExtensions(TLV.TransferHook(compto_transfer_hook_id_pubkey)));
+    }).addExtensions(
+        TLV.TransferHook(compto_transfer_hook_id_pubkey, global_data_account_pubkey),
+        TLV.MetadataPointer(global_data_account_pubkey, comptoken_mint_pubkey),
+        TLV.TokenMetadata({
+            updateAuthority: global_data_account_pubkey,
+            mint: comptoken_mint_pubkey,
+            name: COMPTOKEN_NAME,
+            symbol: COMPTOKEN_SYMBOL,
+            uri: COMPTOKEN_URI,
+            additionalMetadata: [],
+        }),
+    ));
+}
+
+/**
+ * @returns {Object} the canonical bumps the comptoken program finds at initialization
+ */
+export function get_default_bumps() {
+    const bump = (seeds, program_id = compto_program_id_pubkey) => PublicKey.findProgramAddressSync(seeds, program_id)[1];
+    return {
+        globalData: bump([Buffer.from(GLOBAL_DATA_SEED)]),
+        interestBank: bump([Buffer.from(INTEREST_BANK_SEED)]),
+        verifiedHumanUbiBank: bump([Buffer.from(VERIFIED_HUMAN_UBI_BANK_SEED)]),
+        futureUbiBank: bump([Buffer.from(FUTURE_UBI_BANK_SEED)]),
+        interestBankData: bump([interest_bank_account_pubkey.toBytes()]),
+        verifiedHumanUbiBankData: bump([verified_human_ubi_bank_account_pubkey.toBytes()]),
+        futureUbiBankData: bump([future_ubi_bank_account_pubkey.toBytes()]),
+        extraAccountMetas: bump([Buffer.from(EXTRA_ACCOUNT_METAS_SEED), comptoken_mint_pubkey.toBytes()], compto_transfer_hook_id_pubkey),
+    };
 }
 
 /**
@@ -447,6 +682,10 @@ export function get_default_comptoken_mint() {
 export function get_default_global_data() {
     return new GlobalDataAccount(global_data_account_pubkey, BIG_NUMBER, compto_program_id_pubkey,
         new GlobalData({
+            header: get_account_header("GlblData", GlobalData.VERSION),
+            comptokenMint: comptoken_mint_pubkey,
+            transferHookProgram: compto_transfer_hook_id_pubkey,
+            bumps: get_default_bumps(),
             validBlockhashes: {
                 announcedBlockhash: Uint8Array.from({ length: 32 }, (v, i) => i),
                 announcedBlockhashTime: DEFAULT_ANNOUNCE_TIME,
@@ -461,6 +700,15 @@ export function get_default_global_data() {
                 oldestHistoricValue: 0n,
                 historicDistributions: Array.from({ length: GlobalData.DAILY_DISTRIBUTION_HISTORY_SIZE }, (v, i) => [0, 0n]),
             },
+            bankLiabilities: {
+                interestBank: 0n,
+                verifiedHumanUbiBank: 0n,
+                futureUbiBank: 0n,
+                lastDustSweepTime: DEFAULT_DISTRIBUTION_TIME,
+            },
+            // no admin, tests that need one set it
+            admin: PublicKey.default,
+            dayBoundaryOffset: DAY_BOUNDARY_OFFSET,
         }));
 }
 
@@ -517,13 +765,17 @@ export function get_default_unpaid_future_ubi_bank() {
 
 /**
  * @param {PublicKey} address 
+ * @param {number} bump 
  * @returns {UserDataAccount}
  */
-export function get_default_user_data_account(address) {
+export function get_default_user_data_account(address, bump) {
     return new UserDataAccount(address, BIG_NUMBER, compto_program_id_pubkey,
         new UserData({
+            header: get_account_header("UserData", UserData.VERSION),
             lastInterestPayoutDate: DEFAULT_DISTRIBUTION_TIME,
             isVerifiedHuman: false,
+            bump: bump,
+            proofsSorted: true,
             length: 0n,
             recentBlockhash: new Uint8Array(32),
             proofs: Array.from({ length: 8 }, (v, i) => new Uint8Array(32))
@@ -553,6 +805,12 @@ export function get_default_extra_account_metas_account() {
             isSigner: false,
             isWritable: false,
         }),
+        new ExtraAccountMeta({
+            discriminator: 0b1000_0000 | 5, // PDA from other program at index 5
+            addressConfig: seedsToAddressConfig([new Seed(Seed.Types.LITERAL, Buffer.from(EXEMPT_ACCOUNTS_SEED))]), // 1 seed, the exempt accounts seed
+            isSigner: false,
+            isWritable: false,
+        }),
     ];
     let acct = new ExtraAccountMetaAccount(compto_extra_account_metas_account_pubkey, BIG_NUMBER, compto_transfer_hook_id_pubkey,
         new ExtraAccountMetaAccountData({
@@ -563,4 +821,18 @@ export function get_default_extra_account_metas_account() {
             extraAccountsList: { count: extraAccountsMetaList.length, extraAccounts: extraAccountsMetaList, },
         }));
     return acct;
-}
\ No newline at end of file
+}
+/**
+ * @param {PublicKey[]} accounts the exempt comptoken token accounts
+ * @returns {ExemptAccountsAccount}
+ */
+export function get_default_exempt_accounts_account(accounts = []) {
+    accounts = [...accounts].sort((left, right) => Buffer.compare(left.toBuffer(), right.toBuffer()));
+    return new ExemptAccountsAccount(exempt_accounts_account_pubkey, BIG_NUMBER, compto_program_id_pubkey,
+        new ExemptAccounts({
+            header: get_account_header("ExmptAct", ExemptAccounts.VERSION),
+            bump: PublicKey.findProgramAddressSync([Buffer.from(EXEMPT_ACCOUNTS_SEED)], compto_program_id_pubkey)[1],
+            length: BigInt(accounts.length),
+            accounts: accounts,
+        }));
+}
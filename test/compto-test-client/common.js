@@ -7,14 +7,21 @@ import * as bs58_ from "bs58";
 export const bs58 = bs58_.default;
 
 export const DEFAULT_START_TIME = 1_721_940_656n;
-export const DEFAULT_DISTRIBUTION_TIME = 1_721_865_600n; // DEFAULT_START_TIME - DEFAULT_START_TIME % SEC_PER_DAY
-export const DEFAULT_ANNOUNCE_TIME = 1_721_865_300n; // DEFAULT_DISTRIBUTION_TIME - (5 * 60) <-- 5 minutes before distribution
+export const DAY_BOUNDARY_OFFSET = 0n; // the offset the tests initialize the program with
+export const DEFAULT_DISTRIBUTION_TIME = 1_721_865_600n + DAY_BOUNDARY_OFFSET; // DEFAULT_START_TIME - (DEFAULT_START_TIME - DAY_BOUNDARY_OFFSET) % SEC_PER_DAY
+export const DEFAULT_ANNOUNCE_TIME = DEFAULT_DISTRIBUTION_TIME - 300n; // DEFAULT_DISTRIBUTION_TIME - (5 * 60) <-- 5 minutes before distribution
 export const SEC_PER_DAY = 86_400n;
 export const BIG_NUMBER = 1_000_000_000;
 export const COMPTOKEN_DECIMALS = 2; // MAGIC NUMBER: remain consistent with comptoken.rs and common.py
+export const COMPTOKEN_NAME = "Comptoken"; // MAGIC NUMBER: remain consistent with constants.rs
+export const COMPTOKEN_SYMBOL = "CPT"; // MAGIC NUMBER: remain consistent with constants.rs
+export const COMPTOKEN_URI = ""; // MAGIC NUMBER: remain consistent with constants.rs
+export const APR_METADATA_KEY = "apr"; // MAGIC NUMBER: remain consistent with constants.rs
+export const VERIFIED_HUMANS_METADATA_KEY = "verified_humans"; // MAGIC NUMBER: remain consistent with constants.rs
 export const COMPTOKEN_DISTRIBUTION_MULTIPLIER = 146000n; // MAGIC NUMBER: remain consistent with constants.rs
 export const FUTURE_UBI_VERIFIED_HUMANS = 1_000_000_000; // MAGIC NUMBER: remain consistent with constants.rs
 export const MINING_AMOUNT = 10000n; // MAGIC NUMBER: remain consistent with constants.rs
+export const CRANK_REWARD = 100n; // MAGIC NUMBER: remain consistent with constants.rs
 
 // Read Cache Files
 import global_data_account from "../.cache/compto_global_data_account.json" assert { type: "json" };
@@ -30,7 +37,7 @@ import future_ubi_bank_account from "../.cache/compto_future_ubi_bank_account.js
 const future_ubi_bank_account_str = future_ubi_bank_account["address"];
 
 import comptoken_id from "../.cache/comptoken_mint.json" assert { type: "json" };
-const comptoken_mint_str = comptoken_id["commandOutput"]["address"];
+const comptoken_mint_str = comptoken_id["address"];
 
 import compto_program_id from "../.cache/compto_program_id.json" assert { type: "json" };
 const compto_program_id_str = compto_program_id["programId"];
@@ -57,6 +64,32 @@ export const future_ubi_bank_account_pubkey = new PublicKey(bs58.decode(future_u
 export const compto_transfer_hook_id_pubkey = new PublicKey(bs58.decode(compto_transfer_hook_id_str));
 export const compto_extra_account_metas_account_pubkey = new PublicKey(bs58.decode(compto_extra_account_metas_account_str));
 
+// MAGIC NUMBER: the upgradeable loader's program id, which web3.js doesn't export
+export const BPF_LOADER_UPGRADEABLE_PROGRAM_ID = new PublicKey("BPFLoaderUpgradeab1e11111111111111111111111");
+// knows the comptoken program's upgrade authority, who initializes the program and chooses its admin
+export const compto_program_data_pubkey = PublicKey.findProgramAddressSync([compto_program_id_pubkey.toBytes()], BPF_LOADER_UPGRADEABLE_PROGRAM_ID)[0];
+
+export const DISTRIBUTION_RECORD_SEED = "distribution record"; // MAGIC NUMBER: remain consistent with distribution_record.rs
+// MAGIC NUMBERS: remain consistent with constants.rs
+export const GLOBAL_DATA_SEED = "Global Data";
+export const INTEREST_BANK_SEED = "Interest Bank";
+export const VERIFIED_HUMAN_UBI_BANK_SEED = "Verified Human UBI Bank";
+export const FUTURE_UBI_BANK_SEED = "Future UBI Bank";
+export const EXTRA_ACCOUNT_METAS_SEED = "extra-account-metas"; // MAGIC NUMBER: remain consistent with the transfer hook interface
+export const EXEMPT_ACCOUNTS_SEED = "Exempt Accounts"; // MAGIC NUMBER: remain consistent with exempt_accounts.rs
+
+export const exempt_accounts_account_pubkey = PublicKey.findProgramAddressSync([Buffer.from(EXEMPT_ACCOUNTS_SEED)], compto_program_id_pubkey)[0];
+
+/**
+ * @param {BigInt} distribution_time the (normalized) time of the distribution
+ * @returns {PublicKey}
+ */
+export function get_distribution_record_pubkey(distribution_time) {
+    let time_bytes = Buffer.alloc(8);
+    time_bytes.writeBigInt64LE(distribution_time);
+    return PublicKey.findProgramAddressSync([Buffer.from(DISTRIBUTION_RECORD_SEED), time_bytes], compto_program_id_pubkey)[0];
+}
+
 // KeyPair
 let solana_id = JSON.parse(fs.readFileSync(os.homedir() + "/.config/solana/id.json").toString());
 export const me_keypair = Keypair.fromSecretKey(new Uint8Array(solana_id));
@@ -0,0 +1,88 @@
+import { Keypair, PublicKey } from "@solana/web3.js";
+
+import {
+    get_default_comptoken_mint,
+    get_default_comptoken_token_account,
+    get_default_extra_account_metas_account,
+    get_default_global_data,
+    get_default_unpaid_interest_bank,
+    get_default_unpaid_verified_human_ubi_bank,
+    get_default_user_data_account,
+    GlobalDataAccount,
+    TokenAccount,
+} from "../accounts.js";
+import { Assert } from "../assert.js";
+import { BIG_NUMBER, compto_program_id_pubkey, DEFAULT_DISTRIBUTION_TIME, global_data_account_pubkey, SEC_PER_DAY } from "../common.js";
+import { get_account, run_test, setup_test } from "../generic_test.js";
+import { createCloseUserDataAccountInstruction } from "../instruction.js";
+
+/**
+ * @param {boolean} is_verified_human
+ */
+async function setup_close_test(is_verified_human) {
+    const user = Keypair.generate();
+
+    let comptoken_mint = get_default_comptoken_mint();
+    comptoken_mint.data.supply = 292_004n;
+
+    let user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
+    user_comptoken_wallet.data.amount = 2n;
+
+    const [user_data_pda, user_data_bump] = PublicKey.findProgramAddressSync([user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    let user_data_account = get_default_user_data_account(user_data_pda, user_data_bump);
+    user_data_account.data.lastInterestPayoutDate = DEFAULT_DISTRIBUTION_TIME - SEC_PER_DAY;
+    user_data_account.data.isVerifiedHuman = is_verified_human;
+
+    let global_data = get_default_global_data();
+    global_data.data.dailyDistributionData.historicDistributions[0].interestRate = 1.5;
+    global_data.data.dailyDistributionData.oldestHistoricValue = 1n;
+    global_data.data.dailyDistributionData.yesterdaySupply = 292_004n;
+    global_data.data.dailyDistributionData.verifiedHumans = is_verified_human ? 1n : 0n;
+
+    let interest_bank = get_default_unpaid_interest_bank();
+    interest_bank.data.amount = 146_000n;
+
+    const existing_accounts = [
+        comptoken_mint, global_data, interest_bank, get_default_unpaid_verified_human_ubi_bank(), user_comptoken_wallet,
+        user_data_account, get_default_extra_account_metas_account(),
+    ];
+
+    return { user, user_comptoken_wallet, user_data_account, context: await setup_test(existing_accounts) };
+}
+
+async function test_closeUserDataAccount() {
+    let { user, user_comptoken_wallet, user_data_account, context } = await setup_close_test(false);
+    const recipient = PublicKey.unique();
+
+    let instructions = [await createCloseUserDataAccountInstruction(user.publicKey, user_comptoken_wallet.address, recipient)];
+
+    context = await run_test("closeUserDataAccount", context, instructions, [context.payer, user], false, async (context, result) => {
+        const final_user_comptoken_wallet = await get_account(context, user_comptoken_wallet.address, TokenAccount);
+        Assert.assertEqual(final_user_comptoken_wallet.data.amount, 3n, "owed interest is paid before closing");
+
+        Assert.assertEqual(await context.banksClient.getAccount(user_data_account.address), null, "user data is closed");
+        const final_recipient = await context.banksClient.getAccount(recipient);
+        Assert.assertEqual(BigInt(final_recipient.lamports), BigInt(BIG_NUMBER), "recipient gets the user data's lamports");
+    });
+}
+
+async function test_closeVerifiedHumanUserDataAccount() {
+    let { user, user_comptoken_wallet, context } = await setup_close_test(true);
+
+    let instructions = [await createCloseUserDataAccountInstruction(user.publicKey, user_comptoken_wallet.address, context.payer.publicKey)];
+
+    context = await run_test("closeVerifiedHumanUserDataAccountWithoutForfeiting", context, instructions, [context.payer, user], true, async (context, result) => { });
+
+    ({ user, user_comptoken_wallet, context } = await setup_close_test(true));
+    instructions = [await createCloseUserDataAccountInstruction(user.publicKey, user_comptoken_wallet.address, context.payer.publicKey, true)];
+
+    context = await run_test("closeVerifiedHumanUserDataAccount", context, instructions, [context.payer, user], false, async (context, result) => {
+        const final_global_data = await get_account(context, global_data_account_pubkey, GlobalDataAccount);
+        Assert.assertEqual(final_global_data.data.dailyDistributionData.verifiedHumans, 0n, "forfeited verified human is no longer counted");
+    });
+}
+
+(async () => {
+    await test_closeUserDataAccount();
+    await test_closeVerifiedHumanUserDataAccount();
+})();
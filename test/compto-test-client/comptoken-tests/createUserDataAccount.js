@@ -21,22 +21,21 @@ async function test_createUserDataAccount() {
 
     let context = await setup_test(existing_accounts);
 
-    const rent = {
-        getMinimumBalanceForRentExemption: async function (dataLength, commitment) {
-            let rent = await context.banksClient.getRent();
-            return Number(rent.minimumBalance(BigInt(dataLength)));
-        }
-    }
-
     const instructions = [
-        await createCreateUserDataAccountInstruction(rent, UserData.MIN_SIZE, context.payer.publicKey, user.publicKey, original_user_comptoken_wallet.address),
+        await createCreateUserDataAccountInstruction(1, context.payer.publicKey, user.publicKey, original_user_comptoken_wallet.address),
     ];
 
     context = await run_test("createUserDataAccount", context, instructions, [context.payer, user], false, async (context, result) => {
-        const user_data_pda = PublicKey.findProgramAddressSync([original_user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey)[0];
+        const [user_data_pda, user_data_bump] = PublicKey.findProgramAddressSync([original_user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
         const final_user_data_account = await get_account(context, user_data_pda, UserDataAccount);
         Assert.assertEqual(final_user_data_account.data.lastInterestPayoutDate, DEFAULT_DISTRIBUTION_TIME, "user data lastInterestPayoutDate");
         Assert.assert(!final_user_data_account.data.isVerifiedHuman, "user data isVerifiedHuman");
+        Assert.assertEqual(final_user_data_account.data.bump, user_data_bump, "user data bump");
+
+        const packed_user_data_account = await context.banksClient.getAccount(user_data_pda);
+        const rent = await context.banksClient.getRent();
+        Assert.assertEqual(packed_user_data_account.data.length, UserData.MIN_SIZE, "user data size");
+        Assert.assertEqual(BigInt(packed_user_data_account.lamports), rent.minimumBalance(BigInt(UserData.MIN_SIZE)), "the program pays exactly the rent");
     });
 }
 
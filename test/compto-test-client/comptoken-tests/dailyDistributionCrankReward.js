@@ -0,0 +1,52 @@
+import { Keypair, PublicKey } from "@solana/web3.js";
+import { Clock } from "solana-bankrun";
+import {
+    get_default_comptoken_mint,
+    get_default_comptoken_token_account,
+    get_default_global_data,
+    get_default_unpaid_future_ubi_bank,
+    get_default_unpaid_interest_bank,
+    get_default_unpaid_verified_human_ubi_bank,
+    GlobalDataAccount,
+    MintAccount,
+    TokenAccount,
+} from "../accounts.js";
+import { Assert } from "../assert.js";
+import {
+    CRANK_REWARD,
+    DEFAULT_DISTRIBUTION_TIME,
+    DEFAULT_START_TIME,
+    SEC_PER_DAY,
+} from "../common.js";
+import { get_account, run_test, setup_test } from "../generic_test.js";
+import { createDailyDistributionEventInstruction } from "../instruction.js";
+
+async function test_dailyDistributionCrankReward() {
+    const caller = Keypair.generate();
+
+    let original_comptoken_mint = get_default_comptoken_mint();
+    original_comptoken_mint.data.supply = 10_000n;
+    let original_global_data_account = get_default_global_data();
+    let original_caller_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), caller.publicKey);
+
+    const existing_accounts = [
+        original_comptoken_mint, original_global_data_account, get_default_unpaid_interest_bank(),
+        get_default_unpaid_verified_human_ubi_bank(), get_default_unpaid_future_ubi_bank(), original_caller_comptoken_wallet,
+    ];
+
+    // 216_000 is mostly arbitrary, but it should roughly correspond to a days worth of slots
+    let context = await setup_test(existing_accounts, new Clock(216_000n, 0n, 0n, 0n, DEFAULT_START_TIME + SEC_PER_DAY));
+
+    let instructions = [await createDailyDistributionEventInstruction(context.payer.publicKey, DEFAULT_DISTRIBUTION_TIME + SEC_PER_DAY, caller.publicKey, original_caller_comptoken_wallet.address)];
+
+    context = await run_test("dailyDistributionCrankReward", context, instructions, [context.payer, caller], false, async (context, result) => {
+        const final_caller_comptoken_wallet = await get_account(context, original_caller_comptoken_wallet.address, TokenAccount);
+        Assert.assertEqual(final_caller_comptoken_wallet.data.amount, original_caller_comptoken_wallet.data.amount + CRANK_REWARD, "caller is rewarded");
+
+        const final_comptoken_mint = await get_account(context, original_comptoken_mint.address, MintAccount);
+        const final_global_data_account = await get_account(context, original_global_data_account.address, GlobalDataAccount);
+        Assert.assertEqual(final_global_data_account.data.dailyDistributionData.yesterdaySupply, final_comptoken_mint.data.supply, "crank reward is part of the supply");
+    });
+}
+
+(async () => { await test_dailyDistributionCrankReward(); })();
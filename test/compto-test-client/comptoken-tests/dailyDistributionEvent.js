@@ -5,12 +5,15 @@ import {
     get_default_unpaid_future_ubi_bank,
     get_default_unpaid_interest_bank,
     get_default_unpaid_verified_human_ubi_bank,
+    DistributionRecordAccount,
     GlobalDataAccount,
     MintAccount,
 } from "../accounts.js";
 import { Assert } from "../assert.js";
 import {
+    DEFAULT_DISTRIBUTION_TIME,
     DEFAULT_START_TIME,
+    get_distribution_record_pubkey,
     SEC_PER_DAY,
 } from "../common.js";
 import { Distribution, generic_daily_distribution_assertions, get_account, run_test, setup_test, YesterdaysAccounts } from "../generic_test.js";
@@ -34,7 +37,7 @@ async function test_dailyDistributionEvent() {
     // 216_000 is mostly arbitrary, but it should roughly correspond to a days worth of slots
     let context = await setup_test(existing_accounts, new Clock(216_000n, 0n, 0n, 0n, DEFAULT_START_TIME + SEC_PER_DAY));
 
-    let instructions = [await createDailyDistributionEventInstruction()];
+    let instructions = [await createDailyDistributionEventInstruction(context.payer.publicKey, DEFAULT_DISTRIBUTION_TIME + SEC_PER_DAY)];
 
     context = await run_test("dailyDistributionEvent", context, instructions, [context.payer], false, async (context, result) => {
         await generic_daily_distribution_assertions(context, result, yesterdays_accounts, 1n, comptokens_minted, 0n, 0n);
@@ -50,6 +53,19 @@ async function test_dailyDistributionEvent() {
 
         const high_watermark_increase = comptokens_minted;
         const distribution = new Distribution(final_daily_distribution_data, high_watermark_increase, yesterdays_accounts.unpaid_future_ubi_bank.data.amount);
+
+        const distribution_record_account = await get_account(context, get_distribution_record_pubkey(DEFAULT_DISTRIBUTION_TIME + SEC_PER_DAY), DistributionRecordAccount);
+        const distribution_record = distribution_record_account.data;
+        Assert.assertEqual(distribution_record.distributionTime, final_daily_distribution_data.lastDailyDistributionTime, "distribution record time");
+        Assert.assertEqual(distribution_record.supply, original_comptoken_mint.data.supply, "distribution record supply");
+        Assert.assertEqual(distribution_record.highWaterMark, final_daily_distribution_data.highWaterMark, "distribution record high water mark");
+        Assert.assertEqual(distribution_record.highWaterMarkIncrease, high_watermark_increase, "distribution record high water mark increase");
+        Assert.assertEqual(distribution_record.verifiedHumans, 1n, "distribution record verified humans");
+        Assert.assertEqual(
+            distribution_record.interestDistribution + distribution_record.ubiForVerifiedHumans + distribution_record.futureUbiDistribution,
+            final_comptoken_mint.data.supply - original_comptoken_mint.data.supply,
+            "distribution record accounts for all minted comptokens"
+        );
     });
 }
 
@@ -13,6 +13,7 @@ import {
 import { Assert } from "../assert.js";
 import {
     COMPTOKEN_DISTRIBUTION_MULTIPLIER,
+    DEFAULT_DISTRIBUTION_TIME,
     DEFAULT_START_TIME,
     SEC_PER_DAY,
 } from "../common.js";
@@ -60,7 +61,7 @@ export async function testDailyDistributionEvent(inputs) {
     // 216_000 is mostly arbitrary, but it should roughly correspond to a days worth of slots
     let context = await setup_test(existing_accounts, new Clock(216_000n, 0n, 0n, 0n, DEFAULT_START_TIME + SEC_PER_DAY));
 
-    let instructions = [await createDailyDistributionEventInstruction()];
+    let instructions = [await createDailyDistributionEventInstruction(context.payer.publicKey, DEFAULT_DISTRIBUTION_TIME + SEC_PER_DAY)];
 
     context = await run_test(testname, context, instructions, [context.payer], false, async (context, result) => {
         await generic_daily_distribution_assertions(context, result, yesterdays_accounts, 1n, comptokens_minted, 0n, 0n);
@@ -81,7 +82,7 @@ export async function testDailyDistributionEvent(inputs) {
         const distribution_split = (supply_increase / 2n);
         const naive_interest_distribution = distribution_split;
         const total_ubi_distribution = distribution_split;
-        const future_ubi_interest = BigInt(Math.round((Number(naive_interest_distribution) / Number(initial_supply + comptokens_minted)) * Number(initial_unpaid_future_ubi_bank)));
+        const future_ubi_interest = BigInt(Math.floor((Number(naive_interest_distribution) / Number(initial_supply + comptokens_minted)) * Number(initial_unpaid_future_ubi_bank)));
         Assert.assertEqual(
             final_unpaid_interest_bank.data.amount,
             original_unpaid_interest_bank.data.amount + naive_interest_distribution - future_ubi_interest,
@@ -8,7 +8,7 @@ import {
     get_default_unpaid_verified_human_ubi_bank,
 } from "../accounts.js";
 import { Assert } from "../assert.js";
-import { COMPTOKEN_DISTRIBUTION_MULTIPLIER } from "../common.js";
+import { COMPTOKEN_DISTRIBUTION_MULTIPLIER, DEFAULT_DISTRIBUTION_TIME, SEC_PER_DAY } from "../common.js";
 import { DaysParameters, generic_daily_distribution_assertions, run_multiday_test, setup_test, YesterdaysAccounts } from "../generic_test.js";
 import { createDailyDistributionEventInstruction, createTestInstruction } from "../instruction.js";
 import { clamp } from "../utils.js";
@@ -95,7 +95,7 @@ class DefinedMultidayDailyDistributionDaysParameters extends DaysParameters {
         return [this.payer, this.testuser]
     }
     async get_instructions() {
-        return [await createDailyDistributionEventInstruction()];
+        return [await createDailyDistributionEventInstruction(this.payer.publicKey, DEFAULT_DISTRIBUTION_TIME + SEC_PER_DAY * this.day)];
     }
     async get_signers() {
         return [this.payer];
@@ -7,7 +7,7 @@ import {
     MintAccount,
 } from "../accounts.js";
 import { Assert } from "../assert.js";
-import { comptoken_mint_pubkey } from "../common.js";
+import { comptoken_mint_pubkey, DEFAULT_DISTRIBUTION_TIME } from "../common.js";
 import { get_account, run_test, setup_test } from "../generic_test.js";
 import { createDailyDistributionEventInstruction } from "../instruction.js";
 
@@ -21,7 +21,7 @@ async function test_earlyDailyDistributionEvent() {
 
     let context = await setup_test(existing_accounts);
 
-    let instructions = [await createDailyDistributionEventInstruction()];
+    let instructions = [await createDailyDistributionEventInstruction(context.payer.publicKey, DEFAULT_DISTRIBUTION_TIME)];
 
     context = await run_test("earlyDailyDistributionEvent", context, instructions, [context.payer], true, async (context, result) => {
         Assert.assert(
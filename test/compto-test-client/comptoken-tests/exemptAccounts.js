@@ -0,0 +1,75 @@
+import { Keypair, PublicKey, SystemProgram } from "@solana/web3.js";
+
+import { ExemptAccountsAccount, get_default_exempt_accounts_account, get_default_global_data } from "../accounts.js";
+import { Assert } from "../assert.js";
+import { BIG_NUMBER, exempt_accounts_account_pubkey } from "../common.js";
+import { get_account, run_test, setup_test } from "../generic_test.js";
+import { createAddExemptAccountInstruction, createRemoveExemptAccountInstruction } from "../instruction.js";
+
+async function test_addExemptAccount() {
+    const admin = Keypair.generate();
+    let global_data = get_default_global_data();
+    global_data.data.admin = admin.publicKey;
+
+    let context = await setup_test([global_data]);
+
+    const escrows = [PublicKey.unique(), PublicKey.unique()];
+    let instructions = [
+        // the admin pays for the exempt accounts account
+        SystemProgram.transfer({ fromPubkey: context.payer.publicKey, toPubkey: admin.publicKey, lamports: BIG_NUMBER }),
+        ...await Promise.all(escrows.map((escrow) => createAddExemptAccountInstruction(admin.publicKey, escrow))),
+    ];
+
+    context = await run_test("addExemptAccount", context, instructions, [context.payer, admin], false, async (context, result) => {
+        const final_exempt_accounts_account = await get_account(context, exempt_accounts_account_pubkey, ExemptAccountsAccount);
+        const oracle_exempt_accounts = get_default_exempt_accounts_account(escrows).data;
+        Assert.assertEqual(final_exempt_accounts_account.data.bump, oracle_exempt_accounts.bump, "bump");
+        Assert.assertEqual(final_exempt_accounts_account.data.length, 2n, "length");
+        for (const [final, oracle] of oracle_exempt_accounts.accounts.map((v, i) => [final_exempt_accounts_account.data.accounts[i], v])) {
+            Assert.assert(final.equals(oracle), "the exempt accounts are sorted");
+        }
+    });
+}
+
+async function test_removeExemptAccount() {
+    const admin = Keypair.generate();
+    let global_data = get_default_global_data();
+    global_data.data.admin = admin.publicKey;
+    const escrows = [PublicKey.unique(), PublicKey.unique()];
+
+    let context = await setup_test([global_data, get_default_exempt_accounts_account(escrows)]);
+
+    let instructions = [await createRemoveExemptAccountInstruction(admin.publicKey, escrows[0])];
+
+    context = await run_test("removeExemptAccount", context, instructions, [context.payer, admin], false, async (context, result) => {
+        const final_exempt_accounts_account = await get_account(context, exempt_accounts_account_pubkey, ExemptAccountsAccount);
+        Assert.assertEqual(final_exempt_accounts_account.data.length, 1n, "length");
+        Assert.assertEqual(final_exempt_accounts_account.data.accounts.length, 1, "the account shrinks");
+        Assert.assert(final_exempt_accounts_account.data.accounts[0].equals(escrows[1]), "the other account is still exempt");
+    });
+}
+
+async function test_failAddExemptAccountNotAdmin() {
+    const admin = Keypair.generate();
+    const not_admin = Keypair.generate();
+    let global_data = get_default_global_data();
+    global_data.data.admin = admin.publicKey;
+
+    let context = await setup_test([global_data]);
+
+    let instructions = [await createAddExemptAccountInstruction(not_admin.publicKey, PublicKey.unique())];
+
+    context = await run_test("failAddExemptAccountNotAdmin", context, instructions, [context.payer, not_admin], true, async (context, result) => {
+        Assert.assertNotNull(result.result, "program should fail");
+        Assert.assert(
+            result.meta.logMessages.some((msg, i) => msg.includes("only the admin can do this")),
+            "program should have failed b/c the signer isn't the admin"
+        );
+    });
+}
+
+(async () => {
+    await test_addExemptAccount();
+    await test_removeExemptAccount();
+    await test_failAddExemptAccountNotAdmin();
+})();
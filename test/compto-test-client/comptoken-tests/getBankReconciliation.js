@@ -0,0 +1,48 @@
+import {
+    get_default_comptoken_mint,
+    get_default_global_data,
+    get_default_unpaid_future_ubi_bank,
+    get_default_unpaid_interest_bank,
+    get_default_unpaid_verified_human_ubi_bank,
+} from "../accounts.js";
+import { Assert } from "../assert.js";
+import { run_test, setup_test } from "../generic_test.js";
+import { createGetBankReconciliationInstruction } from "../instruction.js";
+
+async function test_getBankReconciliation() {
+    let interest_bank = get_default_unpaid_interest_bank();
+    interest_bank.data.amount = 1_000n;
+    let verified_human_ubi_bank = get_default_unpaid_verified_human_ubi_bank();
+    verified_human_ubi_bank.data.amount = 2_000n;
+    let future_ubi_bank = get_default_unpaid_future_ubi_bank();
+    future_ubi_bank.data.amount = 3_000n;
+
+    let global_data = get_default_global_data();
+    global_data.data.bankLiabilities.interestBank = 900n;
+    global_data.data.bankLiabilities.verifiedHumanUbiBank = 2_500n;
+    global_data.data.bankLiabilities.futureUbiBank = 3_000n;
+
+    const existing_accounts = [
+        get_default_comptoken_mint(), global_data, interest_bank, verified_human_ubi_bank, future_ubi_bank,
+    ];
+
+    let context = await setup_test(existing_accounts);
+
+    let instructions = [await createGetBankReconciliationInstruction()];
+
+    context = await run_test("getBankReconciliation", context, instructions, [context.payer], false, async (context, result) => {
+        // each bank returns (balance: u64, liabilities: u64, surplus: i64)
+        const data = Buffer.from(result.meta.returnData.data);
+        Assert.assertEqual(data.readBigUInt64LE(0), 1_000n, "interest bank balance");
+        Assert.assertEqual(data.readBigUInt64LE(8), 900n, "interest bank liabilities");
+        Assert.assertEqual(data.readBigInt64LE(16), 100n, "interest bank surplus");
+        Assert.assertEqual(data.readBigUInt64LE(24), 2_000n, "verified human ubi bank balance");
+        Assert.assertEqual(data.readBigUInt64LE(32), 2_500n, "verified human ubi bank liabilities");
+        Assert.assertEqual(data.readBigInt64LE(40), -500n, "verified human ubi bank deficit");
+        Assert.assertEqual(data.readBigUInt64LE(48), 3_000n, "future ubi bank balance");
+        Assert.assertEqual(data.readBigUInt64LE(56), 3_000n, "future ubi bank liabilities");
+        Assert.assertEqual(data.readBigInt64LE(64), 0n, "future ubi bank surplus");
+    });
+}
+
+(async () => { await test_getBankReconciliation(); })();
@@ -29,8 +29,8 @@ async function test_getOwedComptokens() {
     let original_user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
     original_user_comptoken_wallet.data.amount = 2n;
 
-    const user_data_pda = PublicKey.findProgramAddressSync([original_user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey)[0];
-    let original_user_data_account = get_default_user_data_account(user_data_pda);
+    const [user_data_pda, user_data_bump] = PublicKey.findProgramAddressSync([original_user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    let original_user_data_account = get_default_user_data_account(user_data_pda, user_data_bump);
     original_user_data_account.data.lastInterestPayoutDate = DEFAULT_DISTRIBUTION_TIME - SEC_PER_DAY;
 
     let global_data = get_default_global_data();
@@ -62,4 +62,55 @@ async function test_getOwedComptokens() {
     });
 }
 
-(async () => { await test_getOwedComptokens(); })();
+async function test_getOwedComptokensVerifiedHuman() {
+    const user = Keypair.generate();
+
+    let comptoken_mint = get_default_comptoken_mint();
+    comptoken_mint.data.supply = 292_004n
+
+    let original_user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
+    original_user_comptoken_wallet.data.amount = 2n;
+
+    const [user_data_pda, user_data_bump] = PublicKey.findProgramAddressSync([original_user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    let original_user_data_account = get_default_user_data_account(user_data_pda, user_data_bump);
+    original_user_data_account.data.lastInterestPayoutDate = DEFAULT_DISTRIBUTION_TIME - SEC_PER_DAY;
+    original_user_data_account.data.isVerifiedHuman = true;
+
+    let global_data = get_default_global_data();
+    global_data.data.dailyDistributionData.historicDistributions[0].interestRate = 1.5;
+    global_data.data.dailyDistributionData.historicDistributions[0].ubiAmount = 10n;
+    global_data.data.dailyDistributionData.oldestHistoricValue = 1n;
+    global_data.data.dailyDistributionData.yesterdaySupply = 292_004n;
+    global_data.data.dailyDistributionData.verifiedHumans = 1n;
+
+    let interest_bank = get_default_unpaid_interest_bank();
+    interest_bank.data.amount = 146_000n;
+
+    let ubi_bank = get_default_unpaid_verified_human_ubi_bank();
+    ubi_bank.data.amount = 146_000n;
+
+    const existing_accounts = [
+        comptoken_mint, global_data, interest_bank, ubi_bank, original_user_comptoken_wallet, original_user_data_account,
+        get_default_extra_account_metas_account(),
+    ];
+
+    let context = await setup_test(existing_accounts);
+
+    let instructions = [await createGetOwedComptokensInstruction(user.publicKey, original_user_comptoken_wallet.address)];
+
+    context = await run_test("getOwedComptokensVerifiedHuman", context, instructions, [context.payer, user], false, async (context, result) => {
+        const final_user_comptoken_wallet = await get_account(context, original_user_comptoken_wallet.address, TokenAccount);
+        Assert.assertEqual(final_user_comptoken_wallet.data.amount, 13n, "interest and ubi amount");
+
+        const final_interest_bank = await get_account(context, interest_bank.address, TokenAccount);
+        Assert.assertEqual(final_interest_bank.data.amount, 145_999n, "interest is paid from the interest bank");
+
+        const final_ubi_bank = await get_account(context, ubi_bank.address, TokenAccount);
+        Assert.assertEqual(final_ubi_bank.data.amount, 145_990n, "ubi is paid from the verified human ubi bank");
+    });
+}
+
+(async () => {
+    await test_getOwedComptokens();
+    await test_getOwedComptokensVerifiedHuman();
+})();
@@ -1,6 +1,5 @@
 import { Keypair, PublicKey } from "@solana/web3.js";
 
-import { getMinimumBalanceForRentExemptAccount } from "@solana/spl-token";
 import { get_default_comptoken_mint, get_default_comptoken_token_account, get_default_global_data, get_default_user_data_account, UserData } from "../accounts.js";
 import { Assert } from "../assert.js";
 import { compto_program_id_pubkey } from "../common.js";
@@ -11,29 +10,26 @@ async function test_growUserDataAccount() {
     const user = Keypair.generate();
 
     const user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
-    const user_data_pda = PublicKey.findProgramAddressSync([user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey)[0];
-    const user_data_account = get_default_user_data_account(user_data_pda);
+    const [user_data_pda, user_data_bump] = PublicKey.findProgramAddressSync([user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    const user_data_account = get_default_user_data_account(user_data_pda, user_data_bump);
 
     const existing_accounts = [
         get_default_comptoken_mint(), get_default_global_data(), user_comptoken_wallet, user_data_account,
     ];
 
     let context = await setup_test(existing_accounts);
-    let connection = {
-        async getMinimumBalanceForRentExemption(dataLength, commitment) {
-            let rent = await context.banksClient.getRent();
-            return rent.minimumBalance(BigInt(dataLength));
-        }
-    }
-
-    const new_user_data_size = BigInt(UserData.MIN_SIZE + 32 * 10);
+
+    const new_user_data_size = UserData.sizeForCapacity(11);
     let instructions = [
-        await createGrowUserDataAccountInstruction(connection, new_user_data_size, context.payer.publicKey, user.publicKey, user_comptoken_wallet.address)
+        await createGrowUserDataAccountInstruction(11, context.payer.publicKey, user.publicKey, user_comptoken_wallet.address)
     ];
 
     context = await run_test("growUserDataAccount", context, instructions, [context.payer, user], false, async (context, result) => {
         const packed_final_user_data_account = await context.banksClient.getAccount(user_data_account.address);
-        Assert.assertEqual(new_user_data_size, BigInt(packed_final_user_data_account.data.length));
+        Assert.assertEqual(new_user_data_size, packed_final_user_data_account.data.length);
+        // the default user data account is funded with far more than its rent, and the excess is refunded
+        const rent = await context.banksClient.getRent();
+        Assert.assertEqual(BigInt(packed_final_user_data_account.lamports), rent.minimumBalance(BigInt(new_user_data_size)), "excess lamports are refunded");
     });
 }
 
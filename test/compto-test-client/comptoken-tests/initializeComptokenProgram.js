@@ -1,22 +1,27 @@
 import { AccountState } from "@solana/spl-token";
 
-import { ExtraAccountMetaAccount, get_default_comptoken_mint, get_default_extra_account_metas_account, GlobalDataAccount, TokenAccount } from "../accounts.js";
+import { ExtensionType, ExtraAccountMetaAccount, get_default_bumps, get_default_extra_account_metas_account, GlobalDataAccount, MintAccount, TLV, TokenAccount } from "../accounts.js";
 import { Assert } from "../assert.js";
 import {
     compto_extra_account_metas_account_pubkey,
+    compto_transfer_hook_id_pubkey,
+    COMPTOKEN_DECIMALS,
     comptoken_mint_pubkey,
+    COMPTOKEN_NAME,
+    COMPTOKEN_SYMBOL,
+    DAY_BOUNDARY_OFFSET,
     DEFAULT_ANNOUNCE_TIME,
     DEFAULT_DISTRIBUTION_TIME,
     global_data_account_pubkey,
     interest_bank_account_pubkey,
     verified_human_ubi_bank_account_pubkey
 } from "../common.js";
-import { get_account, run_test, setup_test } from "../generic_test.js";
+import { comptoken_upgrade_authority, get_account, run_test, setup_test } from "../generic_test.js";
 import { createInitializeComptokenProgramInstruction } from "../instruction.js";
 import { isArrayEqual } from "../utils.js";
 
 async function initialize_comptoken_program() {
-    const existing_accounts = [get_default_comptoken_mint()];
+    const existing_accounts = [];
 
     let context = await setup_test(existing_accounts);
     const connection = {
@@ -26,24 +31,50 @@ async function initialize_comptoken_program() {
         }
     }
 
-    let instructions = [await createInitializeComptokenProgramInstruction(connection, context.payer.publicKey)];
+    let instructions = [await createInitializeComptokenProgramInstruction(context.payer.publicKey, comptoken_upgrade_authority.publicKey)];
 
-    context = await run_test("initializeComptokenProgram", context, instructions, [context.payer], false, async (context, result) => {
+    context = await run_test("initializeComptokenProgram", context, instructions, [context.payer, comptoken_upgrade_authority], false, async (context, result) => {
         const final_global_data = await get_account(context, global_data_account_pubkey, GlobalDataAccount);
+        Assert.assert(final_global_data.data.comptokenMint.equals(comptoken_mint_pubkey), "comptoken mint");
+        Assert.assert(final_global_data.data.transferHookProgram.equals(compto_transfer_hook_id_pubkey), "transfer hook program");
+        Assert.assert(final_global_data.data.admin.equals(comptoken_upgrade_authority.publicKey), "the upgrade authority is the admin");
+        Assert.assertEqual(final_global_data.data.dayBoundaryOffset, DAY_BOUNDARY_OFFSET, "day boundary offset");
+        for (const [name, bump] of Object.entries(get_default_bumps())) {
+            Assert.assertEqual(final_global_data.data.bumps[name], bump, `${name} bump`);
+        }
         Assert.assertEqual(final_global_data.data.validBlockhashes.announcedBlockhashTime, DEFAULT_ANNOUNCE_TIME, "announced blockhash time");
         Assert.assertEqual(final_global_data.data.validBlockhashes.validBlockhashTime, DEFAULT_DISTRIBUTION_TIME, "valid blockhash time");
 
+        const final_mint = await get_account(context, comptoken_mint_pubkey, MintAccount);
+        Assert.assert(final_mint.data.mintAuthority.equals(global_data_account_pubkey), "mint authority");
+        Assert.assertEqual(final_mint.data.freezeAuthorityOption, 0, "freeze authority");
+        Assert.assertEqual(final_mint.data.decimals, COMPTOKEN_DECIMALS, "decimals");
+        Assert.assertEqual(final_mint.data.supply, 0n, "supply");
+        const extension = (type) => final_mint.data.extensions.find((extension) => extension.type === type);
+        const transfer_hook = extension(ExtensionType.TransferHook);
+        const expected_transfer_hook = TLV.TransferHook(compto_transfer_hook_id_pubkey, global_data_account_pubkey);
+        Assert.assert(transfer_hook !== undefined && isArrayEqual(transfer_hook.value, expected_transfer_hook.value), "transfer hook extension");
+        const metadata_pointer = extension(ExtensionType.MetadataPointer);
+        Assert.assert(metadata_pointer !== undefined && isArrayEqual(metadata_pointer.value, Uint8Array.from([...global_data_account_pubkey.toBytes(), ...comptoken_mint_pubkey.toBytes()])), "metadata pointer extension");
+        const metadata = extension(ExtensionType.TokenMetadata).toTokenMetadata();
+        Assert.assertEqual(metadata.name, COMPTOKEN_NAME, "metadata name");
+        Assert.assertEqual(metadata.symbol, COMPTOKEN_SYMBOL, "metadata symbol");
+        Assert.assert(metadata.updateAuthority.equals(global_data_account_pubkey), "metadata update authority");
+
         const final_interest_bank = await get_account(context, interest_bank_account_pubkey, TokenAccount);
         Assert.assertEqual(final_interest_bank.data.amount, 0n, "interest amount");
         Assert.assert(final_interest_bank.data.mint.equals(comptoken_mint_pubkey), "interest mint");
         Assert.assert(final_interest_bank.data.owner.equals(global_data_account_pubkey), "interest owner");
         Assert.assertEqual(final_interest_bank.data.state, AccountState.Initialized, "interest state");
+        const bank_rent = BigInt(await connection.getMinimumBalanceForRentExemption(final_interest_bank.data.getSize()));
+        Assert.assertEqual(BigInt(final_interest_bank.lamports), bank_rent, "interest lamports");
 
         const final_UBI_bank = await get_account(context, verified_human_ubi_bank_account_pubkey, TokenAccount);
         Assert.assertEqual(final_UBI_bank.data.amount, 0n, "ubi amount");
         Assert.assert(final_UBI_bank.data.mint.equals(comptoken_mint_pubkey), "ubi mint");
         Assert.assert(final_UBI_bank.data.owner.equals(global_data_account_pubkey), "ubi owner");
         Assert.assertEqual(final_UBI_bank.data.state, AccountState.Initialized, "ubi state");
+        Assert.assertEqual(BigInt(final_UBI_bank.lamports), bank_rent, "ubi lamports");
 
         const final_extra_account_metas_account = await get_account(context, compto_extra_account_metas_account_pubkey, ExtraAccountMetaAccount);
         // comptoken program id
@@ -60,4 +91,21 @@ async function initialize_comptoken_program() {
     });
 }
 
-(async () => { await initialize_comptoken_program(); })();
+async function test_failInitializeComptokenProgramNotUpgradeAuthority() {
+    let context = await setup_test([]);
+
+    // otherwise anyone could initialize the program first, and so administer it
+    let instructions = [await createInitializeComptokenProgramInstruction(context.payer.publicKey, context.payer.publicKey)];
+
+    context = await run_test("failInitializeComptokenProgramNotUpgradeAuthority", context, instructions, [context.payer], true, async (context, result) => {
+        Assert.assert(
+            result.meta.logMessages.some((msg, i) => msg.includes("only the upgrade authority can do this")),
+            "program should have failed b/c the signer isn't the upgrade authority"
+        );
+    });
+}
+
+(async () => {
+    await initialize_comptoken_program();
+    await test_failInitializeComptokenProgramNotUpgradeAuthority();
+})();
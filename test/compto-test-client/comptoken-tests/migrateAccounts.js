@@ -0,0 +1,172 @@
+import { Keypair, PublicKey } from "@solana/web3.js";
+
+import { get_default_bumps, get_default_comptoken_mint, get_default_comptoken_token_account, get_default_global_data, get_default_unpaid_future_ubi_bank, get_default_unpaid_interest_bank, get_default_unpaid_verified_human_ubi_bank, get_default_user_data_account, GlobalData, GlobalDataAccount, UserData, UserDataAccount } from "../accounts.js";
+import { Assert } from "../assert.js";
+import { compto_program_id_pubkey, global_data_account_pubkey } from "../common.js";
+import { get_account, run_test, setup_test } from "../generic_test.js";
+import { createMigrateGlobalDataInstruction, createMigrateLegacyGlobalDataInstruction, createMigrateUserDataInstruction } from "../instruction.js";
+import { isArrayEqual } from "../utils.js";
+
+async function test_migrateCurrentAccounts() {
+    const user = Keypair.generate();
+
+    const user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
+    const [user_data_pda, user_data_bump] = PublicKey.findProgramAddressSync([user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    const user_data_account = get_default_user_data_account(user_data_pda, user_data_bump);
+
+    const existing_accounts = [
+        get_default_comptoken_mint(), get_default_global_data(), user_comptoken_wallet, user_data_account,
+    ];
+
+    let context = await setup_test(existing_accounts);
+    const original_global_data = await context.banksClient.getAccount(global_data_account_pubkey);
+    const original_user_data = await context.banksClient.getAccount(user_data_pda);
+
+    // the user doesn't need to sign for their data to be migrated
+    let instructions = [
+        await createMigrateGlobalDataInstruction(context.payer.publicKey),
+        await createMigrateUserDataInstruction(context.payer.publicKey, user_comptoken_wallet.address),
+    ];
+
+    context = await run_test("migrateCurrentAccounts", context, instructions, [context.payer], false, async (context, result) => {
+        const final_global_data = await context.banksClient.getAccount(global_data_account_pubkey);
+        const final_user_data = await context.banksClient.getAccount(user_data_pda);
+        Assert.assertEqual(Buffer.compare(original_global_data.data, final_global_data.data), 0, "global data is unchanged");
+        Assert.assertEqual(Buffer.compare(original_user_data.data, final_user_data.data), 0, "user data is unchanged");
+    });
+}
+
+async function test_migrateGlobalDataV1() {
+    // version 1 ended before the admin and the day boundary offset
+    const global_data_account = get_default_global_data();
+    let v1_global_data = global_data_account.toAddedAccount();
+    v1_global_data.info.data = v1_global_data.info.data.slice(0, GlobalData.LAYOUT.span - 40);
+    v1_global_data.info.data[8] = 1;
+
+    const existing_accounts = [get_default_comptoken_mint(), { toAddedAccount: () => v1_global_data }];
+
+    let context = await setup_test(existing_accounts);
+
+    let instructions = [await createMigrateGlobalDataInstruction(context.payer.publicKey)];
+
+    context = await run_test("migrateGlobalDataV1", context, instructions, [context.payer], false, async (context, result) => {
+        const final_global_data_account = await get_account(context, global_data_account_pubkey, GlobalDataAccount);
+        Assert.assertEqual(final_global_data_account.data.header.version, GlobalData.VERSION, "global data is migrated");
+        Assert.assert(final_global_data_account.data.admin.equals(PublicKey.default), "a migrated program has no admin");
+        Assert.assertEqual(final_global_data_account.data.dayBoundaryOffset, 0n, "days start at midnight UTC+0");
+        Assert.assert(final_global_data_account.data.comptokenMint.equals(global_data_account.data.comptokenMint), "the rest is kept");
+    });
+}
+
+async function test_migrateGlobalDataV2() {
+    // version 2 ended before the day boundary offset
+    const global_data_account = get_default_global_data();
+    global_data_account.data.admin = Keypair.generate().publicKey;
+    let v2_global_data = global_data_account.toAddedAccount();
+    v2_global_data.info.data = v2_global_data.info.data.slice(0, GlobalData.LAYOUT.span - 8);
+    v2_global_data.info.data[8] = 2;
+
+    const existing_accounts = [get_default_comptoken_mint(), { toAddedAccount: () => v2_global_data }];
+
+    let context = await setup_test(existing_accounts);
+
+    let instructions = [await createMigrateGlobalDataInstruction(context.payer.publicKey)];
+
+    context = await run_test("migrateGlobalDataV2", context, instructions, [context.payer], false, async (context, result) => {
+        const final_global_data_account = await get_account(context, global_data_account_pubkey, GlobalDataAccount);
+        Assert.assertEqual(final_global_data_account.data.header.version, GlobalData.VERSION, "global data is migrated");
+        Assert.assertEqual(final_global_data_account.data.dayBoundaryOffset, 0n, "days start at midnight UTC+0");
+        Assert.assert(final_global_data_account.data.admin.equals(global_data_account.data.admin), "the admin is kept");
+    });
+}
+
+async function test_migrateLegacyGlobalData() {
+    const global_data_account = get_default_global_data();
+    let legacy_global_data = global_data_account.toAddedAccount();
+    legacy_global_data.info.data = legacy_global_data.info.data.slice(GlobalData.LEGACY_OFFSET, GlobalData.LEGACY_OFFSET + GlobalData.LEGACY_SIZE);
+    const interest_bank = get_default_unpaid_interest_bank();
+    interest_bank.data.amount = 100n;
+
+    const existing_accounts = [
+        get_default_comptoken_mint(), { toAddedAccount: () => legacy_global_data }, interest_bank,
+        get_default_unpaid_verified_human_ubi_bank(), get_default_unpaid_future_ubi_bank(),
+    ];
+
+    let context = await setup_test(existing_accounts);
+
+    let instructions = [await createMigrateLegacyGlobalDataInstruction(context.payer.publicKey)];
+
+    context = await run_test("migrateLegacyGlobalData", context, instructions, [context.payer], false, async (context, result) => {
+        const final_global_data_account = await get_account(context, global_data_account_pubkey, GlobalDataAccount);
+        const final_global_data = final_global_data_account.data;
+        Assert.assertEqual(final_global_data.header.version, GlobalData.VERSION, "global data is migrated");
+        Assert.assert(final_global_data.comptokenMint.equals(global_data_account.data.comptokenMint), "the mint is recorded");
+        Assert.assert(final_global_data.transferHookProgram.equals(global_data_account.data.transferHookProgram), "the transfer hook is read from the mint");
+        Assert.assertEqual(JSON.stringify(final_global_data.bumps), JSON.stringify(get_default_bumps()), "the bumps are found");
+        Assert.assert(isArrayEqual(final_global_data.validBlockhashes.validBlockhash, global_data_account.data.validBlockhashes.validBlockhash), "the legacy data is kept");
+        Assert.assertEqual(final_global_data.bankLiabilities.interestBank, 100n, "the banks' balances are owed");
+        Assert.assert(final_global_data.admin.equals(PublicKey.default), "a migrated program has no admin");
+    });
+}
+
+async function test_migrateLegacyUserData() {
+    const user = Keypair.generate();
+
+    const user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
+    const [user_data_pda, user_data_bump] = PublicKey.findProgramAddressSync([user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    const user_data_account = get_default_user_data_account(user_data_pda, user_data_bump);
+    user_data_account.data.isVerifiedHuman = true;
+    user_data_account.data.length = 1n;
+    user_data_account.data.proofs[0] = new Uint8Array(32).fill(1);
+    // the legacy user data had no header, and the bump was padding
+    let legacy_user_data = user_data_account.toAddedAccount();
+    legacy_user_data.info.data = legacy_user_data.info.data.slice(16);
+    legacy_user_data.info.data.fill(0, 9, 16);
+
+    const existing_accounts = [
+        get_default_comptoken_mint(), get_default_global_data(), user_comptoken_wallet, { toAddedAccount: () => legacy_user_data },
+    ];
+
+    let context = await setup_test(existing_accounts);
+
+    let instructions = [await createMigrateUserDataInstruction(context.payer.publicKey, user_comptoken_wallet.address)];
+
+    context = await run_test("migrateLegacyUserData", context, instructions, [context.payer], false, async (context, result) => {
+        const final_user_data = (await get_account(context, user_data_pda, UserDataAccount)).data;
+        Assert.assertEqual(final_user_data.header.version, UserData.VERSION, "user data is migrated");
+        Assert.assertEqual(final_user_data.bump, user_data_bump, "the bump is found");
+        Assert.assert(final_user_data.isVerifiedHuman, "the legacy data is kept");
+        Assert.assertEqual(final_user_data.length, 1n, "the proofs are kept");
+        Assert.assert(isArrayEqual(final_user_data.proofs[0], new Uint8Array(32).fill(1)), "the proofs are kept");
+        Assert.assert(!final_user_data.proofsSorted, "legacy proofs are sorted on the next insert");
+    });
+}
+
+async function test_migrateNewerUserData() {
+    const user = Keypair.generate();
+
+    const user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
+    const [user_data_pda, user_data_bump] = PublicKey.findProgramAddressSync([user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    const user_data_account = get_default_user_data_account(user_data_pda, user_data_bump);
+    user_data_account.data.header.version += 1;
+
+    const existing_accounts = [
+        get_default_comptoken_mint(), get_default_global_data(), user_comptoken_wallet, user_data_account,
+    ];
+
+    let context = await setup_test(existing_accounts);
+
+    let instructions = [await createMigrateUserDataInstruction(context.payer.publicKey, user_comptoken_wallet.address)];
+
+    // a newer layout can't be migrated back
+    context = await run_test("migrateNewerUserData", context, instructions, [context.payer], true, async (context, result) => { });
+}
+
+(async () => {
+    await test_migrateCurrentAccounts();
+    await test_migrateGlobalDataV1();
+    await test_migrateGlobalDataV2();
+    await test_migrateLegacyGlobalData();
+    await test_migrateLegacyUserData();
+    await test_migrateNewerUserData();
+})();
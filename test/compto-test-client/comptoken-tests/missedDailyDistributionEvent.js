@@ -0,0 +1,50 @@
+import { Clock } from "solana-bankrun";
+import {
+    get_default_comptoken_mint,
+    get_default_global_data,
+    get_default_unpaid_future_ubi_bank,
+    get_default_unpaid_interest_bank,
+    get_default_unpaid_verified_human_ubi_bank,
+    GlobalDataAccount,
+} from "../accounts.js";
+import { Assert } from "../assert.js";
+import {
+    DEFAULT_DISTRIBUTION_TIME,
+    DEFAULT_START_TIME,
+    SEC_PER_DAY,
+} from "../common.js";
+import { get_account, run_test, setup_test } from "../generic_test.js";
+import { createDailyDistributionEventInstruction } from "../instruction.js";
+
+async function test_missedDailyDistributionEvent() {
+    let original_comptoken_mint = get_default_comptoken_mint();
+    original_comptoken_mint.data.supply = 10_000n;
+    let original_global_data_account = get_default_global_data();
+
+    const existing_accounts = [
+        original_comptoken_mint, original_global_data_account, get_default_unpaid_interest_bank(),
+        get_default_unpaid_verified_human_ubi_bank(), get_default_unpaid_future_ubi_bank(),
+    ];
+
+    // nobody called the daily distribution for 2 days
+    let context = await setup_test(existing_accounts, new Clock(648_000n, 0n, 0n, 0n, DEFAULT_START_TIME + 3n * SEC_PER_DAY));
+
+    let instructions = [await createDailyDistributionEventInstruction(context.payer.publicKey, DEFAULT_DISTRIBUTION_TIME + 3n * SEC_PER_DAY)];
+
+    context = await run_test("missedDailyDistributionEvent", context, instructions, [context.payer], false, async (context, result) => {
+        const final_global_data_account = await get_account(context, original_global_data_account.address, GlobalDataAccount);
+        const final_daily_distribution_data = final_global_data_account.data.dailyDistributionData;
+
+        Assert.assertEqual(final_daily_distribution_data.oldestHistoricValue, 3n, "one history entry per day");
+        Assert.assertEqual(final_daily_distribution_data.historicDistributions[0].interestRate, 1, "missed day has no interest");
+        Assert.assertEqual(final_daily_distribution_data.historicDistributions[0].ubiAmount, 0n, "missed day has no ubi");
+        Assert.assertEqual(final_daily_distribution_data.historicDistributions[1].interestRate, 1, "missed day has no interest");
+        Assert.assertEqual(final_daily_distribution_data.historicDistributions[1].ubiAmount, 0n, "missed day has no ubi");
+        Assert.assertEqual(
+            final_daily_distribution_data.lastDailyDistributionTime, DEFAULT_DISTRIBUTION_TIME + 3n * SEC_PER_DAY,
+            "last daily distribution time is today"
+        );
+    });
+}
+
+(async () => { await test_missedDailyDistributionEvent(); })();
@@ -10,7 +10,9 @@ import {
 } from "../accounts.js";
 import { Assert, } from "../assert.js";
 import {
+    DEFAULT_DISTRIBUTION_TIME,
     global_data_account_pubkey,
+    SEC_PER_DAY,
 } from "../common.js";
 import { DaysParameters, generic_daily_distribution_assertions, get_account, run_multiday_test, setup_test, YesterdaysAccounts } from "../generic_test.js";
 import { createDailyDistributionEventInstruction, createTestInstruction } from "../instruction.js";
@@ -56,7 +58,7 @@ class MultidayDailyDistributionDaysParameters extends DaysParameters {
         return [this.payer, this.testuser]
     }
     async get_instructions() {
-        return [await createDailyDistributionEventInstruction()];
+        return [await createDailyDistributionEventInstruction(this.payer.publicKey, DEFAULT_DISTRIBUTION_TIME + SEC_PER_DAY * this.day)];
     }
     async get_signers() {
         return [this.payer];
@@ -7,6 +7,7 @@ import {
     get_default_user_data_account,
     MintAccount,
     TokenAccount,
+    UserData,
     UserDataAccount,
 } from "../accounts.js";
 import { Assert } from "../assert.js";
@@ -22,8 +23,8 @@ async function test_proofSubmission() {
     const original_comptoken_mint = get_default_comptoken_mint();
     const original_global_data_account = get_default_global_data();
     const original_user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
-    const user_data_pda = PublicKey.findProgramAddressSync([original_user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey)[0];
-    const original_user_data_account = get_default_user_data_account(user_data_pda);
+    const [user_data_pda, user_data_bump] = PublicKey.findProgramAddressSync([original_user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    const original_user_data_account = get_default_user_data_account(user_data_pda, user_data_bump);
 
     const accounts = [original_comptoken_mint, original_global_data_account, original_user_comptoken_wallet, original_user_data_account];
 
@@ -56,4 +57,131 @@ async function test_proofSubmission() {
     });
 }
 
-(async () => { await test_proofSubmission(); })();
+function get_full_user_data_account(user_comptoken_wallet, global_data_account) {
+    const [user_data_pda, user_data_bump] = PublicKey.findProgramAddressSync([user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    const user_data_account = get_default_user_data_account(user_data_pda, user_data_bump);
+    user_data_account.data.recentBlockhash = global_data_account.data.validBlockhashes.validBlockhash;
+    user_data_account.data.length = 1n;
+    user_data_account.data.proofs = [new Uint8Array(32)];
+    return user_data_account;
+}
+
+async function test_proofSubmissionGrowsUserData() {
+    const user = Keypair.generate();
+
+    const original_global_data_account = get_default_global_data();
+    const original_user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
+    const original_user_data_account = get_full_user_data_account(original_user_comptoken_wallet, original_global_data_account);
+
+    const accounts = [get_default_comptoken_mint(), original_global_data_account, original_user_comptoken_wallet, original_user_data_account];
+
+    let context = await setup_test(accounts);
+
+    let proof = new ComptokenProof(original_user_comptoken_wallet.address, original_global_data_account.data.validBlockhashes.validBlockhash);
+    proof.mine();
+
+    const growth_step = 3;
+    let instructions = [
+        await createProofSubmissionInstruction(proof, user.publicKey, original_user_comptoken_wallet.address, context.payer.publicKey, growth_step),
+    ];
+
+    context = await run_test("proofSubmissionGrowsUserData", context, instructions, [context.payer, user], false, async (context, result) => {
+        const final_user_data_account = await get_account(context, original_user_data_account.address, UserDataAccount);
+        Assert.assertEqual(final_user_data_account.data.proofs.length, 1 + growth_step, "user data has grown by the step");
+        Assert.assertEqual(final_user_data_account.data.length, 2n, "user data has stored a proof");
+        Assert.assert(isArrayEqual(final_user_data_account.data.proofs[1], proof.hash), "user data has stored the proof submitted");
+
+        const packed_final_user_data_account = await context.banksClient.getAccount(original_user_data_account.address);
+        Assert.assertEqual(UserData.sizeForCapacity(1 + growth_step), packed_final_user_data_account.data.length);
+    });
+}
+
+async function test_failProofSubmissionFullUserData() {
+    const user = Keypair.generate();
+
+    const original_global_data_account = get_default_global_data();
+    const original_user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
+    const original_user_data_account = get_full_user_data_account(original_user_comptoken_wallet, original_global_data_account);
+
+    const accounts = [get_default_comptoken_mint(), original_global_data_account, original_user_comptoken_wallet, original_user_data_account];
+
+    let context = await setup_test(accounts);
+
+    let proof = new ComptokenProof(original_user_comptoken_wallet.address, original_global_data_account.data.validBlockhashes.validBlockhash);
+    proof.mine();
+
+    // without a payer, a full user data account can't grow
+    let instructions = [await createProofSubmissionInstruction(proof, user.publicKey, original_user_comptoken_wallet.address)];
+
+    context = await run_test("failProofSubmissionFullUserData", context, instructions, [context.payer, user], true, async (context, result) => {
+        Assert.assertNotNull(result.result, "program should fail");
+        Assert.assert(
+            result.meta.logMessages.some((msg, i) => msg.includes("User Data Account not large enough")),
+            "program should have failed b/c the user data account is full"
+        );
+    });
+}
+
+async function test_failProofSubmissionPayerWithoutSystemProgram() {
+    const user = Keypair.generate();
+
+    const original_global_data_account = get_default_global_data();
+    const original_user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
+    const original_user_data_account = get_full_user_data_account(original_user_comptoken_wallet, original_global_data_account);
+
+    const accounts = [get_default_comptoken_mint(), original_global_data_account, original_user_comptoken_wallet, original_user_data_account];
+
+    let context = await setup_test(accounts);
+
+    let proof = new ComptokenProof(original_user_comptoken_wallet.address, original_global_data_account.data.validBlockhashes.validBlockhash);
+    proof.mine();
+
+    let instruction = await createProofSubmissionInstruction(proof, user.publicKey, original_user_comptoken_wallet.address, context.payer.publicKey);
+    // the payer needs the system program to fund the growth
+    instruction.keys.pop();
+    let instructions = [instruction];
+
+    context = await run_test("failProofSubmissionPayerWithoutSystemProgram", context, instructions, [context.payer, user], true, async (context, result) => {
+        Assert.assertNotNull(result.result, "program should fail");
+        Assert.assert(
+            result.meta.logMessages.some((msg, i) => msg.includes("insufficient account keys")),
+            "program should have failed b/c the system program is missing"
+        );
+    });
+}
+
+async function test_failProofSubmissionInvalidGrowthStep() {
+    const user = Keypair.generate();
+
+    const original_global_data_account = get_default_global_data();
+    const original_user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
+    const original_user_data_account = get_full_user_data_account(original_user_comptoken_wallet, original_global_data_account);
+
+    const accounts = [get_default_comptoken_mint(), original_global_data_account, original_user_comptoken_wallet, original_user_data_account];
+
+    let context = await setup_test(accounts);
+
+    let proof = new ComptokenProof(original_user_comptoken_wallet.address, original_global_data_account.data.validBlockhashes.validBlockhash);
+    proof.mine();
+
+    // a step of 0 can't make room for the proof
+    let instructions = [
+        await createProofSubmissionInstruction(proof, user.publicKey, original_user_comptoken_wallet.address, context.payer.publicKey, 0),
+    ];
+
+    context = await run_test("failProofSubmissionInvalidGrowthStep", context, instructions, [context.payer, user], true, async (context, result) => {
+        Assert.assertNotNull(result.result, "program should fail");
+        Assert.assert(
+            result.meta.logMessages.some((msg, i) => msg.includes("the growth step must be between")),
+            "program should have failed b/c the growth step is 0"
+        );
+    });
+}
+
+(async () => {
+    await test_proofSubmission();
+    await test_proofSubmissionGrowsUserData();
+    await test_failProofSubmissionFullUserData();
+    await test_failProofSubmissionPayerWithoutSystemProgram();
+    await test_failProofSubmissionInvalidGrowthStep();
+})();
@@ -7,6 +7,7 @@ import {
     get_default_unpaid_interest_bank,
     get_default_unpaid_verified_human_ubi_bank,
 } from "../accounts.js";
+import { DEFAULT_DISTRIBUTION_TIME, SEC_PER_DAY } from "../common.js";
 import { DaysParameters, generic_daily_distribution_assertions, run_multiday_test, setup_test, YesterdaysAccounts } from "../generic_test.js";
 import { createDailyDistributionEventInstruction, createTestInstruction } from "../instruction.js";
 import { debug } from "../parse_args.js";
@@ -42,7 +43,7 @@ class RandomMultidayDailyDistributionDaysParameters extends DaysParameters {
         return [this.payer, this.testuser]
     }
     async get_instructions() {
-        return [await createDailyDistributionEventInstruction()];
+        return [await createDailyDistributionEventInstruction(this.payer.publicKey, DEFAULT_DISTRIBUTION_TIME + SEC_PER_DAY * this.day)];
     }
     async get_signers() {
         return [this.payer];
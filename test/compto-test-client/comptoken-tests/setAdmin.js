@@ -0,0 +1,43 @@
+import { Keypair } from "@solana/web3.js";
+
+import { get_default_global_data, GlobalDataAccount } from "../accounts.js";
+import { Assert } from "../assert.js";
+import { global_data_account_pubkey } from "../common.js";
+import { comptoken_upgrade_authority, get_account, run_test, setup_test } from "../generic_test.js";
+import { createSetAdminInstruction } from "../instruction.js";
+
+async function test_setAdmin() {
+    // e.g. migrated from before the admin was recorded, so there is none
+    let context = await setup_test([get_default_global_data()]);
+
+    const admin = Keypair.generate();
+    let instructions = [await createSetAdminInstruction(comptoken_upgrade_authority.publicKey, admin.publicKey)];
+
+    context = await run_test("setAdmin", context, instructions, [context.payer, comptoken_upgrade_authority], false, async (context, result) => {
+        const final_global_data = await get_account(context, global_data_account_pubkey, GlobalDataAccount);
+        Assert.assert(final_global_data.data.admin.equals(admin.publicKey), "the admin is set");
+    });
+}
+
+async function test_failSetAdminNotUpgradeAuthority() {
+    const admin = Keypair.generate();
+    let global_data = get_default_global_data();
+    global_data.data.admin = admin.publicKey;
+
+    let context = await setup_test([global_data]);
+
+    // not even the admin may choose the next admin
+    let instructions = [await createSetAdminInstruction(admin.publicKey, Keypair.generate().publicKey)];
+
+    context = await run_test("failSetAdminNotUpgradeAuthority", context, instructions, [context.payer, admin], true, async (context, result) => {
+        Assert.assert(
+            result.meta.logMessages.some((msg, i) => msg.includes("only the upgrade authority can do this")),
+            "program should have failed b/c the signer isn't the upgrade authority"
+        );
+    });
+}
+
+(async () => {
+    await test_setAdmin();
+    await test_failSetAdminNotUpgradeAuthority();
+})();
@@ -2,40 +2,90 @@ import { Keypair, PublicKey } from "@solana/web3.js";
 
 import { get_default_comptoken_mint, get_default_comptoken_token_account, get_default_global_data, get_default_user_data_account, UserData } from "../accounts.js";
 import { Assert } from "../assert.js";
-import { compto_program_id_pubkey } from "../common.js";
+import { BIG_NUMBER, compto_program_id_pubkey } from "../common.js";
 import { run_test, setup_test } from "../generic_test.js";
-import { createGrowUserDataAccountInstruction } from "../instruction.js";
+import { createGrowUserDataAccountInstruction, createShrinkUserDataAccountInstruction } from "../instruction.js";
 
-async function test_failShrinkUserDataAccount() {
+function get_user_accounts(user) {
+    const user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
+    const [user_data_pda, user_data_bump] = PublicKey.findProgramAddressSync([user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    const user_data_account = get_default_user_data_account(user_data_pda, user_data_bump);
+    return { user_comptoken_wallet, user_data_account };
+}
+
+async function test_shrinkUserDataAccount() {
     const user = Keypair.generate();
+    const { user_comptoken_wallet, user_data_account } = get_user_accounts(user);
+    // proofs for a blockhash that is no longer valid don't need to be kept
+    user_data_account.data.length = 3n;
+    const global_data_account = get_default_global_data();
+    const accounts = [get_default_comptoken_mint(), global_data_account, user_comptoken_wallet, user_data_account];
 
-    const user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
-    const user_data_pda = PublicKey.findProgramAddressSync([user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey)[0];
-    const user_data_account = get_default_user_data_account(user_data_pda);
+    let context = await setup_test(accounts);
+
+    // the default user data account holds 8 proofs
+    const new_user_data_size = UserData.sizeForCapacity(1);
+    let instructions = [
+        await createShrinkUserDataAccountInstruction(1, user.publicKey, user_comptoken_wallet.address),
+    ];
+
+    context = await run_test("shrinkUserDataAccount", context, instructions, [context.payer, user], false, async (context, result) => {
+        const packed_final_user_data_account = await context.banksClient.getAccount(user_data_account.address);
+        Assert.assertEqual(new_user_data_size, packed_final_user_data_account.data.length);
+        const rent = await context.banksClient.getRent();
+        const user_data_rent = rent.minimumBalance(BigInt(new_user_data_size));
+        Assert.assertEqual(BigInt(packed_final_user_data_account.lamports), user_data_rent, "excess lamports are refunded");
+        const final_user_wallet = await context.banksClient.getAccount(user.publicKey);
+        Assert.assertEqual(BigInt(final_user_wallet.lamports), BigInt(BIG_NUMBER) - user_data_rent, "the wallet receives the excess");
+    });
+}
+
+async function test_failShrinkUserDataAccountBelowProofs() {
+    const user = Keypair.generate();
+    const { user_comptoken_wallet, user_data_account } = get_user_accounts(user);
+    const global_data_account = get_default_global_data();
+    user_data_account.data.length = 3n;
+    user_data_account.data.recentBlockhash = global_data_account.data.validBlockhashes.validBlockhash;
+    const accounts = [get_default_comptoken_mint(), global_data_account, user_comptoken_wallet, user_data_account];
+
+    let context = await setup_test(accounts);
+
+    let instructions = [
+        await createShrinkUserDataAccountInstruction(2, user.publicKey, user_comptoken_wallet.address),
+    ];
+
+    context = await run_test("failShrinkUserDataAccountBelowProofs", context, instructions, [context.payer, user], true, async (context, result) => {
+        Assert.assertNotNull(result.result, "program should fail");
+        Assert.assert(
+            result.meta.logMessages.some((msg, i) => msg.includes("user data can't shrink below the proofs it holds")),
+            "program should have failed b/c the proofs for the current blockhash wouldn't fit"
+        );
+    });
+}
+
+async function test_failShrinkUserDataAccount() {
+    const user = Keypair.generate();
+    const { user_comptoken_wallet, user_data_account } = get_user_accounts(user);
     const accounts = [get_default_comptoken_mint(), get_default_global_data(), user_comptoken_wallet, user_data_account];
 
     let context = await setup_test(accounts);
-    let connection = {
-        async getMinimumBalanceForRentExemption(dataLength, commitment) {
-            let rent = await context.banksClient.getRent();
-            return rent.minimumBalance(BigInt(dataLength));
-        }
-    }
-
-    let new_user_data_size = BigInt(UserData.MIN_SIZE);
+
+    // the default user data account holds 8 proofs
     let instructions = [
-        await createGrowUserDataAccountInstruction(
-            connection, new_user_data_size, context.payer.publicKey, user.publicKey, user_comptoken_wallet.address
-        ),
+        await createGrowUserDataAccountInstruction(1, context.payer.publicKey, user.publicKey, user_comptoken_wallet.address),
     ];
 
     context = await run_test("failShrinkUserDataAccount", context, instructions, [context.payer, user], true, async (context, result) => {
         Assert.assertNotNull(result.result, "program should fail");
         Assert.assert(
-            result.meta.logMessages.some((msg, i) => msg.includes("assertion failed: user_data_account.data_len() < new_size")),
+            result.meta.logMessages.some((msg, i) => msg.includes("user data can only grow")),
             "program should have failed b/c it wouldn't shrink"
         );
     });
 }
 
-(async () => { await test_failShrinkUserDataAccount(); })();
+(async () => {
+    await test_shrinkUserDataAccount();
+    await test_failShrinkUserDataAccountBelowProofs();
+    await test_failShrinkUserDataAccount();
+})();
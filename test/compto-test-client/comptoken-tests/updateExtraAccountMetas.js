@@ -0,0 +1,110 @@
+import { Keypair, SystemProgram } from "@solana/web3.js";
+
+import {
+    ExtraAccountMeta,
+    ExtraAccountMetaAccount,
+    get_default_comptoken_mint,
+    get_default_extra_account_metas_account,
+    get_default_global_data,
+} from "../accounts.js";
+import { Assert } from "../assert.js";
+import { BIG_NUMBER, compto_extra_account_metas_account_pubkey } from "../common.js";
+import { get_account, run_test, setup_test } from "../generic_test.js";
+import { createMigrateExtraAccountMetasInstruction, createUpdateExtraAccountMetasInstruction } from "../instruction.js";
+import { isArrayEqual } from "../utils.js";
+
+/**
+ * @returns {ExtraAccountMeta}
+ */
+function get_config_account_meta() {
+    return new ExtraAccountMeta({
+        discriminator: 0, // Literal
+        addressConfig: Keypair.generate().publicKey.toBytes(),
+        isSigner: false,
+        isWritable: false,
+    });
+}
+
+async function test_updateExtraAccountMetas() {
+    const admin = Keypair.generate();
+    let global_data = get_default_global_data();
+    global_data.data.admin = admin.publicKey;
+    const default_account_meta_list = get_default_extra_account_metas_account();
+
+    let context = await setup_test([get_default_comptoken_mint(), global_data, default_account_meta_list]);
+
+    const config_account_meta = get_config_account_meta();
+    let instructions = [
+        // the admin pays for the longer list
+        SystemProgram.transfer({ fromPubkey: context.payer.publicKey, toPubkey: admin.publicKey, lamports: BIG_NUMBER }),
+        await createUpdateExtraAccountMetasInstruction(admin.publicKey, [config_account_meta]),
+    ];
+
+    context = await run_test("updateExtraAccountMetas", context, instructions, [context.payer, admin], false, async (context, result) => {
+        const final_extra_account_meta_list_account = await get_account(context, compto_extra_account_metas_account_pubkey, ExtraAccountMetaAccount);
+        const final_extra_accounts = final_extra_account_meta_list_account.data.extraAccountsList.extraAccounts;
+        const default_extra_accounts = default_account_meta_list.data.extraAccountsList.extraAccounts;
+        Assert.assertEqual(final_extra_accounts.length, default_extra_accounts.length + 1, "the account is added");
+        for (const [final, oracle] of default_extra_accounts.map((v, i) => [final_extra_accounts[i], v])) {
+            Assert.assert(isArrayEqual(final.addressConfig, oracle.addressConfig), "the comptoken accounts come first");
+        }
+        Assert.assert(
+            isArrayEqual(final_extra_accounts[default_extra_accounts.length].addressConfig, config_account_meta.addressConfig),
+            "the added account comes last"
+        );
+    });
+}
+
+async function test_failUpdateExtraAccountMetasNotAdmin() {
+    const admin = Keypair.generate();
+    const not_admin = Keypair.generate();
+    let global_data = get_default_global_data();
+    global_data.data.admin = admin.publicKey;
+
+    let context = await setup_test([get_default_comptoken_mint(), global_data, get_default_extra_account_metas_account()]);
+
+    let instructions = [await createUpdateExtraAccountMetasInstruction(not_admin.publicKey, [get_config_account_meta()])];
+
+    context = await run_test("failUpdateExtraAccountMetasNotAdmin", context, instructions, [context.payer, not_admin], true, async (context, result) => {
+        Assert.assertNotNull(result.result, "program should fail");
+        Assert.assert(
+            result.meta.logMessages.some((msg, i) => msg.includes("only the admin can do this")),
+            "program should have failed b/c the signer isn't the admin"
+        );
+    });
+}
+
+async function test_migrateLegacyExtraAccountMetas() {
+    const default_account_meta_list = get_default_extra_account_metas_account();
+    const default_extra_accounts = default_account_meta_list.data.extraAccountsList.extraAccounts;
+    // before the exempt accounts, the list held the comptoken program and the sender's and receiver's user data,
+    // followed by the accounts the admin added
+    const config_account_meta = get_config_account_meta();
+    let legacy_account_meta_list = get_default_extra_account_metas_account();
+    legacy_account_meta_list.data.extraAccountsList.extraAccounts = [...default_extra_accounts.slice(0, 3), config_account_meta];
+
+    let context = await setup_test([get_default_comptoken_mint(), get_default_global_data(), legacy_account_meta_list]);
+
+    // anyone may migrate
+    let instructions = [await createMigrateExtraAccountMetasInstruction(context.payer.publicKey)];
+
+    context = await run_test("migrateLegacyExtraAccountMetas", context, instructions, [context.payer], false, async (context, result) => {
+        const final_extra_account_meta_list_account = await get_account(context, compto_extra_account_metas_account_pubkey, ExtraAccountMetaAccount);
+        const final_extra_accounts = final_extra_account_meta_list_account.data.extraAccountsList.extraAccounts;
+        Assert.assertEqual(final_extra_accounts.length, default_extra_accounts.length + 1, "the exempt accounts are added");
+        for (const [final, oracle] of default_extra_accounts.map((v, i) => [final_extra_accounts[i], v])) {
+            Assert.assertEqual(final.discriminator, oracle.discriminator, "the comptoken accounts come first");
+            Assert.assert(isArrayEqual(final.addressConfig, oracle.addressConfig), "the comptoken accounts come first");
+        }
+        Assert.assert(
+            isArrayEqual(final_extra_accounts[default_extra_accounts.length].addressConfig, config_account_meta.addressConfig),
+            "the admin's account is kept"
+        );
+    });
+}
+
+(async () => {
+    await test_updateExtraAccountMetas();
+    await test_failUpdateExtraAccountMetasNotAdmin();
+    await test_migrateLegacyExtraAccountMetas();
+})();
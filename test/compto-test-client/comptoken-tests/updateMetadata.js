@@ -0,0 +1,137 @@
+import { Keypair, SystemProgram } from "@solana/web3.js";
+import { Clock } from "solana-bankrun";
+
+import {
+    ExtensionType,
+    get_default_comptoken_mint,
+    get_default_global_data,
+    get_default_unpaid_future_ubi_bank,
+    get_default_unpaid_interest_bank,
+    get_default_unpaid_verified_human_ubi_bank,
+    MintAccount,
+    TLV,
+} from "../accounts.js";
+import { Assert } from "../assert.js";
+import {
+    APR_METADATA_KEY,
+    BIG_NUMBER,
+    COMPTOKEN_NAME,
+    comptoken_mint_pubkey,
+    DEFAULT_DISTRIBUTION_TIME,
+    DEFAULT_START_TIME,
+    SEC_PER_DAY,
+    VERIFIED_HUMANS_METADATA_KEY,
+} from "../common.js";
+import { get_account, run_test, setup_test } from "../generic_test.js";
+import {
+    createDailyDistributionEventInstruction,
+    createRemoveMetadataKeyInstruction,
+    createUpdateMetadataFieldInstruction,
+} from "../instruction.js";
+
+/**
+ * @param {[string, string][]} additionalMetadata
+ * @returns {MintAccount}
+ */
+function get_mint_with_additional_metadata(additionalMetadata) {
+    let mint = get_default_comptoken_mint();
+    mint.data.extensions = mint.data.extensions.map((extension) => extension.type === ExtensionType.TokenMetadata
+        ? TLV.TokenMetadata({ ...extension.toTokenMetadata(), additionalMetadata })
+        : extension);
+    return mint;
+}
+
+/**
+ * @param {ProgramTestContext} context
+ */
+async function get_token_metadata(context) {
+    const mint = await get_account(context, comptoken_mint_pubkey, MintAccount);
+    return mint.data.extensions.find((extension) => extension.type === ExtensionType.TokenMetadata).toTokenMetadata();
+}
+
+async function test_updateMetadataField() {
+    const admin = Keypair.generate();
+    let global_data = get_default_global_data();
+    global_data.data.admin = admin.publicKey;
+
+    let context = await setup_test([get_default_comptoken_mint(), global_data]);
+
+    let instructions = [
+        // the admin pays for the larger metadata
+        SystemProgram.transfer({ fromPubkey: context.payer.publicKey, toPubkey: admin.publicKey, lamports: BIG_NUMBER }),
+        await createUpdateMetadataFieldInstruction(admin.publicKey, "uri", "https://compto.com/metadata.json"),
+        await createUpdateMetadataFieldInstruction(admin.publicKey, "website", "https://compto.com"),
+    ];
+
+    context = await run_test("updateMetadataField", context, instructions, [context.payer, admin], false, async (context, result) => {
+        const metadata = await get_token_metadata(context);
+        Assert.assertEqual(metadata.name, COMPTOKEN_NAME, "name is unchanged");
+        Assert.assertEqual(metadata.uri, "https://compto.com/metadata.json", "uri is updated");
+        Assert.assertEqual(metadata.additionalMetadata.length, 1, "the custom field is added");
+        Assert.assertEqual(metadata.additionalMetadata[0][0], "website", "custom key");
+        Assert.assertEqual(metadata.additionalMetadata[0][1], "https://compto.com", "custom value");
+    });
+}
+
+async function test_failUpdateMetadataFieldNotAdmin() {
+    const admin = Keypair.generate();
+    const not_admin = Keypair.generate();
+    let global_data = get_default_global_data();
+    global_data.data.admin = admin.publicKey;
+
+    let context = await setup_test([get_default_comptoken_mint(), global_data]);
+
+    let instructions = [await createUpdateMetadataFieldInstruction(not_admin.publicKey, "name", "Not Comptoken")];
+
+    context = await run_test("failUpdateMetadataFieldNotAdmin", context, instructions, [context.payer, not_admin], true, async (context, result) => {
+        Assert.assertNotNull(result.result, "program should fail");
+        Assert.assert(
+            result.meta.logMessages.some((msg, i) => msg.includes("only the admin can do this")),
+            "program should have failed b/c the signer isn't the admin"
+        );
+    });
+}
+
+async function test_removeMetadataKey() {
+    const admin = Keypair.generate();
+    let global_data = get_default_global_data();
+    global_data.data.admin = admin.publicKey;
+
+    let context = await setup_test([get_mint_with_additional_metadata([["website", "https://compto.com"]]), global_data]);
+
+    let instructions = [await createRemoveMetadataKeyInstruction(admin.publicKey, "website")];
+
+    context = await run_test("removeMetadataKey", context, instructions, [context.payer, admin], false, async (context, result) => {
+        const metadata = await get_token_metadata(context);
+        Assert.assertEqual(metadata.additionalMetadata.length, 0, "the custom field is removed");
+    });
+}
+
+async function test_dailyDistributionRefreshesMetadata() {
+    // the admin opts into the refresh by adding the keys
+    let mint = get_mint_with_additional_metadata([[APR_METADATA_KEY, ""], [VERIFIED_HUMANS_METADATA_KEY, ""]]);
+    mint.data.supply = 10_000n;
+    const existing_accounts = [
+        mint, get_default_global_data(), get_default_unpaid_interest_bank(),
+        get_default_unpaid_verified_human_ubi_bank(), get_default_unpaid_future_ubi_bank(),
+    ];
+
+    // 216_000 is mostly arbitrary, but it should roughly correspond to a days worth of slots
+    let context = await setup_test(existing_accounts, new Clock(216_000n, 0n, 0n, 0n, DEFAULT_START_TIME + SEC_PER_DAY));
+
+    let instructions = [await createDailyDistributionEventInstruction(context.payer.publicKey, DEFAULT_DISTRIBUTION_TIME + SEC_PER_DAY)];
+
+    context = await run_test("dailyDistributionRefreshesMetadata", context, instructions, [context.payer], false, async (context, result) => {
+        const metadata = new Map((await get_token_metadata(context)).additionalMetadata);
+        const apr = metadata.get(APR_METADATA_KEY);
+        Assert.assert(apr.endsWith("%") && apr !== "0.00%", `the apr is refreshed, not ${apr}`);
+        Assert.assertEqual(metadata.get(VERIFIED_HUMANS_METADATA_KEY), "0", "the verified humans are refreshed");
+    });
+}
+
+(async () => {
+    await test_updateMetadataField();
+    await test_failUpdateMetadataFieldNotAdmin();
+    await test_removeMetadataKey();
+    await test_dailyDistributionRefreshesMetadata();
+})();
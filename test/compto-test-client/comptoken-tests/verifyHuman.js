@@ -25,8 +25,8 @@ async function testVerifyHuman() {
     original_unpaid_future_ubi_bank.data.amount = 1_000_000_000n;
     const original_user_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user.publicKey);
     console.log(original_user_comptoken_wallet);
-    const user_data_pda = PublicKey.findProgramAddressSync([original_user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey)[0];
-    const original_user_data_account = get_default_user_data_account(user_data_pda);
+    const [user_data_pda, user_data_bump] = PublicKey.findProgramAddressSync([original_user_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    const original_user_data_account = get_default_user_data_account(user_data_pda, user_data_bump);
     console.log(original_user_data_account);
 
     const existing_accounts = [
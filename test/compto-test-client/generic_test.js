@@ -1,3 +1,5 @@
+import fs from "node:fs";
+import path from "node:path";
 import { format } from "node:util";
 
 import { Keypair, PublicKey, Transaction, TransactionInstruction } from "@solana/web3.js";
@@ -17,6 +19,8 @@ import {
 } from "./accounts.js";
 import { Assert, AssertionError } from "./assert.js";
 import {
+    BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
+    compto_program_data_pubkey,
     compto_program_id_pubkey,
     compto_transfer_hook_id_pubkey,
     COMPTOKEN_DISTRIBUTION_MULTIPLIER,
@@ -142,7 +146,7 @@ export class Distribution {
         let future_ubi_distribution = ubi_distribution - verified_human_ubi_distribution;
         debug("future_ubi before UBI Interest: %d", future_ubi_distribution);
 
-        let future_ubi_interest = BigInt(round_ties_even(Number(unpaid_future_ubi_amount) * interest_rate));
+        let future_ubi_interest = BigInt(Math.floor(Number(unpaid_future_ubi_amount) * interest_rate));
         future_ubi_distribution += future_ubi_interest;
         interest_distribution -= future_ubi_interest;
 
@@ -385,6 +389,35 @@ function float_equals(a, b) {
     return Math.abs(a - b) < epsilon;
 }
 
+// of the comptoken program, so the only one who may initialize it and choose its admin
+export const comptoken_upgrade_authority = Keypair.generate();
+
+/**
+ * deploys comptoken.so from SBF_OUT_DIR like `solana program deploy` would, so that it has program data with an
+ * upgrade authority
+ * @returns {AddedAccount[]}
+ */
+function get_upgradeable_comptoken_program_accounts() {
+    const elf = fs.readFileSync(path.join(process.env.SBF_OUT_DIR, "comptoken.so"));
+    // MAGIC NUMBERS: the serialized layouts of the upgradeable loader's Program and ProgramData states
+    let program = Buffer.alloc(36);
+    program.writeUInt32LE(2, 0);
+    program.set(compto_program_data_pubkey.toBytes(), 4);
+    let program_data = Buffer.alloc(45 + elf.length);
+    program_data.writeUInt32LE(3, 0);
+    // deployed at slot 0, with an upgrade authority
+    program_data.writeUInt8(1, 12);
+    program_data.set(comptoken_upgrade_authority.publicKey.toBytes(), 13);
+    program_data.set(elf, 45);
+
+    // MAGIC NUMBER: rent exemption is 6960 lamports per byte, counting 128 bytes for the account itself
+    const account = (address, data, executable) => ({
+        address,
+        info: { lamports: (data.length + 128) * 6960, data, owner: BPF_LOADER_UPGRADEABLE_PROGRAM_ID, executable },
+    });
+    return [account(compto_program_id_pubkey, program, true), account(compto_program_data_pubkey, program_data, false)];
+}
+
 /**
  * @param {Account[]} existing_accounts 
  * @param {Clock} clock
@@ -393,10 +426,12 @@ function float_equals(a, b) {
 export async function setup_test(existing_accounts, clock = new Clock(0n, 0n, 0n, 0n, DEFAULT_START_TIME)) {
     let context = await start(
         [
-            { name: "comptoken", programId: compto_program_id_pubkey },
             { name: "comptoken_transfer_hook", programId: compto_transfer_hook_id_pubkey },
         ],
-        existing_accounts.map((account, i) => account.toAddedAccount()),
+        [
+            ...get_upgradeable_comptoken_program_accounts(),
+            ...existing_accounts.map((account, i) => account.toAddedAccount()),
+        ],
     );
     context.setClock(clock);
 
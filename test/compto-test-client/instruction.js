@@ -1,13 +1,17 @@
 import { Connection, PublicKey, SystemProgram, SYSVAR_SLOT_HASHES_PUBKEY, TransactionInstruction } from "@solana/web3.js";
 
 import { TOKEN_2022_PROGRAM_ID } from "@solana/spl-token";
-import { COMPTOKEN_WALLET_SIZE, GlobalData } from "./accounts.js";
+import { ExtraAccountMeta } from "./accounts.js";
 import {
     compto_extra_account_metas_account_pubkey,
+    compto_program_data_pubkey,
     compto_program_id_pubkey,
     compto_transfer_hook_id_pubkey,
     comptoken_mint_pubkey,
+    DAY_BOUNDARY_OFFSET,
+    exempt_accounts_account_pubkey,
     future_ubi_bank_account_pubkey,
+    get_distribution_record_pubkey,
     global_data_account_pubkey,
     interest_bank_account_pubkey,
     verified_human_ubi_bank_account_pubkey,
@@ -25,6 +29,19 @@ export const Instruction = {
     GET_OWED_COMPTOKENS: 6,
     GROW_USER_DATA_ACCOUNT: 7,
     VERIFY_HUMAN: 8,
+    GET_BANK_RECONCILIATION: 9,
+    MIGRATE_GLOBAL_DATA: 10,
+    MIGRATE_USER_DATA: 11,
+    CLOSE_USER_DATA_ACCOUNT: 12,
+    SHRINK_USER_DATA_ACCOUNT: 13,
+    UPDATE_METADATA_FIELD: 14,
+    REMOVE_METADATA_KEY: 15,
+    UPDATE_EXTRA_ACCOUNT_METAS: 16,
+    ADD_EXEMPT_ACCOUNT: 17,
+    REMOVE_EXEMPT_ACCOUNT: 18,
+    MIGRATE_LEGACY_GLOBAL_DATA: 19,
+    SET_ADMIN: 20,
+    MIGRATE_EXTRA_ACCOUNT_METAS: 21,
     TEST: 255,
 };
 
@@ -54,14 +71,26 @@ export function createTestInstruction(user_wallet_address, user_comptoken_token_
 }
 
 /**
+ * with a payer, the user data account is grown if it has no room for the proof
  * @param {ComptokenProof} comptoken_proof
  * @param {PublicKey} user_wallet_address
  * @param {PublicKey} user_comptoken_token_account_address
- * @param {PublicKey} destination_data_account_address
+ * @param {PublicKey | null} payer_address pays the rent if the user data account needs to grow
+ * @param {number | null} growth_step how many proofs to grow the user data account by, instead of the program's default
  * @returns {TransactionInstruction}
  */
-export async function createProofSubmissionInstruction(comptoken_proof, user_wallet_address, user_comptoken_token_account_address) {
+export async function createProofSubmissionInstruction(comptoken_proof, user_wallet_address, user_comptoken_token_account_address, payer_address = null, growth_step = null) {
     const user_data_account_address = PublicKey.findProgramAddressSync([user_comptoken_token_account_address.toBytes()], compto_program_id_pubkey)[0];
+    let growth_keys = [];
+    if (payer_address !== null) {
+        growth_keys = [
+            // pays the rent if the user data account has to grow
+            { pubkey: payer_address, isSigner: true, isWritable: true },
+            // system account is used to fund the larger account
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ];
+    }
+    const growth_data = growth_step === null ? [] : bigintAsU64ToBytes(BigInt(growth_step));
     return new TransactionInstruction({
         programId: compto_program_id_pubkey,
         keys: [
@@ -77,26 +106,35 @@ export async function createProofSubmissionInstruction(comptoken_proof, user_wal
             { pubkey: user_data_account_address, isSigner: false, isWritable: true },
             // for the actual minting
             { pubkey: TOKEN_2022_PROGRAM_ID, isSigner: false, isWritable: false },
+            ...growth_keys,
         ], data: Buffer.from([
             Instruction.PROOF_SUBMISSION,
             ...comptoken_proof.serializeData(),
+            ...growth_data,
         ]),
     });
 }
 
 /**
- * @param {Connection} connection 
  * @param {PublicKey} payer 
+ * @param {PublicKey} upgrade_authority
+ * @param {bigint} day_boundary_offset seconds after midnight UTC+0 at which each day starts
  * @returns {TransactionInstruction}
  */
-export async function createInitializeComptokenProgramInstruction(connection, payer) {
+export async function createInitializeComptokenProgramInstruction(payer, upgrade_authority, day_boundary_offset = DAY_BOUNDARY_OFFSET) {
     return new TransactionInstruction({
         programId: compto_program_id_pubkey,
         keys: [
             // the payer of the rent for the account
             { pubkey: payer, isSigner: true, isWritable: true },
-            // the comptoken mint account
-            { pubkey: comptoken_mint_pubkey, isSigner: false, isWritable: false },
+            // only the comptoken program's upgrade authority may initialize it, and becomes the admin
+            { pubkey: upgrade_authority, isSigner: true, isWritable: false },
+            // the transfer hook records the comptoken program to check transfers against
+            { pubkey: compto_program_id_pubkey, isSigner: false, isWritable: false },
+            // knows the upgrade authority
+            { pubkey: compto_program_data_pubkey, isSigner: false, isWritable: false },
+            // the address of the comptoken mint to be created
+            { pubkey: comptoken_mint_pubkey, isSigner: false, isWritable: true },
             // the address of the global data account to be created
             { pubkey: global_data_account_pubkey, isSigner: false, isWritable: true },
             // the address of the interest bank account to be created
@@ -116,31 +154,27 @@ export async function createInitializeComptokenProgramInstruction(connection, pa
             // program will pull a recent hash from slothashes sysvar if a new valid blockhash is needed.
             { pubkey: SYSVAR_SLOT_HASHES_PUBKEY, isSigner: false, isWritable: false },
         ],
-        data: Buffer.from([
-            Instruction.INITIALIZE_COMPTOKEN_PROGRAM,
-            ...bigintAsU64ToBytes(BigInt(await connection.getMinimumBalanceForRentExemption(GlobalData.LAYOUT.span))),
-            ...bigintAsU64ToBytes(BigInt(await connection.getMinimumBalanceForRentExemption(COMPTOKEN_WALLET_SIZE))),
-            ...bigintAsU64ToBytes(BigInt(await connection.getMinimumBalanceForRentExemption(COMPTOKEN_WALLET_SIZE))),
-            ...bigintAsU64ToBytes(BigInt(await connection.getMinimumBalanceForRentExemption(COMPTOKEN_WALLET_SIZE))),
-        ]),
+        // the program sizes and funds the global data and the banks itself
+        data: Buffer.from([Instruction.INITIALIZE_COMPTOKEN_PROGRAM, ...bigintAsU64ToBytes(day_boundary_offset)]),
     });
 }
 
 /**
- * @param {Connection} connection
- * @param {Number} user_data_size
+ * @param {number} proof_capacity how many proofs the account can hold (at least 1). the program computes the rent
  * @param {PublicKey} payer_address
  * @param {PublicKey} user_wallet_address
  * @param {PublicKey} user_comptoken_token_account_address
  * @returns {TransactionInstruction}
  */
-export async function createCreateUserDataAccountInstruction(connection, user_data_size, payer_address, user_wallet_address, user_comptoken_token_account_address) {
+export async function createCreateUserDataAccountInstruction(proof_capacity, payer_address, user_wallet_address, user_comptoken_token_account_address) {
     const user_data_account_address = PublicKey.findProgramAddressSync([user_comptoken_token_account_address.toBytes()], compto_program_id_pubkey)[0];
     return new TransactionInstruction({
         programId: compto_program_id_pubkey,
         keys: [
             // the payer of the rent for the account
             { pubkey: payer_address, isSigner: true, isWritable: true },
+            // knows which mint the comptoken wallet must belong to
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: false },
             // the owner of the comptoken wallet
             { pubkey: user_wallet_address, isSigner: true, isWritable: false },
             // the payers comptoken wallet (comptoken token acct)
@@ -150,22 +184,29 @@ export async function createCreateUserDataAccountInstruction(connection, user_da
             // system account is used to create the account
             { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         ],
-        data: Buffer.from([
-            Instruction.CREATE_USER_DATA_ACCOUNT,
-            ...bigintAsU64ToBytes(BigInt(await connection.getMinimumBalanceForRentExemption(user_data_size))),
-            ...bigintAsU64ToBytes(BigInt(user_data_size)),
-        ]),
+        data: Buffer.from([Instruction.CREATE_USER_DATA_ACCOUNT, ...bigintAsU64ToBytes(BigInt(proof_capacity))]),
     });
 }
 
 /**
+ * @param {PublicKey} payer_address
+ * @param {BigInt} distribution_time the start of the day being distributed
+ * @param {PublicKey | null} caller_wallet
+ * @param {PublicKey | null} caller_comptoken_wallet
  * @returns {TransactionInstruction}
  */
-export async function createDailyDistributionEventInstruction() {
+export async function createDailyDistributionEventInstruction(payer_address, distribution_time, caller_wallet = null, caller_comptoken_wallet = null) {
+    // optional, the caller's accounts to receive the crank reward
+    const caller_keys = caller_wallet === null ? [] : [
+        { pubkey: caller_wallet, isSigner: true, isWritable: false },
+        { pubkey: caller_comptoken_wallet, isSigner: false, isWritable: true },
+    ];
     return new TransactionInstruction({
         programId: compto_program_id_pubkey,
         keys: [
-            // so the token program knows what kind of token
+            // the payer of the rent for the distribution record, and for any growth of the refreshed metadata
+            { pubkey: payer_address, isSigner: true, isWritable: true },
+            // so the token program knows what kind of token. also holds the metadata that is refreshed
             { pubkey: comptoken_mint_pubkey, isSigner: false, isWritable: true },
             // stores information for/from the daily distribution
             { pubkey: global_data_account_pubkey, isSigner: false, isWritable: true },
@@ -175,12 +216,15 @@ export async function createDailyDistributionEventInstruction() {
             { pubkey: verified_human_ubi_bank_account_pubkey, isSigner: false, isWritable: true },
             // comptoken token account used as bank for future ubi payouts
             { pubkey: future_ubi_bank_account_pubkey, isSigner: false, isWritable: true },
+            // permanent record of this distribution
+            { pubkey: get_distribution_record_pubkey(distribution_time), isSigner: false, isWritable: true },
+            // system account is used to create the distribution record
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
             // the token program that will mint the tokens when instructed by the mint authority
             { pubkey: TOKEN_2022_PROGRAM_ID, isSigner: false, isWritable: false },
             // program will pull a recent hash from slothashes sysvar if a new valid blockhash is needed.  
             { pubkey: SYSVAR_SLOT_HASHES_PUBKEY, isSigner: false, isWritable: false },
-            // comptoken token account used as bank for future ubi payouts
-            { pubkey: future_ubi_bank_account_pubkey, isSigner: false, isWritable: true },
+            ...caller_keys,
         ],
         data: Buffer.from([Instruction.DAILY_DISTRIBUTION_EVENT]),
     });
@@ -216,8 +260,8 @@ export async function createGetOwedComptokensInstruction(user_wallet_address, us
             { pubkey: compto_program_id_pubkey, isSigner: false, isWritable: false },
             //  Comptoken Mint lets the token program know what kind of token to move
             { pubkey: comptoken_mint_pubkey, isSigner: false, isWritable: false },
-            //  Comptoken Global Data (also mint authority) stores interest data
-            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: false },
+            //  Comptoken Global Data (also mint authority) stores interest data and bank liabilities
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: true },
             //  Comptoken Interest Bank stores comptokens owed for interest
             { pubkey: interest_bank_account_pubkey, isSigner: false, isWritable: true },
             //  Comptoken UBI Bank stores comptokens owed for UBI
@@ -236,6 +280,8 @@ export async function createGetOwedComptokensInstruction(user_wallet_address, us
             { pubkey: compto_transfer_hook_id_pubkey, isSigner: false, isWritable: false },
             //  stores account metas to add to transfer instructions
             { pubkey: compto_extra_account_metas_account_pubkey, isSigner: false, isWritable: false },
+            //  needed by the transfer hook program (may not exist yet)
+            { pubkey: exempt_accounts_account_pubkey, isSigner: false, isWritable: false },
             //  Token 2022 Program moves the tokens
             { pubkey: TOKEN_2022_PROGRAM_ID, isSigner: false, isWritable: false },
         ],
@@ -244,20 +290,37 @@ export async function createGetOwedComptokensInstruction(user_wallet_address, us
 }
 
 /**
- * @param {Connection} connection 
- * @param {Number} new_user_data_size 
+ * @param {PublicKey} user_wallet_address
+ * @param {PublicKey} user_comptoken_token_account_address
+ * @param {PublicKey} recipient_address
+ * @param {boolean} forfeit_verified_human
+ * @returns {TransactionInstruction}
+ */
+export async function createCloseUserDataAccountInstruction(user_wallet_address, user_comptoken_token_account_address, recipient_address, forfeit_verified_human = false) {
+    let instruction = await createGetOwedComptokensInstruction(user_wallet_address, user_comptoken_token_account_address);
+    //  receives the lamports of the closed User's Data Account
+    instruction.keys.push({ pubkey: recipient_address, isSigner: false, isWritable: true });
+    instruction.data = Buffer.from([Instruction.CLOSE_USER_DATA_ACCOUNT, forfeit_verified_human ? 1 : 0]);
+    return instruction;
+}
+
+/**
+ * grows by at most 10 KiB per instruction, so large capacities may need the instruction to be sent more than once
+ * @param {number} proof_capacity how many proofs the account should be able to hold
  * @param {PublicKey} payer_address 
  * @param {PublicKey} user_wallet_address 
  * @param {PublicKey} user_comptoken_wallet_address 
  * @returns {TransactionInstruction}
  */
-export async function createGrowUserDataAccountInstruction(connection, new_user_data_size, payer_address, user_wallet_address, user_comptoken_wallet_address) {
+export async function createGrowUserDataAccountInstruction(proof_capacity, payer_address, user_wallet_address, user_comptoken_wallet_address) {
     const user_data_account_address = PublicKey.findProgramAddressSync([user_comptoken_wallet_address.toBytes()], compto_program_id_pubkey)[0];
     return new TransactionInstruction({
         programId: compto_program_id_pubkey,
         keys: [
             // the payer of the rent for the account
             { pubkey: payer_address, isSigner: true, isWritable: true },
+            // knows which mint the comptoken wallet must belong to
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: false },
             // the owner of the comptoken wallet
             { pubkey: user_wallet_address, isSigner: true, isWritable: false },
             // the payers comptoken wallet (comptoken token acct)
@@ -267,11 +330,32 @@ export async function createGrowUserDataAccountInstruction(connection, new_user_
             // system account is used to create the account
             { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         ],
-        data: Buffer.from([
-            Instruction.GROW_USER_DATA_ACCOUNT,
-            ...bigintAsU64ToBytes(BigInt(await connection.getMinimumBalanceForRentExemption(new_user_data_size))),
-            ...bigintAsU64ToBytes(BigInt(new_user_data_size)),
-        ]),
+        data: Buffer.from([Instruction.GROW_USER_DATA_ACCOUNT, ...bigintAsU64ToBytes(BigInt(proof_capacity))]),
+    })
+}
+
+/**
+ * the rent freed by shrinking is refunded to the user's wallet
+ * @param {number} proof_capacity how many proofs the account should be able to hold
+ * @param {PublicKey} user_wallet_address 
+ * @param {PublicKey} user_comptoken_wallet_address 
+ * @returns {TransactionInstruction}
+ */
+export async function createShrinkUserDataAccountInstruction(proof_capacity, user_wallet_address, user_comptoken_wallet_address) {
+    const user_data_account_address = PublicKey.findProgramAddressSync([user_comptoken_wallet_address.toBytes()], compto_program_id_pubkey)[0];
+    return new TransactionInstruction({
+        programId: compto_program_id_pubkey,
+        keys: [
+            // knows which mint the comptoken wallet must belong to, and which proofs are still current
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: false },
+            // the owner of the comptoken wallet, which receives the freed rent
+            { pubkey: user_wallet_address, isSigner: true, isWritable: true },
+            // the payers comptoken wallet (comptoken token acct)
+            { pubkey: user_comptoken_wallet_address, isSigner: false, isWritable: false },
+            // the data account tied to the comptoken wallet
+            { pubkey: user_data_account_address, isSigner: false, isWritable: true },
+        ],
+        data: Buffer.from([Instruction.SHRINK_USER_DATA_ACCOUNT, ...bigintAsU64ToBytes(BigInt(proof_capacity))]),
     })
 }
 
@@ -305,9 +389,266 @@ export async function createVerifyHumanInstruction(user_wallet_address, user_com
             { pubkey: compto_transfer_hook_id_pubkey, isSigner: false, isWritable: false },
             //  stores account metas to add to transfer instructions
             { pubkey: compto_extra_account_metas_account_pubkey, isSigner: false, isWritable: false },
+            //  needed by the transfer hook program (may not exist yet)
+            { pubkey: exempt_accounts_account_pubkey, isSigner: false, isWritable: false },
             //  Token 2022 Program moves the tokens
             { pubkey: TOKEN_2022_PROGRAM_ID, isSigner: false, isWritable: false },
         ],
         data: Buffer.from([Instruction.VERIFY_HUMAN]),
     });
-}
\ No newline at end of file
+}
+
+/**
+ * @returns {TransactionInstruction}
+ */
+export async function createGetBankReconciliationInstruction() {
+    return new TransactionInstruction({
+        programId: compto_program_id_pubkey,
+        keys: [
+            //  Comptoken Global Data stores the liabilities of each bank
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: false },
+            //  Comptoken Interest Bank stores comptokens owed for interest
+            { pubkey: interest_bank_account_pubkey, isSigner: false, isWritable: false },
+            //  Comptoken UBI Bank stores comptokens owed for UBI
+            { pubkey: verified_human_ubi_bank_account_pubkey, isSigner: false, isWritable: false },
+            //  Comptoken Future UBI Bank stores comptokens owed to future verified humans
+            { pubkey: future_ubi_bank_account_pubkey, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([Instruction.GET_BANK_RECONCILIATION]),
+    });
+}
+
+/**
+ * @param {PublicKey} payer_address
+ * @returns {TransactionInstruction}
+ */
+export async function createMigrateGlobalDataInstruction(payer_address) {
+    return new TransactionInstruction({
+        programId: compto_program_id_pubkey,
+        keys: [
+            // the payer of any extra rent for the migrated account
+            { pubkey: payer_address, isSigner: true, isWritable: true },
+            //  Comptoken Global Data is the account to migrate
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: true },
+            // system account is used to pay any extra rent
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([Instruction.MIGRATE_GLOBAL_DATA]),
+    });
+}
+
+/**
+ * @param {PublicKey} payer_address
+ * @returns {TransactionInstruction}
+ */
+export async function createMigrateLegacyGlobalDataInstruction(payer_address) {
+    return new TransactionInstruction({
+        programId: compto_program_id_pubkey,
+        keys: [
+            // the payer of the extra rent for the migrated account
+            { pubkey: payer_address, isSigner: true, isWritable: true },
+            //  Comptoken Global Data is the account to migrate
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: true },
+            // the banks' balances are recorded as owed
+            { pubkey: interest_bank_account_pubkey, isSigner: false, isWritable: false },
+            { pubkey: verified_human_ubi_bank_account_pubkey, isSigner: false, isWritable: false },
+            { pubkey: future_ubi_bank_account_pubkey, isSigner: false, isWritable: false },
+            // system account is used to pay the extra rent
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            // the mint, which knows the transfer hook program
+            { pubkey: comptoken_mint_pubkey, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([Instruction.MIGRATE_LEGACY_GLOBAL_DATA]),
+    });
+}
+
+/**
+ * @param {PublicKey} payer_address
+ * @param {PublicKey} user_comptoken_token_account_address
+ * @returns {TransactionInstruction}
+ */
+export async function createMigrateUserDataInstruction(payer_address, user_comptoken_token_account_address) {
+    const user_data_account_address = PublicKey.findProgramAddressSync([user_comptoken_token_account_address.toBytes()], compto_program_id_pubkey)[0];
+    return new TransactionInstruction({
+        programId: compto_program_id_pubkey,
+        keys: [
+            // the payer of any extra rent for the migrated account (need not be the user)
+            { pubkey: payer_address, isSigner: true, isWritable: true },
+            // knows which mint the comptoken wallet must belong to
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: false },
+            // the comptoken wallet (comptoken token acct) the data account is tied to
+            { pubkey: user_comptoken_token_account_address, isSigner: false, isWritable: false },
+            // the data account to migrate
+            { pubkey: user_data_account_address, isSigner: false, isWritable: true },
+            // system account is used to pay any extra rent
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([Instruction.MIGRATE_USER_DATA]),
+    });
+}
+
+/**
+ * @param {PublicKey} admin_address
+ * @param {string} field "name", "symbol", "uri", or the key of a custom field
+ * @param {string} value
+ * @returns {TransactionInstruction}
+ */
+export async function createUpdateMetadataFieldInstruction(admin_address, field, value) {
+    // MAGIC NUMBER: remain consistent with token_metadata.rs
+    const field_data = { name: [0], symbol: [1], uri: [2] }[field]
+        ?? [3, Buffer.from(field).length, ...Buffer.from(field)];
+    return new TransactionInstruction({
+        programId: compto_program_id_pubkey,
+        keys: [
+            // only the admin recorded in the global data may update the metadata. pays for any extra rent
+            { pubkey: admin_address, isSigner: true, isWritable: true },
+            // the mint holds its own metadata
+            { pubkey: comptoken_mint_pubkey, isSigner: false, isWritable: true },
+            // the update authority of the metadata, and knows the admin
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: false },
+            // system account is used to pay any extra rent
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+            // the token program updates the metadata
+            { pubkey: TOKEN_2022_PROGRAM_ID, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([Instruction.UPDATE_METADATA_FIELD, ...field_data, ...Buffer.from(value)]),
+    });
+}
+
+/**
+ * @param {PublicKey} admin_address
+ * @param {string} key the key of the custom field to remove
+ * @returns {TransactionInstruction}
+ */
+export async function createRemoveMetadataKeyInstruction(admin_address, key) {
+    return new TransactionInstruction({
+        programId: compto_program_id_pubkey,
+        keys: [
+            // only the admin recorded in the global data may update the metadata
+            { pubkey: admin_address, isSigner: true, isWritable: false },
+            // the mint holds its own metadata
+            { pubkey: comptoken_mint_pubkey, isSigner: false, isWritable: true },
+            // the update authority of the metadata, and knows the admin
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: false },
+            // the token program updates the metadata
+            { pubkey: TOKEN_2022_PROGRAM_ID, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([Instruction.REMOVE_METADATA_KEY, ...Buffer.from(key)]),
+    });
+}
+
+/**
+ * @param {PublicKey} admin_address
+ * @param {ExtraAccountMeta[]} extra_account_metas the accounts the transfer hook is passed after the comptoken accounts
+ * @returns {TransactionInstruction}
+ */
+export async function createUpdateExtraAccountMetasInstruction(admin_address, extra_account_metas) {
+    return new TransactionInstruction({
+        programId: compto_program_id_pubkey,
+        keys: [
+            // only the admin recorded in the global data may change the transfer hook's accounts. pays for any extra rent
+            { pubkey: admin_address, isSigner: true, isWritable: true },
+            // the mint the extra account metas are for
+            { pubkey: comptoken_mint_pubkey, isSigner: false, isWritable: false },
+            // the mint authority, which the transfer hook requires to sign the update
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: false },
+            // the transfer hook program stores the extra account metas
+            { pubkey: compto_transfer_hook_id_pubkey, isSigner: false, isWritable: false },
+            // the account that stores the extra account metas
+            { pubkey: compto_extra_account_metas_account_pubkey, isSigner: false, isWritable: true },
+            // system account is used to pay any extra rent
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([
+            Instruction.UPDATE_EXTRA_ACCOUNT_METAS,
+            ...extra_account_metas.flatMap((extra_account_meta) => Array.from(extra_account_meta.toBytes())),
+        ]),
+    });
+}
+
+/**
+ * @param {PublicKey} payer_address
+ * @returns {TransactionInstruction}
+ */
+export async function createMigrateExtraAccountMetasInstruction(payer_address) {
+    return new TransactionInstruction({
+        programId: compto_program_id_pubkey,
+        keys: [
+            // anyone may migrate the extra account metas. pays for the extra rent
+            { pubkey: payer_address, isSigner: true, isWritable: true },
+            // the mint the extra account metas are for
+            { pubkey: comptoken_mint_pubkey, isSigner: false, isWritable: false },
+            // the mint authority, which the transfer hook requires to sign the update
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: false },
+            // the transfer hook program stores the extra account metas
+            { pubkey: compto_transfer_hook_id_pubkey, isSigner: false, isWritable: false },
+            // the account that stores the extra account metas
+            { pubkey: compto_extra_account_metas_account_pubkey, isSigner: false, isWritable: true },
+            // system account is used to pay the extra rent
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([Instruction.MIGRATE_EXTRA_ACCOUNT_METAS]),
+    });
+}
+
+/**
+ * @param {PublicKey} admin_address
+ * @param {PublicKey} account the comptoken token account to exempt from the transfer hook's checks
+ * @returns {TransactionInstruction}
+ */
+export async function createAddExemptAccountInstruction(admin_address, account) {
+    return new TransactionInstruction({
+        programId: compto_program_id_pubkey,
+        keys: [
+            // only the admin recorded in the global data may exempt accounts. pays for the extra rent
+            { pubkey: admin_address, isSigner: true, isWritable: true },
+            // knows the admin
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: false },
+            // stores the exempt accounts. created with the first exempt account
+            { pubkey: exempt_accounts_account_pubkey, isSigner: false, isWritable: true },
+            // system account is used to pay the extra rent
+            { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+        ],
+        data: Buffer.from([Instruction.ADD_EXEMPT_ACCOUNT, ...account.toBytes()]),
+    });
+}
+
+/**
+ * @param {PublicKey} admin_address
+ * @param {PublicKey} account the exempt comptoken token account to check again
+ * @returns {TransactionInstruction}
+ */
+export async function createRemoveExemptAccountInstruction(admin_address, account) {
+    return new TransactionInstruction({
+        programId: compto_program_id_pubkey,
+        keys: [
+            // only the admin recorded in the global data may remove exempt accounts. receives the freed rent
+            { pubkey: admin_address, isSigner: true, isWritable: true },
+            // knows the admin
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: false },
+            // stores the exempt accounts
+            { pubkey: exempt_accounts_account_pubkey, isSigner: false, isWritable: true },
+        ],
+        data: Buffer.from([Instruction.REMOVE_EXEMPT_ACCOUNT, ...account.toBytes()]),
+    });
+}
+
+/**
+ * @param {PublicKey} upgrade_authority
+ * @param {PublicKey} admin_address
+ * @returns {TransactionInstruction}
+ */
+export async function createSetAdminInstruction(upgrade_authority, admin_address) {
+    return new TransactionInstruction({
+        programId: compto_program_id_pubkey,
+        keys: [
+            // only the comptoken program's upgrade authority may choose the admin
+            { pubkey: upgrade_authority, isSigner: true, isWritable: false },
+            // knows the upgrade authority
+            { pubkey: compto_program_data_pubkey, isSigner: false, isWritable: false },
+            // records the admin
+            { pubkey: global_data_account_pubkey, isSigner: false, isWritable: true },
+        ],
+        data: Buffer.from([Instruction.SET_ADMIN, ...admin_address.toBytes()]),
+    });
+}
@@ -9,11 +9,9 @@ import {
 } from "@solana/web3.js";
 
 import {
-    AuthorityType,
     TOKEN_2022_PROGRAM_ID,
+    createAssociatedTokenAccountIdempotent,
     getAssociatedTokenAddressSync,
-    setAuthority,
-    unpackMint,
 } from '@solana/spl-token';
 
 import {
@@ -51,8 +49,8 @@ let connection = new Connection('http://localhost:8899', 'recent');
 
 (async () => {
     await airdrop(testUser_keypair.publicKey);
-    await setMintAuthorityIfNeeded();
     await createGlobalDataAccount();
+    await createTestUserTokenAccount();
     await testMint();
     await createUserDataAccount();
     let current_block = (await getValidBlockHashes()).current_block;
@@ -68,33 +66,6 @@ async function airdrop(pubkey) {
     console.log("Airdrop confirmed");
 }
 
-async function setMintAuthorityIfNeeded() {
-    const info = await connection.getAccountInfo(comptoken_mint_pubkey, "confirmed");
-    const unpackedMint = unpackMint(comptoken_mint_pubkey, info, TOKEN_2022_PROGRAM_ID);
-    if (unpackedMint.mintAuthority.toString() == global_data_account_pubkey.toString()) {
-        console.log("Mint Authority already set, skipping setAuthority Transaction");
-    } else {
-        console.log("Mint Authority not set, setting Authority");
-        await setMintAuthority(unpackedMint.mintAuthority);
-    }
-}
-
-async function setMintAuthority(current_mint_authority_pubkey) {
-    let me_signer = { publicKey: me_keypair.publicKey, secretKey: me_keypair.secretKey }
-    let new_mint_authority = global_data_account_pubkey;
-    const res = await setAuthority(
-        connection,
-        me_signer,
-        comptoken_mint_pubkey,
-        current_mint_authority_pubkey,
-        AuthorityType.MintTokens,
-        new_mint_authority,
-        undefined,
-        undefined,
-        TOKEN_2022_PROGRAM_ID
-    );
-}
-
 async function testMint() {
 
     let testMintTransaction = new Transaction();
@@ -109,16 +80,31 @@ async function createGlobalDataAccount() {
     let createGlobalDataAccountTransaction = new Transaction();
     createGlobalDataAccountTransaction
         .add(
-            await createInitializeComptokenProgramInstruction(connection, testUser_keypair.publicKey),
+            // the test user deployed the comptoken program, so is its upgrade authority
+            await createInitializeComptokenProgramInstruction(testUser_keypair.publicKey, testUser_keypair.publicKey),
         );
     let createGlobalDataAccountResult = await sendAndConfirmTransaction(connection, createGlobalDataAccountTransaction, [testUser_keypair, testUser_keypair]);
     console.log("createGlobalDataAccount transaction confirmed", createGlobalDataAccountResult);
 }
 
+// the comptoken mint is created by the comptoken program, so the token account can only be created after initialization
+async function createTestUserTokenAccount() {
+    let testUser_signer = { publicKey: testUser_keypair.publicKey, secretKey: testUser_keypair.secretKey };
+    await createAssociatedTokenAccountIdempotent(
+        connection,
+        testUser_signer,
+        comptoken_mint_pubkey,
+        testUser_keypair.publicKey,
+        undefined,
+        TOKEN_2022_PROGRAM_ID
+    );
+    console.log("createTestUserTokenAccount transaction confirmed");
+}
+
 async function createUserDataAccount() {
     let createUserDataAccountTransaction = new Transaction();
     createUserDataAccountTransaction.add(
-        await createCreateUserDataAccountInstruction(connection, 88, testUser_keypair.publicKey, testUser_keypair.publicKey, testuser_pubkey),
+        await createCreateUserDataAccountInstruction(1, testUser_keypair.publicKey, testUser_keypair.publicKey, testuser_pubkey),
     );
     let createUserDataAccountResult = await sendAndConfirmTransaction(connection, createUserDataAccountTransaction, [testUser_keypair]);
     console.log("createUserDataAccount transaction confirmed", createUserDataAccountResult);
@@ -4,25 +4,34 @@ import { Keypair, PublicKey, TransactionInstruction } from "@solana/web3.js";
 import {
     get_default_comptoken_mint,
     get_default_comptoken_token_account,
+    get_default_exempt_accounts_account,
     get_default_extra_account_metas_account,
     get_default_user_data_account,
     TokenAccount
 } from "../accounts.js";
 import { Assert } from "../assert.js";
-import { compto_program_id_pubkey, compto_transfer_hook_id_pubkey, COMPTOKEN_DECIMALS } from "../common.js";
+import {
+    compto_program_id_pubkey,
+    compto_transfer_hook_id_pubkey,
+    COMPTOKEN_DECIMALS,
+    exempt_accounts_account_pubkey,
+} from "../common.js";
 import { get_account, run_test, setup_test } from "../generic_test.js";
 
+// MAGIC NUMBER: remain consistent with `TransferHookError::RecipientWithoutUserData` in comptoken-transfer-hook.rs
+const RECIPIENT_WITHOUT_USER_DATA = 1000;
+
 async function test_execute() {
     const user1 = Keypair.generate();
     const comptoken_mint = get_default_comptoken_mint();
     let original_user1_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user1.publicKey);
     original_user1_comptoken_wallet.data.amount = 1n;
-    const user1_data_pda = PublicKey.findProgramAddressSync([original_user1_comptoken_wallet.address.toBytes()], compto_program_id_pubkey)[0];
-    const user1_data_account = get_default_user_data_account(user1_data_pda);
+    const [user1_data_pda, user1_data_bump] = PublicKey.findProgramAddressSync([original_user1_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    const user1_data_account = get_default_user_data_account(user1_data_pda, user1_data_bump);
 
     const original_user2_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), PublicKey.unique());
-    const user2_data_pda = PublicKey.findProgramAddressSync([original_user2_comptoken_wallet.address.toBytes()], compto_program_id_pubkey)[0];
-    const user2_data_account = get_default_user_data_account(user2_data_pda);
+    const [user2_data_pda, user2_data_bump] = PublicKey.findProgramAddressSync([original_user2_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    const user2_data_account = get_default_user_data_account(user2_data_pda, user2_data_bump);
 
     const accounts = [
         comptoken_mint, original_user1_comptoken_wallet, user1_data_account, original_user2_comptoken_wallet, user2_data_account,
@@ -45,6 +54,7 @@ async function test_execute() {
         { pubkey: compto_program_id_pubkey, isSigner: false, isWritable: false },
         { pubkey: user1_data_account.address, isSigner: false, isWritable: false },
         { pubkey: user2_data_account.address, isSigner: false, isWritable: false },
+        { pubkey: exempt_accounts_account_pubkey, isSigner: false, isWritable: false },
         // transfer hook program
         { pubkey: compto_transfer_hook_id_pubkey, isSigner: false, isWritable: false },
     ]
@@ -70,4 +80,120 @@ async function test_execute() {
     });
 }
 
-(async () => { await test_execute(); })();
\ No newline at end of file
+async function test_execute_exempt() {
+    const user1 = Keypair.generate();
+    const comptoken_mint = get_default_comptoken_mint();
+    let original_user1_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user1.publicKey);
+    original_user1_comptoken_wallet.data.amount = 1n;
+    const [user1_data_pda, user1_data_bump] = PublicKey.findProgramAddressSync([original_user1_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    const user1_data_account = get_default_user_data_account(user1_data_pda, user1_data_bump);
+
+    // an exempt account, e.g. an escrow, needs no user data account
+    const original_escrow_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), PublicKey.unique());
+    const escrow_data_pda = PublicKey.findProgramAddressSync([original_escrow_comptoken_wallet.address.toBytes()], compto_program_id_pubkey)[0];
+
+    const accounts = [
+        comptoken_mint, original_user1_comptoken_wallet, user1_data_account, original_escrow_comptoken_wallet,
+        get_default_extra_account_metas_account(), get_default_exempt_accounts_account([original_escrow_comptoken_wallet.address]),
+    ];
+
+    let context = await setup_test(accounts);
+
+    const keys = [
+        // transfer keys
+        { pubkey: original_user1_comptoken_wallet.address, isSigner: false, isWritable: true },
+        { pubkey: comptoken_mint.address, isSigner: false, isWritable: false },
+        { pubkey: original_escrow_comptoken_wallet.address, isSigner: false, isWritable: true },
+        { pubkey: user1.publicKey, isSigner: true, isWritable: false },
+        // transfer hook api keys
+        { pubkey: get_default_extra_account_metas_account().address, isSigner: false, isWritable: false },
+        // our transfer hook keys
+        { pubkey: compto_program_id_pubkey, isSigner: false, isWritable: false },
+        { pubkey: user1_data_account.address, isSigner: false, isWritable: false },
+        { pubkey: escrow_data_pda, isSigner: false, isWritable: false },
+        { pubkey: exempt_accounts_account_pubkey, isSigner: false, isWritable: false },
+        // transfer hook program
+        { pubkey: compto_transfer_hook_id_pubkey, isSigner: false, isWritable: false },
+    ]
+
+    const data = Buffer.alloc(transferCheckedInstructionData.span);
+    transferCheckedInstructionData.encode(
+        {
+            instruction: TokenInstruction.TransferChecked,
+            amount: 1n,
+            decimals: COMPTOKEN_DECIMALS,
+        },
+        data
+    );
+
+    let instructions = [new TransactionInstruction({ programId: TOKEN_2022_PROGRAM_ID, keys, data })];
+
+    context = await run_test("execute_exempt", context, instructions, [context.payer, user1], false, async (context, result) => {
+        const final_escrow_comptoken_wallet = await get_account(context, original_escrow_comptoken_wallet.address, TokenAccount);
+        Assert.assertEqual(final_escrow_comptoken_wallet.data.amount, 1n);
+    });
+}
+
+async function test_failExecuteRecipientWithoutUserData() {
+    const user1 = Keypair.generate();
+    const comptoken_mint = get_default_comptoken_mint();
+    let original_user1_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), user1.publicKey);
+    original_user1_comptoken_wallet.data.amount = 1n;
+    const [user1_data_pda, user1_data_bump] = PublicKey.findProgramAddressSync([original_user1_comptoken_wallet.address.toBytes()], compto_program_id_pubkey);
+    const user1_data_account = get_default_user_data_account(user1_data_pda, user1_data_bump);
+
+    // the recipient hasn't created its user data account yet
+    const original_user2_comptoken_wallet = get_default_comptoken_token_account(PublicKey.unique(), PublicKey.unique());
+    const user2_data_pda = PublicKey.findProgramAddressSync([original_user2_comptoken_wallet.address.toBytes()], compto_program_id_pubkey)[0];
+
+    const accounts = [
+        comptoken_mint, original_user1_comptoken_wallet, user1_data_account, original_user2_comptoken_wallet,
+        get_default_extra_account_metas_account(),
+    ];
+
+    let context = await setup_test(accounts);
+
+    const keys = [
+        // transfer keys
+        { pubkey: original_user1_comptoken_wallet.address, isSigner: false, isWritable: true },
+        { pubkey: comptoken_mint.address, isSigner: false, isWritable: false },
+        { pubkey: original_user2_comptoken_wallet.address, isSigner: false, isWritable: true },
+        { pubkey: user1.publicKey, isSigner: true, isWritable: false },
+        // transfer hook api keys
+        { pubkey: get_default_extra_account_metas_account().address, isSigner: false, isWritable: false },
+        // our transfer hook keys
+        { pubkey: compto_program_id_pubkey, isSigner: false, isWritable: false },
+        { pubkey: user1_data_account.address, isSigner: false, isWritable: false },
+        { pubkey: user2_data_pda, isSigner: false, isWritable: false },
+        { pubkey: exempt_accounts_account_pubkey, isSigner: false, isWritable: false },
+        // transfer hook program
+        { pubkey: compto_transfer_hook_id_pubkey, isSigner: false, isWritable: false },
+    ]
+
+    const data = Buffer.alloc(transferCheckedInstructionData.span);
+    transferCheckedInstructionData.encode(
+        {
+            instruction: TokenInstruction.TransferChecked,
+            amount: 1n,
+            decimals: COMPTOKEN_DECIMALS,
+        },
+        data
+    );
+
+    let instructions = [new TransactionInstruction({ programId: TOKEN_2022_PROGRAM_ID, keys, data })];
+
+    context = await run_test("failExecuteRecipientWithoutUserData", context, instructions, [context.payer, user1], true, async (context, result) => {
+        Assert.assert(
+            result.meta.logMessages.some((msg, i) => msg.includes(`custom program error: 0x${RECIPIENT_WITHOUT_USER_DATA.toString(16)}`)),
+            "the wallet is told the recipient needs a user data account"
+        );
+        const final_user2_comptoken_wallet = await get_account(context, original_user2_comptoken_wallet.address, TokenAccount);
+        Assert.assertEqual(final_user2_comptoken_wallet.data.amount, 0n);
+    });
+}
+
+(async () => {
+    await test_execute();
+    await test_execute_exempt();
+    await test_failExecuteRecipientWithoutUserData();
+})();
\ No newline at end of file
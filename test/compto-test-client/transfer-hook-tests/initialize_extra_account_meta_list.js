@@ -3,7 +3,7 @@ import { Keypair, SystemProgram, TransactionInstruction, } from "@solana/web3.js
 import { ExtraAccountMetaListLayout } from "@solana/spl-token";
 import { ExtraAccountMetaAccount, get_default_comptoken_mint, get_default_extra_account_metas_account } from "../accounts.js";
 import { Assert } from "../assert.js";
-import { compto_extra_account_metas_account_pubkey, compto_transfer_hook_id_pubkey, } from "../common.js";
+import { compto_extra_account_metas_account_pubkey, compto_program_id_pubkey, compto_transfer_hook_id_pubkey, } from "../common.js";
 import { get_account, run_test, setup_test } from "../generic_test.js";
 import { isArrayEqual } from "../utils.js";
 
@@ -27,6 +27,8 @@ async function test_initializeExtraAccountMetaList() {
         { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
         // the account who pays for the creation
         { pubkey: context.payer.publicKey, isSigner: true, isWritable: true },
+        // the comptoken program the transfers are checked against
+        { pubkey: compto_program_id_pubkey, isSigner: false, isWritable: false },
     ];
 
     // first 8 bytes of sha256 of "spl-transfer-hook-interface:execute"
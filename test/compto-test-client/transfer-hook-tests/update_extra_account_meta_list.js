@@ -0,0 +1,110 @@
+import { Keypair, SystemProgram, TransactionInstruction, } from "@solana/web3.js";
+
+import { ExtraAccountMetaLayout, ExtraAccountMetaListLayout } from "@solana/spl-token";
+import {
+    ExtraAccountMeta,
+    ExtraAccountMetaAccount,
+    get_default_comptoken_mint,
+    get_default_extra_account_metas_account,
+} from "../accounts.js";
+import { Assert } from "../assert.js";
+import { compto_extra_account_metas_account_pubkey, compto_transfer_hook_id_pubkey, } from "../common.js";
+import { get_account, run_test, setup_test } from "../generic_test.js";
+import { isArrayEqual } from "../utils.js";
+
+/**
+ * @param {PublicKey} mint_authority
+ * @param {PublicKey} payer
+ * @param {ExtraAccountMeta[]} extra_account_metas the accounts passed after the comptoken accounts
+ * @returns {TransactionInstruction}
+ */
+function createUpdateExtraAccountMetaListInstruction(mint_authority, payer, extra_account_metas) {
+    const keys = [
+        // the account that stores the extra account metas
+        { pubkey: compto_extra_account_metas_account_pubkey, isSigner: false, isWritable: true },
+        // the mint account associated with the transfer hook
+        { pubkey: get_default_comptoken_mint().address, isSigner: false, isWritable: false },
+        // the mint authority for the mint
+        { pubkey: mint_authority, isSigner: true, isWritable: false },
+        // the account who pays for any extra rent
+        { pubkey: payer, isSigner: true, isWritable: true },
+        // system account is used to pay any extra rent
+        { pubkey: SystemProgram.programId, isSigner: false, isWritable: false },
+    ];
+
+    // first 8 bytes of sha256 of "spl-transfer-hook-interface:update-extra-account-metas"
+    // see https://spl.solana.com/transfer-hook-interface/specification
+    let instruction_data = Buffer.from([157, 105, 42, 146, 102, 85, 241, 174]);
+    let extra_account_meta_list_data = Buffer.alloc(4 + extra_account_metas.length * ExtraAccountMetaLayout.span);
+    ExtraAccountMetaListLayout.encode({
+        count: extra_account_metas.length,
+        extraAccounts: extra_account_metas,
+    }, extra_account_meta_list_data);
+    let data = Buffer.concat([instruction_data, extra_account_meta_list_data]);
+
+    return new TransactionInstruction({ programId: compto_transfer_hook_id_pubkey, keys, data });
+}
+
+/**
+ * @returns {ExtraAccountMeta}
+ */
+function get_config_account_meta() {
+    return new ExtraAccountMeta({
+        discriminator: 0, // Literal
+        addressConfig: Keypair.generate().publicKey.toBytes(),
+        isSigner: false,
+        isWritable: false,
+    });
+}
+
+async function test_updateExtraAccountMetaList() {
+    const mint_authority = Keypair.generate();
+    let comptoken_mint = get_default_comptoken_mint();
+    comptoken_mint.data.mintAuthority = mint_authority.publicKey;
+    const default_account_meta_list = get_default_extra_account_metas_account();
+
+    let context = await setup_test([comptoken_mint, default_account_meta_list]);
+
+    const config_account_meta = get_config_account_meta();
+    let instructions = [
+        createUpdateExtraAccountMetaListInstruction(mint_authority.publicKey, context.payer.publicKey, [config_account_meta]),
+    ];
+
+    context = await run_test("updateExtraAccountMetaList", context, instructions, [context.payer, mint_authority], false, async (context, result) => {
+        const final_extra_account_meta_list_account = await get_account(context, compto_extra_account_metas_account_pubkey, ExtraAccountMetaAccount);
+        const final_extra_accounts = final_extra_account_meta_list_account.data.extraAccountsList.extraAccounts;
+        const oracle_extra_accounts = [...default_account_meta_list.data.extraAccountsList.extraAccounts, config_account_meta];
+        Assert.assertEqual(final_extra_accounts.length, oracle_extra_accounts.length, "the account is added");
+        for (const [final, oracle] of final_extra_accounts.map((v, i) => [v, oracle_extra_accounts[i]])) {
+            Assert.assertEqual(final.discriminator, oracle.discriminator, "discriminators aren't the same");
+            Assert.assertEqual(final.isSigner, oracle.isSigner, "isSigner isn't the same");
+            Assert.assertEqual(final.isWritable, oracle.isWritable, "isWritable isn't the same");
+            Assert.assert(isArrayEqual(final.addressConfig, oracle.addressConfig), "address configs aren't the same");
+        }
+    });
+}
+
+async function test_failUpdateExtraAccountMetaListNotMintAuthority() {
+    const not_mint_authority = Keypair.generate();
+
+    let context = await setup_test([get_default_comptoken_mint(), get_default_extra_account_metas_account()]);
+
+    let instructions = [
+        createUpdateExtraAccountMetaListInstruction(not_mint_authority.publicKey, context.payer.publicKey, [get_config_account_meta()]),
+    ];
+
+    context = await run_test("failUpdateExtraAccountMetaListNotMintAuthority", context, instructions, [context.payer, not_mint_authority], true, async (context, result) => {
+        Assert.assertNotNull(result.result, "program should fail");
+        const final_extra_account_meta_list_account = await get_account(context, compto_extra_account_metas_account_pubkey, ExtraAccountMetaAccount);
+        Assert.assertEqual(
+            final_extra_account_meta_list_account.data.extraAccountsList.extraAccounts.length,
+            get_default_extra_account_metas_account().data.extraAccountsList.extraAccounts.length,
+            "the extra account metas are unchanged"
+        );
+    });
+}
+
+(async () => {
+    await test_updateExtraAccountMetaList();
+    await test_failUpdateExtraAccountMetaListNotMintAuthority();
+})();
@@ -42,6 +42,16 @@ export function numAsU16ToLEBytes(num) {
     return Array.from({ length: 2 }, (v, i) => buffer.readUint8(i));
 }
 
+/**
+ * @param {number} num
+ * @returns {number[]}
+ */
+export function numAsU32ToLEBytes(num) {
+    let buffer = Buffer.alloc(4);
+    buffer.writeUInt32LE(num);
+    return Array.from({ length: 4 }, (v, i) => buffer.readUint8(i));
+}
+
 /**
  * @param {number} num
  * @returns {number[]}
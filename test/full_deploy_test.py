@@ -1,4 +1,3 @@
-import json
 import os
 import subprocess
 from contextlib import contextmanager
@@ -7,8 +6,6 @@ from time import sleep, time
 
 from common import *
 
-SPL_TOKEN_CMD = f"spl-token --program-id {TOKEN_2022_PROGRAM_ID}"
-
 @contextmanager
 def createTestValidator():
     with BackgroundProcess(
@@ -63,19 +60,11 @@ def getTransferHookProgramIdIfExists() -> str | None:
 def getTransferHookProgramId():
     return run(f"solana address -k {TRANSFER_HOOK_KEYPAIR}")
 
-def getGlobalData():
-    with open(COMPTO_GLOBAL_DATA_ACCOUNT_JSON, "r") as file:
-        return json.load(file).get("address")
-
-def createToken():
-    run(
-        f"{SPL_TOKEN_CMD} create-token -v --decimals {MINT_DECIMALS} --transfer-hook {getTransferHookProgramId()} --mint-authority {getGlobalData()} --output json {MINT_KEYPAIR} > {COMPTOKEN_MINT_JSON}"
-    )
-
-def createComptoAccount():
+# the test user's comptoken account is created by the test client, since the mint only exists once the comptoken
+# program is initialized
+def createTestUser():
     generateTestUser()
     run(f"solana airdrop 100 {getPubkey(TEST_USER_ACCOUNT_JSON)}")
-    run(f"{SPL_TOKEN_CMD} create-account {getTokenAddress()} --owner {TEST_USER_ACCOUNT_JSON}")
 
 def getPubkey(path: Path) -> str:
     return run(f"solana-keygen pubkey {path}")
@@ -83,27 +72,19 @@ def getPubkey(path: Path) -> str:
 def getAccountBalance(pubkey: str):
     return run(f"solana balance {pubkey}")
 
+# the test user is the upgrade authority, so that the test client can initialize the program
 def deployCompto():
     print("Deploying Compto...")
-    run(f"solana program deploy -v {COMPTO_SO} --output json > {COMPTO_PROGRAM_ID_JSON}")
+    run(
+        f"solana program deploy -v {COMPTO_SO} --upgrade-authority {TEST_USER_ACCOUNT_JSON} "
+        f"--output json > {COMPTO_PROGRAM_ID_JSON}"
+    )
     print("Deployed Compto")
 
 def deployTransferHook():
     print("Deploying Transfer Hook...")
     run(f"solana program deploy -v {TRANSFER_HOOK_SO} --output json > {COMPTO_TRANSFER_HOOK_ID_JSON}")
     print("Deployed Transfer Hook")
-    # TODO: find a more efficient way to do this
-    try:
-        json.loads(run(f"{SPL_TOKEN_CMD} display {getTokenAddress()} --output json")).get("MintAuthority")
-        return True
-    except (FileNotFoundError, SubprocessFailedException, json.decoder.JSONDecodeError):
-        return False
-    except Exception as ex:
-        print(f"new Exception: Type:`{type(ex)}' value: `{ex}'")
-        raise ex
-
-def getTokenAddress():
-    return run(f"solana address -k {MINT_KEYPAIR}")
 
 def checkSolanaConfig():
     result = run("solana config get")
@@ -131,44 +112,34 @@ if __name__ == "__main__":
     args = parseArgs()
     # create cache if it doesn't exist
     generateDirectories(args=argparse.Namespace(log_directory=None, verbose=0))
-    # If ProgramId doesn't exist, we need to build WITHOUT the testmode feature.
-    # This is because the static seed in testmode depends on ProgramId and ProgramId
-    # is generated on the first build.
+    # the programs don't depend on their ids, so the keypairs only decide where they are deployed
     print("Checking if Comptoken ProgramId exists...")
     comptokenProgramId = getComptoProgramIdIfExists()
     if comptokenProgramId is None:
         print("Creating Comptoken ProgramId...")
         createKeyPair(COMPTO_KEYPAIR)
-        #run("cargo build-sbf", COMPTOKEN_SRC_PATH)
         comptokenProgramId = getComptoProgramId()
 
     transferHookId = getTransferHookProgramIdIfExists()
     if transferHookId is None:
         print("Creating Transfer Hook ProgramId...")
         createKeyPair(TRANSFER_HOOK_KEYPAIR)
-        #run("cargo build-sbf", TRANSFER_HOOK_SRC_PATH)
         transferHookId = getTransferHookProgramId()
     checkSolanaConfig()
     print("Creating Validator...")
     with createTestValidator() as validator:
-        print("Checking Compto Program for hardcoded Comptoken Address and static seed...")
-
         if args.generate:
-            createKeyPair(MINT_KEYPAIR)
-            mintAddress = getTokenAddress()
-            generateFiles(comptokenProgramId, transferHookId, mintAddress)
-
-        createToken()
+            generateFiles(comptokenProgramId, transferHookId)
 
         if args.build:
             buildTransferHook()
             buildCompto()
 
+        print("Creating Test User...")
+        createTestUser()
+
         deployTransferHook()
         deployCompto()
-
-        print("Creating Token Account...")
-        createComptoAccount()
         print("Running Test Client...")
         output = runTestClient()
         print(output)